@@ -128,6 +128,10 @@ impl fmt::Debug for PollOpt {
             }
         }
 
+        if !one {
+            try!(write!(fmt, "(empty)"));
+        }
+
         Ok(())
     }
 }
@@ -272,6 +276,10 @@ impl fmt::Debug for EventSet {
             }
         }
 
+        if !one {
+            try!(write!(fmt, "(empty)"));
+        }
+
         Ok(())
     }
 }
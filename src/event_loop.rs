@@ -1,51 +1,152 @@
-use {Handler, Evented, Poll, NotifyError, Token};
+use {Handler, Evented, Poll, NotifyError, Recorded, Recorder, Registered, Registration, Token};
 use event::{IoEvent, EventSet, PollOpt};
+#[cfg(feature = "testing")]
+use faults::Faults;
+use instrument;
 use notify::Notify;
-use timer::{Timer, Timeout, TimerResult};
+#[cfg(feature = "metrics")]
+use metrics::Metrics;
+#[cfg(feature = "metrics")]
+use poll::PollStats;
+use timer::{Clock, MonotonicClock, Timer, Timeout, TimerResult};
+#[cfg(target_os = "linux")]
+use sys::TimerFd;
+use std::collections::{HashMap, VecDeque};
 use std::default::Default;
+use std::io::BufRead;
 use std::{io, fmt, thread, usize};
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "metrics")]
+use std::cmp;
+use std::time::{Duration, Instant};
 
 /// Configure EventLoop runtime details
 #[derive(Copy, Clone, Debug)]
 pub struct EventLoopConfig {
     pub io_poll_timeout_ms: usize,
+    /// Maximum number of IO events dispatched per iteration; further ready
+    /// handles are reported on the next one. `EventLoop::configured` fails
+    /// if set to zero.
+    pub io_events_capacity: usize,
+    /// Busy-poll for up to this long before blocking for IO, see
+    /// `Poll::set_spin`. Off by default.
+    pub io_poll_spin: Option<Duration>,
+    /// Maximum number of `Handler::ready` calls per iteration. Events over
+    /// the budget are queued and dispatched first on the next iteration, so
+    /// busy handles cannot keep others waiting. Unlimited by default.
+    ///
+    /// `EventLoop::configured` fails if set to zero.
+    pub io_dispatch_budget: Option<usize>,
+    /// Maximum number of `Handler::ready` calls per token and iteration,
+    /// counting every handle registered under the token. Further events for
+    /// the token are queued like those over `io_dispatch_budget`. Unlimited
+    /// by default; `EventLoop::configured` fails if set to zero.
+    pub io_dispatch_per_token: Option<usize>,
 
     // == Notifications ==
     pub notify_capacity: usize,
     pub messages_per_tick: usize,
 
     // == Timer ==
-    pub timer_tick_ms: u64,
+    /// Timer resolution. Timeouts fire on the first tick at or after their
+    /// deadline.
+    pub timer_tick: Duration,
+    /// Number of slots in the timer wheel. Timeouts further out than
+    /// `timer_tick * timer_wheel_size` wait in an overflow heap until they
+    /// come within range.
     pub timer_wheel_size: usize,
+    /// Maximum number of pending timeouts, allocated up front.
     pub timer_capacity: usize,
+    /// Wake up for timer ticks with a `timerfd` rather than the poll
+    /// timeout, which is limited to millisecond precision. Only supported on
+    /// Linux with the default clock; ignored elsewhere.
+    pub timer_precise: bool,
+
+    // == Handler ==
+    /// Catch panics in `Handler::ready` and report them to
+    /// `Handler::panicked` rather than unwinding out of the event loop. The
+    /// handles registered under the panicking token are deregistered. Off by
+    /// default.
+    pub catch_panics: bool,
 }
 
 impl Default for EventLoopConfig {
     fn default() -> EventLoopConfig {
         EventLoopConfig {
             io_poll_timeout_ms: 1_000,
+            io_events_capacity: 1_024,
+            io_poll_spin: None,
+            io_dispatch_budget: None,
+            io_dispatch_per_token: None,
             notify_capacity: 4_096,
             messages_per_tick: 256,
-            timer_tick_ms: 100,
+            timer_tick: Duration::from_millis(100),
             timer_wheel_size: 1_024,
             timer_capacity: 65_536,
+            timer_precise: false,
+            catch_panics: false,
         }
     }
 }
 
+/// What happened during a single event loop iteration, as reported by
+/// [EventLoop::run_once](struct.EventLoop.html#method.run_once)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunStats {
+    /// IO events dispatched to `Handler::ready`
+    pub events: usize,
+    /// Messages dispatched to `Handler::notify`
+    pub messages: usize,
+    /// Expired timeouts handed to the handler
+    pub timeouts: usize,
+}
+
+impl RunStats {
+    /// Returns true if nothing was dispatched
+    pub fn is_idle(&self) -> bool {
+        self.events == 0 && self.messages == 0 && self.timeouts == 0
+    }
+}
+
 /// Single threaded IO event loop.
 #[derive(Debug)]
 pub struct EventLoop<H: Handler> {
     run: bool,
     poll: Poll,
-    timer: Timer<H::Timeout>,
+    // Boxed, like `metrics`, to keep the event loop compact
+    timer: Box<Timer<H::Timeout>>,
+    // Tick up to which expired timeouts are being delivered
+    timer_now: u64,
+    // First timeout of the batch, taken to check whether the batch is empty
+    timer_head: Option<H::Timeout>,
+    // Timeouts handed out during the current iteration
+    timer_fired: usize,
+    // Armed with the next tick's deadline when `timer_precise` is set
+    #[cfg(target_os = "linux")]
+    timer_fd: Option<TimerFd>,
     notify: Notify<H::Message>,
+    // Ready events carried over to the next iteration, in dispatch order
+    deferred: VecDeque<IoEvent>,
+    // Dispatches per token in the current iteration, see
+    // `io_dispatch_per_token`
+    dispatch_counts: HashMap<Token, usize>,
+    // Tokens whose handler panicked, see `catch_panics`
+    poisoned: Vec<Token>,
+    // Logs dispatched IO events, see `set_recorder`
+    recorder: Option<Box<Recorder>>,
+    // Boxed to keep the event loop compact
+    #[cfg(feature = "metrics")]
+    metrics: Box<Metrics>,
     config: EventLoopConfig,
 }
 
 // Token used to represent notifications
 const NOTIFY: Token = Token(usize::MAX);
 
+// Token used to represent timer wakeups
+#[cfg(target_os = "linux")]
+const TIMER: Token = Token(usize::MAX - 1);
+
 impl<H: Handler> EventLoop<H> {
 
     /// Initializes a new event loop using default configuration settings. The
@@ -55,14 +156,44 @@ impl<H: Handler> EventLoop<H> {
     }
 
     pub fn configured(config: EventLoopConfig) -> io::Result<EventLoop<H>> {
+        EventLoop::build(config, Box::new(MonotonicClock), true)
+    }
+
+    /// Initializes a new event loop whose timer reads the current time from
+    /// `clock`, e.g. a `ManualClock` in tests.
+    ///
+    /// `EventLoopConfig::timer_precise` is ignored, as the `timerfd` is
+    /// armed with deadlines on the system's monotonic clock.
+    pub fn configured_with_clock(config: EventLoopConfig, clock: Box<Clock>) -> io::Result<EventLoop<H>> {
+        EventLoop::build(config, clock, false)
+    }
+
+    #[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+    fn build(config: EventLoopConfig, clock: Box<Clock>, default_clock: bool) -> io::Result<EventLoop<H>> {
+        if config.io_dispatch_budget == Some(0) || config.io_dispatch_per_token == Some(0) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "IO dispatch limits must be non-zero"));
+        }
+
+        if config.io_events_capacity == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "IO events capacity must be non-zero"));
+        }
+
         // Create the IO poller
-        let mut poll = try!(Poll::new());
+        let mut poll = try!(Poll::with_capacity(config.io_events_capacity));
+        poll.set_spin(config.io_poll_spin);
+
+        // Tokens are needed to drop the queued events of deregistered
+        // handles and to deregister the handles of a panicking token
+        if config.io_dispatch_budget.is_some() || config.io_dispatch_per_token.is_some() || config.catch_panics {
+            poll.track_tokens();
+        }
 
         // Create the timer
-        let mut timer = Timer::new(
-            config.timer_tick_ms,
+        let mut timer = Timer::with_clock(
+            config.timer_tick,
             config.timer_wheel_size,
-            config.timer_capacity);
+            config.timer_capacity,
+            clock);
 
         // Create cross thread notification queue
         let notify = try!(Notify::with_capacity(config.notify_capacity));
@@ -73,11 +204,31 @@ impl<H: Handler> EventLoop<H> {
         // Set the timer's starting time reference point
         timer.setup();
 
+        #[cfg(target_os = "linux")]
+        let timer_fd = if config.timer_precise && default_clock {
+            let timer_fd = try!(TimerFd::new());
+            try!(poll.register(&timer_fd, TIMER, EventSet::readable(), PollOpt::edge()));
+            Some(timer_fd)
+        } else {
+            None
+        };
+
         Ok(EventLoop {
             run: true,
             poll: poll,
-            timer: timer,
+            timer: Box::new(timer),
+            timer_now: 0,
+            timer_head: None,
+            timer_fired: 0,
+            #[cfg(target_os = "linux")]
+            timer_fd: timer_fd,
             notify: notify,
+            deferred: VecDeque::new(),
+            dispatch_counts: HashMap::new(),
+            poisoned: Vec::new(),
+            recorder: None,
+            #[cfg(feature = "metrics")]
+            metrics: Box::new(Metrics::default()),
             config: config,
         })
     }
@@ -167,14 +318,55 @@ impl<H: Handler> EventLoop<H> {
         self.timer.timeout_ms(token, delay)
     }
 
+    /// Like [#timeout_ms](#method.timeout_ms), with the interval given as a
+    /// `Duration`.
+    pub fn timeout(&mut self, token: H::Timeout, delay: Duration) -> TimerResult<Timeout> {
+        self.timer.timeout(token, delay)
+    }
+
+    /// Schedules a timeout to fire at the given monotonic `deadline`.
+    /// Deadlines that have already passed fire on the next timer tick.
+    pub fn timeout_at(&mut self, token: H::Timeout, deadline: Instant) -> TimerResult<Timeout> {
+        self.timer.timeout_at(token, deadline)
+    }
+
     /// If the supplied timeout has not been triggered, cancel it such that it
     /// will not be triggered in the future.
+    ///
+    /// Clearing is O(1). Handles of timeouts that have already fired or been
+    /// cleared are ignored, so it is always safe to call.
     pub fn clear_timeout(&mut self, timeout: Timeout) -> bool {
         self.timer.clear(timeout)
     }
 
+    /// Returns the next timeout that expired as of the current event loop
+    /// tick, for use from
+    /// [Handler::timeouts](trait.Handler.html#method.timeouts).
+    pub fn next_timeout(&mut self) -> Option<H::Timeout> {
+        let timeout = self.timer_head.take().or_else(|| self.timer.tick_to(self.timer_now));
+
+        if timeout.is_some() {
+            self.timer_fired += 1;
+        }
+
+        timeout
+    }
+
+    /// Moves every timeout that expired as of the current event loop tick
+    /// into `dst`, returning how many were added.
+    pub fn expired_timeouts(&mut self, dst: &mut Vec<H::Timeout>) -> usize {
+        let len = dst.len();
+
+        while let Some(t) = self.next_timeout() {
+            dst.push(t);
+        }
+
+        dst.len() - len
+    }
+
     /// Tells the event loop to exit after it is done handling all events in the
-    /// current iteration.
+    /// current iteration. Use [Sender::shutdown](struct.Sender.html#method.shutdown)
+    /// to stop the event loop from another thread.
     pub fn shutdown(&mut self) {
         self.run = false;
     }
@@ -189,21 +381,72 @@ impl<H: Handler> EventLoop<H> {
     pub fn register<E: ?Sized>(&mut self, io: &E, token: Token) -> io::Result<()>
         where E: Evented
     {
-        self.poll.register(io, token, EventSet::all(), PollOpt::level())
+        self.register_opt(io, token, EventSet::all(), PollOpt::level())
     }
 
     /// Registers an IO handle with the event loop.
     pub fn register_opt<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opt: PollOpt) -> io::Result<()>
         where E: Evented
     {
-        self.poll.register(io, token, interest, opt)
+        try!(self.poll.register(io, token, interest, opt));
+        self.unpoison(token);
+        Ok(())
+    }
+
+    /// Registers an IO handle with the event loop, taking ownership of it.
+    /// The returned guard deregisters the handle when dropped.
+    pub fn register_owned<E>(&mut self, io: E, token: Token, interest: EventSet, opt: PollOpt) -> io::Result<Registered<E>>
+        where E: Evented
+    {
+        let io = try!(self.poll.register_owned(io, token, interest, opt));
+        self.unpoison(token);
+        Ok(io)
+    }
+
+    fn unpoison(&mut self, token: Token) {
+        if !self.poisoned.is_empty() {
+            self.poisoned.retain(|t| *t != token);
+        }
     }
 
     /// Re-Registers an IO handle with the event loop.
     pub fn reregister<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opt: PollOpt) -> io::Result<()>
         where E: Evented
     {
-        self.poll.reregister(io, token, interest, opt)
+        try!(self.poll.reregister(io, token, interest, opt));
+        self.unpoison(token);
+        Ok(())
+    }
+
+    /// Activity counters accumulated since the event loop was created or the
+    /// last `reset_metrics`
+    #[cfg(feature = "metrics")]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Resets the event loop's metrics and its poller's statistics
+    #[cfg(feature = "metrics")]
+    pub fn reset_metrics(&mut self) {
+        *self.metrics = Metrics::default();
+        self.poll.reset_stats();
+    }
+
+    /// Returns the counters kept by the event loop's poller, see
+    /// `Poll::stats`
+    #[cfg(feature = "metrics")]
+    pub fn poll_stats(&self) -> &PollStats {
+        self.poll.stats()
+    }
+
+    /// Calls `Handler::ready` for `token` with `events` again on the next
+    /// iteration, after the handles already waiting for their turn.
+    ///
+    /// A handler serving an edge triggered handle can stop draining it after
+    /// doing its share of work and defer the rest, so that one busy
+    /// connection does not starve the others.
+    pub fn defer_ready(&mut self, token: Token, events: EventSet) {
+        self.defer(IoEvent::new(events, token));
     }
 
     /// Keep spinning the event loop indefinitely, and notify the handler whenever
@@ -213,21 +456,112 @@ impl<H: Handler> EventLoop<H> {
 
         while self.run {
             // Execute ticks as long as the event loop is running
-            try!(self.run_once(handler));
+            try!(self.run_once(handler, None));
         }
 
         Ok(())
     }
 
-    /// Deregisters an IO handle with the event loop.
+    /// Deregisters an IO handle with the event loop. Events queued for its
+    /// token are dropped once no other handle is registered under it.
     pub fn deregister<E: ?Sized>(&mut self, io: &E) -> io::Result<()> where E: Evented {
-        self.poll.deregister(io)
+        let res = self.poll.deregister(io);
+        self.purge_deferred();
+        res
     }
 
-    /// Spin the event loop once, with a timeout of one second, and notify the
-    /// handler if any of the registered handles become ready during that
-    /// time.
-    pub fn run_once(&mut self, handler: &mut H) -> io::Result<()> {
+    /// Deregisters a batch of IO handles, see `Poll::deregister_all`
+    pub fn deregister_all<E: ?Sized>(&mut self, ios: &[&E]) -> io::Result<()> where E: Evented {
+        let res = self.poll.deregister_all(ios);
+        self.purge_deferred();
+        res
+    }
+
+    // Drops queued events for tokens that no handle is registered under
+    fn purge_deferred(&mut self) {
+        if self.deferred.is_empty() {
+            return;
+        }
+
+        let poll = &self.poll;
+        self.deferred.retain(|queued| poll.has_token(queued.token).unwrap_or(true));
+    }
+
+    /// Injects faults into the event loop's polling, see `Poll::set_faults`.
+    /// Injected interrupts are reported through `Handler::interrupted`.
+    #[cfg(feature = "testing")]
+    pub fn set_faults(&mut self, faults: Option<Faults>) {
+        self.poll.set_faults(faults);
+    }
+
+    /// Queues a synthetic event for `token`, dispatched by the next
+    /// iteration, see `Poll::inject`
+    pub fn inject(&mut self, token: Token, events: EventSet) {
+        self.poll.inject(token, events);
+    }
+
+    /// Logs every IO event subsequently delivered to `Handler::ready`, or
+    /// stops logging when `None` is given. The previous recorder, if any, is
+    /// flushed and returned.
+    ///
+    /// Recording stops if writing to the recorder fails.
+    pub fn set_recorder(&mut self, recorder: Option<Recorder>) -> Option<Recorder> {
+        let prev = self.recorder.take().map(|rec| *rec);
+        self.recorder = recorder.map(Box::new);
+
+        prev.map(|mut rec| {
+            if let Err(err) = rec.flush() {
+                warn!("failed to flush event recorder; err={:?}", err);
+            }
+            rec
+        })
+    }
+
+    /// Delivers the IO events logged by a `Recorder` to `handler` again, in
+    /// order and without delay, returning how many were delivered.
+    ///
+    /// No polling takes place, so the handler sees exactly the recorded
+    /// sequence. Handles it touches should be stand-ins such as
+    /// `util::MockStream` (`testing` feature) scripted to match the recorded
+    /// run. Replaying stops early once the event loop is shut down, so
+    /// nothing is delivered if it already was.
+    pub fn replay<R: BufRead>(&mut self, handler: &mut H, src: R) -> io::Result<usize> {
+        let mut cnt = 0;
+
+        for line in src.lines() {
+            if !self.run {
+                break;
+            }
+
+            let line = try!(line);
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let rec: Recorded = try!(line.parse());
+
+            self.io_event(handler, IoEvent::new(rec.events, rec.token));
+            cnt += 1;
+        }
+
+        Ok(cnt)
+    }
+
+    /// Lists the registered IO handles, see `Poll::registrations`. The
+    /// event loop's internal handles are included.
+    pub fn registrations(&self) -> io::Result<Vec<Registration>> {
+        self.poll.registrations()
+    }
+
+    /// Spin the event loop once and notify the handler if any of the
+    /// registered handles become ready, messages arrive or timeouts expire.
+    ///
+    /// The wait for IO is bounded by the next timer tick,
+    /// `EventLoopConfig::io_poll_timeout_ms` and, if given, `timeout`. Pass
+    /// `Some(Duration::from_millis(0))` to only handle what is already
+    /// pending, e.g. when embedding the event loop in another one.
+    pub fn run_once(&mut self, handler: &mut H, timeout: Option<Duration>) -> io::Result<RunStats> {
         let mut messages;
 
         trace!("event loop tick");
@@ -235,13 +569,19 @@ impl<H: Handler> EventLoop<H> {
         // Check the notify channel for any pending messages. If there are any,
         // avoid blocking when polling for IO events. Messages will be
         // processed after IO events.
+        self.record_notify_depth();
         messages = self.notify.check(self.config.messages_per_tick, true);
-        let pending = messages > 0;
+        let pending = messages > 0 || !self.deferred.is_empty();
 
         // Check the registered IO handles for any new events. Each poll
         // is for one second, so a shutdown request can last as long as
         // one second before it takes effect.
-        let events = match self.io_poll(pending) {
+        let max_sleep = timeout.map(|t| {
+            let ms = t.as_secs() * 1_000 + (t.subsec_nanos() as u64 + 999_999) / 1_000_000;
+            ms as usize
+        });
+
+        let events = match self.io_poll(pending, max_sleep) {
             Ok(e) => e,
             Err(err) => {
                 if err.kind() == io::ErrorKind::Interrupted {
@@ -260,30 +600,114 @@ impl<H: Handler> EventLoop<H> {
             messages += self.notify.check(remaining, false);
         }
 
-        self.io_process(handler, events);
+        self.timer_fired = 0;
+
+        let events = self.io_process(handler, events);
         self.notify(handler, messages);
         self.timer_process(handler);
-        Ok(())
+        handler.tick(self);
+
+        if self.notify.take_shutdown() {
+            self.run = false;
+        }
+
+        instrument::tick_end(events, messages, self.timer_fired, self.deferred.len());
+
+        Ok(RunStats {
+            events: events,
+            messages: messages,
+            timeouts: self.timer_fired,
+        })
+    }
+
+    #[cfg(feature = "metrics")]
+    fn io_poll(&mut self, immediate: bool, max_sleep: Option<usize>) -> io::Result<usize> {
+        let start = Instant::now();
+        let res = self.io_wait(immediate, max_sleep);
+
+        if let Ok(cnt) = res {
+            let elapsed = start.elapsed();
+            let ns = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+            let metrics = &mut self.metrics;
+
+            metrics.polls += 1;
+            metrics.events += cnt as u64;
+            metrics.max_events_per_poll = cmp::max(metrics.max_events_per_poll, cnt as u64);
+            metrics.poll_ns += ns;
+            metrics.max_poll_ns = cmp::max(metrics.max_poll_ns, ns);
+        }
+
+        res
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    fn io_poll(&mut self, immediate: bool, max_sleep: Option<usize>) -> io::Result<usize> {
+        self.io_wait(immediate, max_sleep)
     }
 
     #[inline]
-    fn io_poll(&mut self, immediate: bool) -> io::Result<usize> {
+    fn io_wait(&mut self, immediate: bool, max_sleep: Option<usize>) -> io::Result<usize> {
         if immediate {
             self.poll.poll(0)
         } else {
-            let mut sleep = self.timer.next_tick_in_ms() as usize;
+            let mut sleep = try!(self.timer_sleep_ms());
 
             if sleep > self.config.io_poll_timeout_ms {
                 sleep = self.config.io_poll_timeout_ms;
             }
 
+            if let Some(max) = max_sleep {
+                if sleep > max {
+                    sleep = max;
+                }
+            }
+
             self.poll.poll(sleep)
         }
     }
 
-    // Process IO events that have been previously polled
-    fn io_process(&mut self, handler: &mut H, cnt: usize) {
+    // Returns how long the poll may block for the timer's sake
+    #[cfg(target_os = "linux")]
+    fn timer_sleep_ms(&mut self) -> io::Result<usize> {
+        if let Some(ref timer_fd) = self.timer_fd {
+            try!(timer_fd.set_deadline_ns(self.timer.next_tick_at_ns()));
+            return Ok(self.config.io_poll_timeout_ms);
+        }
+
+        Ok(self.timer.next_tick_in_ms() as usize)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn timer_sleep_ms(&mut self) -> io::Result<usize> {
+        Ok(self.timer.next_tick_in_ms() as usize)
+    }
+
+    // Process IO events that have been previously polled, returning how many
+    // were dispatched to the handler
+    fn io_process(&mut self, handler: &mut H, cnt: usize) -> usize {
+        let budget = self.config.io_dispatch_budget.unwrap_or(usize::MAX);
         let mut i = 0;
+        let mut dispatched = 0;
+
+        self.dispatch_counts.clear();
+
+        // Events carried over from the previous iteration go first. Events
+        // deferred while processing them wait for the next iteration.
+        for _ in 0..self.deferred.len() {
+            if dispatched == budget {
+                break;
+            }
+
+            let evt = self.deferred.pop_front().unwrap();
+
+            if self.take_turn(evt.token) {
+                self.io_event(handler, evt);
+                dispatched += 1;
+            } else {
+                self.defer(evt);
+            }
+        }
 
         // Iterate over the notifications. Each event provides the token
         // it was registered with (which usually represents, at least, the
@@ -296,15 +720,122 @@ impl<H: Handler> EventLoop<H> {
 
             match evt.token {
                 NOTIFY => self.notify.cleanup(),
-                _ => self.io_event(handler, evt)
+                _ => {
+                    if !self.timer_event(evt.token) {
+                        if dispatched < budget && self.take_turn(evt.token) {
+                            self.io_event(handler, evt);
+                            dispatched += 1;
+                        } else {
+                            self.defer(evt);
+                        }
+                    }
+                }
             }
 
             i += 1;
         }
+
+        dispatched
+    }
+
+    // Returns true if the event was the timer wakeup. The expired timeouts
+    // themselves are handled by `timer_process`.
+    #[cfg(target_os = "linux")]
+    fn timer_event(&mut self, token: Token) -> bool {
+        match self.timer_fd {
+            Some(ref timer_fd) if token == TIMER => {
+                timer_fd.reset();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn timer_event(&mut self, _: Token) -> bool {
+        false
+    }
+
+    // Counts a dispatch for `token`, returning false once it has had its
+    // share of the current iteration
+    fn take_turn(&mut self, token: Token) -> bool {
+        let cap = match self.config.io_dispatch_per_token {
+            Some(cap) => cap,
+            None => return true,
+        };
+
+        let count = self.dispatch_counts.entry(token).or_insert(0);
+
+        if *count == cap {
+            return false;
+        }
+
+        *count += 1;
+        true
+    }
+
+    // Queues `evt` for the next iteration, merging it into an event already
+    // queued for the same token. Level triggered handles are reported by
+    // every poll until drained, so they would otherwise pile up.
+    fn defer(&mut self, evt: IoEvent) {
+        for queued in self.deferred.iter_mut() {
+            if queued.token == evt.token {
+                queued.kind = queued.kind | evt.kind;
+                return;
+            }
+        }
+
+        self.deferred.push_back(evt);
     }
 
     fn io_event(&mut self, handler: &mut H, evt: IoEvent) {
-        handler.ready(self, evt.token, evt.kind);
+        instrument::dispatch_begin(evt.token, evt.kind);
+        self.io_dispatch(handler, evt);
+        instrument::dispatch_end(evt.token);
+    }
+
+    fn io_dispatch(&mut self, handler: &mut H, evt: IoEvent) {
+        if self.config.catch_panics && self.poisoned.contains(&evt.token) {
+            trace!("ignoring event for poisoned token; token={:?}", evt.token);
+            return;
+        }
+
+        self.record(evt);
+
+        if !self.config.catch_panics {
+            handler.ready(self, evt.token, evt.kind);
+            return;
+        }
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| {
+            handler.ready(self, evt.token, evt.kind)
+        }));
+
+        if let Err(err) = res {
+            warn!("handler panicked; token={:?}", evt.token);
+
+            // A level triggered handle would otherwise be reported ready by
+            // every poll, keeping the event loop spinning
+            if let Err(err) = self.poll.deregister_token(evt.token) {
+                warn!("failed to deregister panicking token; token={:?}; err={:?}", evt.token, err);
+            }
+
+            self.deferred.retain(|queued| queued.token != evt.token);
+            self.poisoned.push(evt.token);
+            handler.panicked(self, evt.token, err);
+        }
+    }
+
+    fn record(&mut self, evt: IoEvent) {
+        let failed = match self.recorder {
+            Some(ref mut rec) => rec.record(evt.token, evt.kind).is_err(),
+            None => return,
+        };
+
+        if failed {
+            warn!("failed to record event, recording stopped; token={:?}", evt.token);
+            self.recorder = None;
+        }
     }
 
     fn notify(&mut self, handler: &mut H, mut cnt: usize) {
@@ -324,14 +855,42 @@ impl<H: Handler> EventLoop<H> {
         }
     }
 
+    #[cfg(feature = "metrics")]
+    fn record_notify_depth(&mut self) {
+        let depth = self.notify.pending() as u64;
+
+        self.metrics.notify_depth = depth;
+        self.metrics.max_notify_depth = cmp::max(self.metrics.max_notify_depth, depth);
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    fn record_notify_depth(&mut self) {
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_timer_lag(&mut self) {
+        let lag = self.timer.lag_ns(self.timer_now);
+
+        self.metrics.timer_lag_ns = lag;
+        self.metrics.max_timer_lag_ns = cmp::max(self.metrics.max_timer_lag_ns, lag);
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    fn record_timer_lag(&mut self) {
+    }
+
     fn timer_process(&mut self, handler: &mut H) {
-        let now = self.timer.now();
+        self.timer_now = self.timer.now();
 
-        loop {
-            match self.timer.tick_to(now) {
-                Some(t) => handler.timeout(self, t),
-                _ => return
-            }
+        if self.timer_head.is_none() {
+            self.record_timer_lag();
+            self.timer_head = self.timer.tick_to(self.timer_now);
+        }
+
+        if self.timer_head.is_some() {
+            handler.timeouts(self);
         }
     }
 }
@@ -368,13 +927,31 @@ impl<M: Send> Sender<M> {
         Sender { notify: notify }
     }
 
+    /// Sends a message without blocking. If the queue is at capacity, the
+    /// message is handed back in `NotifyError::Full`.
     pub fn send(&self, msg: M) -> Result<(), NotifyError<M>> {
         self.notify.notify(msg)
     }
+
+    /// Asks the event loop to stop, waking it up if it is sleeping. Events
+    /// of the iteration in progress are still dispatched before `run`
+    /// returns; messages still queued are not.
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.notify.shutdown()
+    }
+
+    /// Sends a message, waiting for room in the queue if it is full.
+    ///
+    /// Only call this from threads other than the event loop's own, which
+    /// would otherwise wait on itself forever.
+    pub fn send_blocking(&self, msg: M) -> Result<(), NotifyError<M>> {
+        self.notify.notify_blocking(msg)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::any::Any;
     use std::str;
     use std::sync::Arc;
     use std::sync::atomic::AtomicIsize;
@@ -433,7 +1010,7 @@ mod tests {
         writer.try_write_buf(&mut SliceBuf::wrap("hello".as_bytes())).unwrap();
         event_loop.register(&reader, Token(10)).unwrap();
 
-        let _ = event_loop.run_once(&mut handler);
+        let _ = event_loop.run_once(&mut handler, None);
         let mut b = ByteBuf::mut_with_capacity(16);
 
         assert_eq!((*rcount).load(SeqCst), 1);
@@ -442,4 +1019,370 @@ mod tests {
 
         assert_eq!(str::from_utf8(b.flip().bytes()).unwrap(), "hello");
     }
+
+    #[test]
+    pub fn test_run_once_stats() {
+        use std::time::Duration;
+
+        let mut event_loop = EventLoop::new().unwrap();
+        let (reader, mut writer) = unix::pipe().unwrap();
+
+        let rcount = Arc::new(AtomicIsize::new(0));
+        let wcount = Arc::new(AtomicIsize::new(0));
+        let mut handler = Funtimes::new(rcount.clone(), wcount.clone());
+
+        event_loop.register(&reader, Token(10)).unwrap();
+
+        // Nothing is ready, so this returns as soon as the timeout elapses
+        let stats = event_loop.run_once(&mut handler, Some(Duration::from_millis(10))).unwrap();
+        assert!(stats.is_idle());
+
+        writer.try_write(b"a").unwrap();
+        event_loop.channel().send(()).unwrap();
+
+        let stats = event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+        assert_eq!(stats.events, 1);
+        assert_eq!(stats.messages, 1);
+    }
+
+    #[test]
+    pub fn test_events_capacity() {
+        use super::EventLoopConfig;
+
+        let config = EventLoopConfig {
+            io_events_capacity: 1,
+            .. EventLoopConfig::default()
+        };
+
+        let mut event_loop = EventLoop::configured(config).unwrap();
+
+        let (reader1, mut writer1) = unix::pipe().unwrap();
+        let (reader2, mut writer2) = unix::pipe().unwrap();
+
+        let rcount = Arc::new(AtomicIsize::new(0));
+        let wcount = Arc::new(AtomicIsize::new(0));
+        let mut handler = Funtimes::new(rcount.clone(), wcount.clone());
+
+        writer1.try_write(b"a").unwrap();
+        writer2.try_write(b"b").unwrap();
+
+        event_loop.register(&reader1, Token(10)).unwrap();
+        event_loop.register(&reader2, Token(10)).unwrap();
+
+        // Only one event fits per iteration
+        event_loop.run_once(&mut handler, None).unwrap();
+        assert_eq!((*rcount).load(SeqCst), 1);
+
+        event_loop.run_once(&mut handler, None).unwrap();
+        assert_eq!((*rcount).load(SeqCst), 2);
+    }
+
+    struct Recorder {
+        seen: Vec<Token>,
+        defer_once: bool,
+    }
+
+    impl Handler for Recorder {
+        type Timeout = usize;
+        type Message = ();
+
+        fn ready(&mut self, event_loop: &mut EventLoop<Recorder>, token: Token, events: EventSet) {
+            self.seen.push(token);
+
+            if self.defer_once {
+                self.defer_once = false;
+                event_loop.defer_ready(token, events);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_dispatch_budget() {
+        use std::time::Duration;
+        use super::EventLoopConfig;
+        use PollOpt;
+
+        let config = EventLoopConfig {
+            io_dispatch_budget: Some(1),
+            .. EventLoopConfig::default()
+        };
+
+        let mut event_loop = EventLoop::configured(config).unwrap();
+        let mut handler = Recorder { seen: vec![], defer_once: false };
+
+        let (reader1, mut writer1) = unix::pipe().unwrap();
+        let (reader2, mut writer2) = unix::pipe().unwrap();
+
+        event_loop.register_opt(&reader1, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+        event_loop.register_opt(&reader2, Token(2), EventSet::readable(), PollOpt::edge()).unwrap();
+
+        writer1.try_write(b"a").unwrap();
+        writer2.try_write(b"b").unwrap();
+
+        // The second edge is carried over rather than lost
+        let now = Some(Duration::from_millis(0));
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+        assert!(event_loop.run_once(&mut handler, now).unwrap().is_idle());
+
+        handler.seen.sort_by(|a, b| a.as_usize().cmp(&b.as_usize()));
+        assert_eq!(handler.seen, vec![Token(1), Token(2)]);
+    }
+
+    #[test]
+    pub fn test_dispatch_limits_reject_zero() {
+        use super::EventLoopConfig;
+
+        let config = EventLoopConfig {
+            io_dispatch_budget: Some(0),
+            .. EventLoopConfig::default()
+        };
+        assert!(EventLoop::<Recorder>::configured(config).is_err());
+
+        let config = EventLoopConfig {
+            io_dispatch_per_token: Some(0),
+            .. EventLoopConfig::default()
+        };
+        assert!(EventLoop::<Recorder>::configured(config).is_err());
+    }
+
+    #[test]
+    pub fn test_events_capacity_rejects_zero() {
+        use std::io::ErrorKind;
+        use super::EventLoopConfig;
+
+        let config = EventLoopConfig {
+            io_events_capacity: 0,
+            .. EventLoopConfig::default()
+        };
+        let err = EventLoop::<Recorder>::configured(config).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    pub fn test_dispatch_per_token() {
+        use std::time::Duration;
+        use super::EventLoopConfig;
+        use PollOpt;
+
+        let config = EventLoopConfig {
+            io_dispatch_per_token: Some(1),
+            .. EventLoopConfig::default()
+        };
+
+        let mut event_loop = EventLoop::configured(config).unwrap();
+        let mut handler = Recorder { seen: vec![], defer_once: false };
+
+        let (reader1, mut writer1) = unix::pipe().unwrap();
+        let (reader2, mut writer2) = unix::pipe().unwrap();
+        let (reader3, mut writer3) = unix::pipe().unwrap();
+
+        // Two handles share a token
+        event_loop.register_opt(&reader1, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+        event_loop.register_opt(&reader2, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+        event_loop.register_opt(&reader3, Token(2), EventSet::readable(), PollOpt::edge()).unwrap();
+
+        writer1.try_write(b"a").unwrap();
+        writer2.try_write(b"b").unwrap();
+        writer3.try_write(b"c").unwrap();
+
+        let now = Some(Duration::from_millis(0));
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 2);
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+        assert!(event_loop.run_once(&mut handler, now).unwrap().is_idle());
+
+        assert_eq!(handler.seen.iter().filter(|t| **t == Token(1)).count(), 2);
+        assert_eq!(*handler.seen.last().unwrap(), Token(1));
+    }
+
+    #[test]
+    pub fn test_deregister_drops_deferred() {
+        use std::time::Duration;
+        use super::EventLoopConfig;
+        use PollOpt;
+
+        let config = EventLoopConfig {
+            io_dispatch_budget: Some(1),
+            .. EventLoopConfig::default()
+        };
+
+        let mut event_loop = EventLoop::configured(config).unwrap();
+        let mut handler = Recorder { seen: vec![], defer_once: false };
+
+        let (reader1, mut writer1) = unix::pipe().unwrap();
+        let (reader2, mut writer2) = unix::pipe().unwrap();
+
+        event_loop.register_opt(&reader1, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+        event_loop.register_opt(&reader2, Token(2), EventSet::readable(), PollOpt::edge()).unwrap();
+
+        writer1.try_write(b"a").unwrap();
+        writer2.try_write(b"b").unwrap();
+
+        let now = Some(Duration::from_millis(0));
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+
+        // The handle whose event was queued goes away before its turn
+        if handler.seen[0] == Token(1) {
+            event_loop.deregister(&reader2).unwrap();
+        } else {
+            event_loop.deregister(&reader1).unwrap();
+        }
+
+        assert!(event_loop.run_once(&mut handler, now).unwrap().is_idle());
+        assert_eq!(handler.seen.len(), 1);
+    }
+
+    #[test]
+    pub fn test_defer_ready() {
+        use std::time::Duration;
+        use PollOpt;
+
+        let mut event_loop = EventLoop::new().unwrap();
+        let mut handler = Recorder { seen: vec![], defer_once: true };
+
+        let (reader, mut writer) = unix::pipe().unwrap();
+        event_loop.register_opt(&reader, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+        writer.try_write(b"a").unwrap();
+
+        let now = Some(Duration::from_millis(0));
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+        assert_eq!(event_loop.run_once(&mut handler, now).unwrap().events, 1);
+        assert!(event_loop.run_once(&mut handler, now).unwrap().is_idle());
+
+        assert_eq!(handler.seen, vec![Token(1), Token(1)]);
+    }
+
+    struct Panicky {
+        ready: Vec<Token>,
+        panicked: Vec<Token>,
+    }
+
+    impl Handler for Panicky {
+        type Timeout = usize;
+        type Message = ();
+
+        fn ready(&mut self, _: &mut EventLoop<Panicky>, token: Token, _: EventSet) {
+            if token == Token(1) {
+                panic!("boom");
+            }
+
+            self.ready.push(token);
+        }
+
+        fn panicked(&mut self, _: &mut EventLoop<Panicky>, token: Token, _: Box<Any + Send>) {
+            self.panicked.push(token);
+        }
+    }
+
+    #[test]
+    pub fn test_catch_panics() {
+        use std::time::Duration;
+        use super::EventLoopConfig;
+
+        let config = EventLoopConfig {
+            catch_panics: true,
+            .. EventLoopConfig::default()
+        };
+
+        let mut event_loop = EventLoop::configured(config).unwrap();
+        let mut handler = Panicky { ready: vec![], panicked: vec![] };
+
+        let (reader1, mut writer1) = unix::pipe().unwrap();
+        let (mut reader2, mut writer2) = unix::pipe().unwrap();
+
+        // Level triggered, so both stay ready
+        event_loop.register(&reader1, Token(1)).unwrap();
+        event_loop.register(&reader2, Token(2)).unwrap();
+
+        writer1.try_write(b"a").unwrap();
+        writer2.try_write(b"b").unwrap();
+
+        let now = Some(Duration::from_millis(0));
+
+        event_loop.run_once(&mut handler, now).unwrap();
+        assert_eq!(handler.panicked, vec![Token(1)]);
+        assert_eq!(handler.ready, vec![Token(2)]);
+
+        // The panicking token is no longer dispatched, the others still are
+        event_loop.run_once(&mut handler, now).unwrap();
+        assert_eq!(handler.panicked, vec![Token(1)]);
+        assert_eq!(handler.ready, vec![Token(2), Token(2)]);
+
+        // Once the other handle is drained, nothing is left ready: the
+        // panicking handle was deregistered rather than polled forever
+        let mut buf = [0; 1];
+        reader2.try_read(&mut buf).unwrap();
+
+        let stats = event_loop.run_once(&mut handler, Some(Duration::from_millis(50))).unwrap();
+        assert_eq!(stats.events, 0);
+        assert_eq!(handler.ready, vec![Token(2), Token(2)]);
+
+        // Registering it again lifts the ban
+        event_loop.register(&reader1, Token(1)).unwrap();
+        event_loop.run_once(&mut handler, now).unwrap();
+        assert_eq!(handler.panicked, vec![Token(1), Token(1)]);
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    pub fn test_metrics() {
+        use std::time::Duration;
+        use PollStats;
+
+        let mut event_loop = EventLoop::new().unwrap();
+        let (reader, mut writer) = unix::pipe().unwrap();
+
+        let rcount = Arc::new(AtomicIsize::new(0));
+        let wcount = Arc::new(AtomicIsize::new(0));
+        let mut handler = Funtimes::new(rcount.clone(), wcount.clone());
+
+        event_loop.register(&reader, Token(10)).unwrap();
+        writer.try_write(b"a").unwrap();
+
+        let sender = event_loop.channel();
+        sender.send(()).unwrap();
+        sender.send(()).unwrap();
+
+        event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+
+        {
+            let metrics = event_loop.metrics();
+            assert_eq!(metrics.polls, 1);
+            assert!(metrics.events >= 1);
+            assert_eq!(metrics.events_per_poll(), metrics.events as f64);
+            assert!(metrics.max_events_per_poll >= 1);
+            assert_eq!(metrics.notify_depth, 2);
+            assert_eq!(metrics.max_notify_depth, 2);
+        }
+
+        {
+            let stats = event_loop.poll_stats();
+            assert_eq!(stats.selects, 1);
+            assert_eq!(stats.wakeups, 1);
+            assert_eq!(stats.events, event_loop.metrics().events);
+            assert_eq!(stats.events_per_wakeup(), stats.events as f64);
+        }
+
+        event_loop.reset_metrics();
+        assert_eq!(event_loop.metrics().polls, 0);
+        assert_eq!(*event_loop.poll_stats(), PollStats::default());
+    }
+
+    #[test]
+    #[cfg(all(feature = "metrics", feature = "testing"))]
+    pub fn test_metrics_interrupts() {
+        use std::time::Duration;
+        use Faults;
+
+        let mut event_loop = EventLoop::new().unwrap();
+        let mut handler = Funtimes::new(Arc::new(AtomicIsize::new(0)), Arc::new(AtomicIsize::new(0)));
+
+        // Injected interrupts count like `EINTR`
+        event_loop.set_faults(Some(Faults { interrupt: 1, .. Faults::default() }));
+        event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+
+        assert_eq!(event_loop.poll_stats().interrupts, 1);
+        assert_eq!(event_loop.poll_stats().wakeups, 0);
+        assert_eq!(event_loop.metrics().polls, 0);
+    }
 }
@@ -0,0 +1,193 @@
+use {EventLoop, EventLoopConfig, Handler, NotifyError, Sender};
+use std::{fmt, io, mem, thread};
+use std::sync::{mpsc, Arc};
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+
+/// A set of event loops, each running on its own thread.
+///
+/// Every loop gets its own handler, built on the loop's thread by the
+/// closure passed to `new`. The closure is also the place to create
+/// per-loop resources, such as a listener bound with `SO_REUSEPORT` so that
+/// the kernel shards incoming connections across the loops.
+///
+/// Registration has to happen on the thread owning the loop, so handles are
+/// placed on a loop by sending them in a message: pick the loop with
+/// `send_to`, or let `send` distribute messages round-robin.
+///
+/// Dropping the pool stops the loops and waits for their threads, like
+/// `join` but ignoring errors.
+///
+/// ```
+/// use mio::{EventLoop, EventLoopConfig, EventLoopPool, Handler};
+/// use std::sync::{mpsc, Mutex};
+///
+/// struct Worker {
+///     done: mpsc::Sender<String>,
+/// }
+///
+/// impl Handler for Worker {
+///     type Timeout = ();
+///     type Message = String;
+///
+///     fn notify(&mut self, _: &mut EventLoop<Worker>, msg: String) {
+///         self.done.send(msg).unwrap();
+///     }
+/// }
+///
+/// let (tx, rx) = mpsc::channel();
+/// let tx = Mutex::new(tx);
+///
+/// let pool = EventLoopPool::new(2, EventLoopConfig::default(), move |_, _| {
+///     Ok(Worker { done: tx.lock().unwrap().clone() })
+/// }).unwrap();
+///
+/// pool.send("hello".to_string()).unwrap();
+///
+/// // Messages still queued when the loops stop are dropped, so wait for
+/// // the work to be done before joining
+/// assert_eq!(rx.recv().unwrap(), "hello");
+/// pool.join().unwrap();
+/// ```
+pub struct EventLoopPool<M: Send> {
+    senders: Vec<Sender<M>>,
+    threads: Vec<thread::JoinHandle<io::Result<()>>>,
+    next: AtomicUsize,
+}
+
+impl<M: Send + 'static> EventLoopPool<M> {
+    /// Spawns `size` event loops. `new_handler` is called on each loop's
+    /// thread with the loop's index and the loop itself, and returns the
+    /// handler to run it with.
+    ///
+    /// There is no portable way to count cores, so `size` is up to the
+    /// caller; one loop per core is a good default. Fails if `size` is zero.
+    pub fn new<H, F>(size: usize, config: EventLoopConfig, new_handler: F) -> io::Result<EventLoopPool<M>>
+        where H: Handler<Message = M> + 'static,
+              F: Fn(usize, &mut EventLoop<H>) -> io::Result<H> + Send + Sync + 'static,
+    {
+        if size == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "event loop pool needs at least one loop"));
+        }
+
+        let new_handler = Arc::new(new_handler);
+        let mut pool = EventLoopPool {
+            senders: Vec::with_capacity(size),
+            threads: Vec::with_capacity(size),
+            next: AtomicUsize::new(0),
+        };
+
+        for id in 0..size {
+            let new_handler = new_handler.clone();
+            let (tx, rx) = mpsc::channel();
+
+            let thread = thread::spawn(move || {
+                let setup = EventLoop::configured(config).and_then(|mut event_loop| {
+                    let handler = try!(new_handler(id, &mut event_loop));
+                    Ok((event_loop, handler))
+                });
+
+                let (mut event_loop, mut handler) = match setup {
+                    Ok(v) => v,
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                        return Ok(());
+                    }
+                };
+
+                let _ = tx.send(Ok(event_loop.channel()));
+                event_loop.run(&mut handler)
+            });
+
+            // The pool shuts down the loops started so far when dropped
+            let sender = match rx.recv() {
+                Ok(res) => try!(res),
+                Err(_) => return Err(io::Error::new(io::ErrorKind::Other, "event loop thread panicked")),
+            };
+
+            pool.senders.push(sender);
+            pool.threads.push(thread);
+        }
+
+        Ok(pool)
+    }
+
+    /// Number of event loops in the pool
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Returns the sender for the loop at `id`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `id` is out of range.
+    pub fn sender(&self, id: usize) -> &Sender<M> {
+        &self.senders[id]
+    }
+
+    /// Sends a message to the loop at `id`
+    pub fn send_to(&self, id: usize, msg: M) -> Result<(), NotifyError<M>> {
+        self.senders[id].send(msg)
+    }
+
+    /// Sends a message to the next loop in round-robin order, returning the
+    /// index of the loop it was sent to
+    pub fn send(&self, msg: M) -> Result<usize, NotifyError<M>> {
+        let id = self.next.fetch_add(1, Relaxed) % self.senders.len();
+        try!(self.senders[id].send(msg));
+        Ok(id)
+    }
+
+    /// Asks every loop to stop after its current iteration
+    pub fn shutdown(&self) -> io::Result<()> {
+        for sender in self.senders.iter() {
+            try!(sender.shutdown());
+        }
+
+        Ok(())
+    }
+
+    /// Stops every loop and waits for their threads to exit, returning the
+    /// first error any of them failed with. Messages the loops have not
+    /// received yet are dropped.
+    pub fn join(mut self) -> io::Result<()> {
+        try!(self.shutdown());
+        self.join_threads()
+    }
+}
+
+impl<M: Send> fmt::Debug for EventLoopPool<M> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "EventLoopPool {{ len: {} }}", self.senders.len())
+    }
+}
+
+impl<M: Send> EventLoopPool<M> {
+    fn join_threads(&mut self) -> io::Result<()> {
+        let mut res = Ok(());
+
+        for thread in mem::replace(&mut self.threads, vec![]) {
+            let ret = match thread.join() {
+                Ok(ret) => ret,
+                Err(_) => Err(io::Error::new(io::ErrorKind::Other, "event loop thread panicked")),
+            };
+
+            if res.is_ok() {
+                res = ret;
+            }
+        }
+
+        res
+    }
+}
+
+impl<M: Send> Drop for EventLoopPool<M> {
+    fn drop(&mut self) {
+        for sender in self.senders.iter() {
+            let _ = sender.shutdown();
+        }
+
+        let _ = self.join_threads();
+    }
+}
@@ -0,0 +1,93 @@
+use event::IoEvent;
+use poll::Events;
+use std::io;
+
+// Number of past events kept around to replay as spurious ones
+const RECENT: usize = 64;
+
+/// Faults injected into a `Poll` to check that handlers cope with
+/// conditions that are rare in practice, see `Poll::set_faults`.
+///
+/// Each field gives the odds of a fault per call to `poll`, as one in `n`;
+/// zero disables it. Faults are drawn from a generator seeded with `seed`,
+/// so a failing run can be reproduced by reusing its seed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Faults {
+    pub seed: u64,
+    /// Fail the poll with `ErrorKind::Interrupted`, as on `EINTR`
+    pub interrupt: u32,
+    /// Return immediately, possibly without any events
+    pub early_wakeup: u32,
+    /// Report a handle ready again although nothing changed. It was ready
+    /// during an earlier poll, so it has likely been drained and reading or
+    /// writing it now fails with `WouldBlock`.
+    pub spurious: u32,
+}
+
+// Per poller state of the fault injector
+pub struct FaultState {
+    faults: Faults,
+    rng: u64,
+    recent: Vec<IoEvent>,
+    next_recent: usize,
+}
+
+impl FaultState {
+    pub fn new(faults: Faults) -> FaultState {
+        FaultState {
+            faults: faults,
+            // xorshift gets stuck on zero
+            rng: if faults.seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { faults.seed },
+            recent: Vec::with_capacity(RECENT),
+            next_recent: 0,
+        }
+    }
+
+    /// Called before polling. Returns the error to fail the poll with, if
+    /// any, and otherwise whether to skip waiting.
+    pub fn before_poll(&mut self) -> io::Result<bool> {
+        let (interrupt, early_wakeup) = (self.faults.interrupt, self.faults.early_wakeup);
+
+        if self.roll(interrupt) {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "injected interrupt"));
+        }
+
+        Ok(self.roll(early_wakeup))
+    }
+
+    /// Called with the events returned by the selector. Returns a spurious
+    /// event to report as well, if any.
+    pub fn after_poll(&mut self, events: &Events) -> Option<IoEvent> {
+        for i in 0..events.len() {
+            let evt = events.get(i);
+
+            if self.recent.len() < RECENT {
+                self.recent.push(evt);
+            } else {
+                self.recent[self.next_recent] = evt;
+                self.next_recent = (self.next_recent + 1) % RECENT;
+            }
+        }
+
+        let spurious = self.faults.spurious;
+
+        if self.recent.is_empty() || !self.roll(spurious) {
+            return None;
+        }
+
+        let idx = (self.next() % self.recent.len() as u64) as usize;
+        Some(self.recent[idx])
+    }
+
+    fn roll(&mut self, one_in: u32) -> bool {
+        one_in != 0 && self.next() % one_in as u64 == 0
+    }
+
+    // xorshift64*
+    fn next(&mut self) -> u64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        self.rng.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
@@ -1,4 +1,5 @@
 use {EventLoop, EventSet, Token};
+use std::any::Any;
 
 #[allow(unused_variables)]
 pub trait Handler {
@@ -26,7 +27,37 @@ pub trait Handler {
     fn timeout(&mut self, event_loop: &mut EventLoop<Self>, timeout: Self::Timeout) {
     }
 
+    /// Invoked once per event loop tick when at least one timeout has
+    /// completed. The default implementation calls `timeout` for each of
+    /// them.
+    ///
+    /// Handlers expecting many timeouts to expire together can override this
+    /// to process them as a batch, collecting them with
+    /// `EventLoop::expired_timeouts` or `EventLoop::next_timeout`. Timeouts
+    /// left uncollected are offered again on the next tick.
+    fn timeouts(&mut self, event_loop: &mut EventLoop<Self>) {
+        while let Some(timeout) = event_loop.next_timeout() {
+            self.timeout(event_loop, timeout);
+        }
+    }
+
     /// Invoked when `EventLoop` has been interrupted by a signal interrupt.
     fn interrupted(&mut self, event_loop: &mut EventLoop<Self>) {
     }
+
+    /// Invoked when `ready` panicked for `token` while
+    /// `EventLoopConfig::catch_panics` is set, with the panic's payload.
+    ///
+    /// The handles registered under `token` have been deregistered, and
+    /// events for it already polled are dropped. Implementations should
+    /// release whatever was associated with the token, such as closing the
+    /// connection, or register it again.
+    fn panicked(&mut self, event_loop: &mut EventLoop<Self>, token: Token, err: Box<Any + Send>) {
+    }
+
+    /// Invoked at the end of every event loop iteration, after IO events,
+    /// messages and timeouts have been dispatched. A natural point to flush
+    /// buffered writes or reap closed connections in a batch.
+    fn tick(&mut self, event_loop: &mut EventLoop<Self>) {
+    }
 }
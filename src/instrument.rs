@@ -0,0 +1,95 @@
+//! Structured trace records, emitted when mio is built with the `trace`
+//! feature.
+//!
+//! Every record is a `trace` level log line under the `mio::instrument`
+//! target, made of an operation name followed by `key=value` fields. Polls
+//! and handler dispatches are bracketed by `begin` / `end` records so that
+//! time spent in the selector and in the handler can be told apart. Without
+//! the feature these functions do nothing and compile away.
+
+use {EventSet, PollOpt, Token};
+use std::io;
+use std::os::unix::io::RawFd;
+
+#[cfg(feature = "trace")]
+pub fn register(op: &'static str, fd: RawFd, token: Token, interest: EventSet, opts: PollOpt) {
+    trace!("{}; fd={}; token={}; interest={:?}; opts={:?}", op, fd, token.as_usize(), interest, opts);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn register(_: &'static str, _: RawFd, _: Token, _: EventSet, _: PollOpt) {
+}
+
+#[cfg(feature = "trace")]
+pub fn deregister(fd: RawFd) {
+    trace!("deregister; fd={}", fd);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn deregister(_: RawFd) {
+}
+
+#[cfg(feature = "trace")]
+pub fn select(timeout_ms: usize, events: usize) {
+    trace!("select; timeout_ms={}; events={}", timeout_ms, events);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn select(_: usize, _: usize) {
+}
+
+#[cfg(feature = "trace")]
+pub fn poll_begin(timeout_ms: usize) {
+    trace!("poll begin; timeout_ms={}", timeout_ms);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn poll_begin(_: usize) {
+}
+
+#[cfg(feature = "trace")]
+pub fn poll_end(res: &io::Result<usize>) {
+    match *res {
+        Ok(events) => trace!("poll end; events={}", events),
+        Err(ref err) => trace!("poll end; err={:?}", err),
+    }
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn poll_end(_: &io::Result<usize>) {
+}
+
+#[cfg(feature = "trace")]
+pub fn dispatch_begin(token: Token, events: EventSet) {
+    trace!("dispatch begin; token={}; events={:?}", token.as_usize(), events);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn dispatch_begin(_: Token, _: EventSet) {
+}
+
+#[cfg(feature = "trace")]
+pub fn dispatch_end(token: Token) {
+    trace!("dispatch end; token={}", token.as_usize());
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn dispatch_end(_: Token) {
+}
+
+#[cfg(feature = "trace")]
+pub fn tick_end(events: usize, messages: usize, timeouts: usize, deferred: usize) {
+    trace!("tick end; events={}; messages={}; timeouts={}; deferred={}", events, messages, timeouts, deferred);
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline]
+pub fn tick_end(_: usize, _: usize, _: usize, _: usize) {
+}
@@ -1,5 +1,8 @@
 use {EventSet, Selector, PollOpt, Token};
 use bytes::{Buf, MutBuf};
+use std::cmp;
+use std::rc::Rc;
+use std::sync::Arc;
 
 // Re-export the io::Result / Error types for convenience
 pub use std::io::{Read, Write, Result, Error};
@@ -16,8 +19,45 @@ pub trait Evented {
     fn deregister(&self, selector: &mut Selector) -> Result<()>;
 }
 
+macro_rules! evented_deref {
+    ($($ty:ty),*) => {$(
+        impl<'a, E: Evented + ?Sized> Evented for $ty {
+            fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> Result<()> {
+                (**self).register(selector, token, interest, opts)
+            }
+
+            fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> Result<()> {
+                (**self).reregister(selector, token, interest, opts)
+            }
+
+            fn deregister(&self, selector: &mut Selector) -> Result<()> {
+                (**self).deregister(selector)
+            }
+        }
+    )*}
+}
+
+evented_deref!(&'a E, &'a mut E, Box<E>, Rc<E>, Arc<E>);
+
+/// Scatter reads: fills several buffers, in order, with a single system
+/// call (`readv`)
+pub trait ReadVectored {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> Result<usize>;
+}
+
+/// Gather writes: writes several buffers, in order, with a single system
+/// call (`writev`)
+pub trait WriteVectored {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> Result<usize>;
+}
+
+/// Non-blocking reads.
+///
+/// A read that cannot make progress fails with `ErrorKind::WouldBlock`, so
+/// results compose with `try!` like any other I/O error. Code written against
+/// the older `Ok(None)` convention can call `map_non_block` on the result.
 pub trait TryRead {
-    fn try_read_buf<B: MutBuf>(&mut self, buf: &mut B) -> Result<Option<usize>>
+    fn try_read_buf<B: MutBuf>(&mut self, buf: &mut B) -> Result<usize>
         where Self : Sized
     {
         // Reads the length of the slice supplied by buf.mut_bytes into the buffer
@@ -25,47 +65,116 @@ pub trait TryRead {
         // If your protocol is msg based (instead of continuous stream) you should
         // ensure that your buffer is large enough to hold an entire segment (1532 bytes if not jumbo
         // frames)
-        let res = self.try_read(unsafe { buf.mut_bytes() });
+        let cnt = try!(self.try_read(unsafe { buf.mut_bytes() }));
+        buf.advance(cnt);
+        Ok(cnt)
+    }
 
-        if let Ok(Some(cnt)) = res {
-            buf.advance(cnt);
-        }
+    fn try_read(&mut self, buf: &mut [u8]) -> Result<usize>;
 
-        res
+    fn try_read_vectored(&mut self, bufs: &mut [&mut [u8]]) -> Result<usize>
+        where Self : ReadVectored
+    {
+        self.read_bufs(bufs)
     }
 
-    fn try_read(&mut self, buf: &mut [u8]) -> Result<Option<usize>>;
+    /// Reads into the writable regions of several buffers with a single
+    /// call, filling them in order and advancing each by what it received.
+    fn try_read_bufs<B: MutBuf>(&mut self, bufs: &mut [B]) -> Result<usize>
+        where Self : ReadVectored + Sized
+    {
+        let mut lens = Vec::with_capacity(bufs.len());
+
+        let cnt = {
+            let mut dst: Vec<&mut [u8]> = bufs.iter_mut()
+                .map(|buf| unsafe { buf.mut_bytes() })
+                .collect();
+
+            lens.extend(dst.iter().map(|b| b.len()));
+
+            try!(self.try_read_vectored(&mut dst))
+        };
+
+        let mut rem = cnt;
+
+        for (buf, len) in bufs.iter_mut().zip(lens) {
+            let n = cmp::min(rem, len);
+            buf.advance(n);
+            rem -= n;
+        }
+
+        Ok(cnt)
+    }
 }
 
+/// Non-blocking writes, following the same conventions as `TryRead`.
 pub trait TryWrite {
-    fn try_write_buf<B: Buf>(&mut self, buf: &mut B) -> Result<Option<usize>>
+    fn try_write_buf<B: Buf>(&mut self, buf: &mut B) -> Result<usize>
         where Self : Sized
     {
-        let res = self.try_write(buf.bytes());
+        let cnt = try!(self.try_write(buf.bytes()));
+        buf.advance(cnt);
+        Ok(cnt)
+    }
 
-        if let Ok(Some(cnt)) = res {
-            buf.advance(cnt);
-        }
+    fn try_write(&mut self, buf: &[u8]) -> Result<usize>;
 
-        res
+    fn try_write_vectored(&mut self, bufs: &[&[u8]]) -> Result<usize>
+        where Self : WriteVectored
+    {
+        self.write_bufs(bufs)
     }
 
-    fn try_write(&mut self, buf: &[u8]) -> Result<Option<usize>>;
+    /// Writes the readable regions of several buffers with a single call,
+    /// advancing each by what was written from it.
+    fn try_write_bufs<B: Buf>(&mut self, bufs: &mut [B]) -> Result<usize>
+        where Self : WriteVectored + Sized
+    {
+        let lens: Vec<usize> = bufs.iter()
+            .map(|buf| buf.bytes().len())
+            .collect();
+
+        let cnt = {
+            let src: Vec<&[u8]> = bufs.iter()
+                .map(|buf| buf.bytes())
+                .collect();
+
+            try!(self.try_write_vectored(&src))
+        };
+
+        let mut rem = cnt;
+
+        for (buf, len) in bufs.iter_mut().zip(lens) {
+            let n = cmp::min(rem, len);
+            buf.advance(n);
+            rem -= n;
+        }
+
+        Ok(cnt)
+    }
 }
 
 impl<T: Read> TryRead for T {
-    fn try_read(&mut self, dst: &mut [u8]) -> Result<Option<usize>> {
+    fn try_read(&mut self, dst: &mut [u8]) -> Result<usize> {
         self.read(dst)
-            .map(|cnt| Some(cnt))
-            .or_else(to_non_block)
     }
 }
 
 impl<T: Write> TryWrite for T {
-    fn try_write(&mut self, src: &[u8]) -> Result<Option<usize>> {
+    fn try_write(&mut self, src: &[u8]) -> Result<usize> {
         self.write(src)
-            .map(|cnt| Some(cnt))
-            .or_else(to_non_block)
+    }
+}
+
+/// Compatibility shim for the `Ok(None)` convention: maps a
+/// `ErrorKind::WouldBlock` error to `Ok(None)` and any success to `Some`.
+pub trait MapNonBlock<T> {
+    fn map_non_block(self) -> Result<Option<T>>;
+}
+
+impl<T> MapNonBlock<T> for Result<T> {
+    fn map_non_block(self) -> Result<Option<T>> {
+        self.map(Some).or_else(to_non_block)
     }
 }
 
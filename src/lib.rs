@@ -93,11 +93,18 @@ pub mod util;
 
 mod event;
 mod event_loop;
+mod event_loop_pool;
+#[cfg(feature = "testing")]
+mod faults;
 mod handler;
+mod instrument;
 mod io;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod net;
 mod notify;
 mod poll;
+mod record;
 mod sys;
 mod timer;
 mod token;
@@ -109,14 +116,25 @@ pub use event::{
 pub use event_loop::{
     EventLoop,
     EventLoopConfig,
+    RunStats,
     Sender,
 };
+pub use event_loop_pool::{
+    EventLoopPool,
+};
+#[cfg(feature = "testing")]
+pub use faults::{
+    Faults,
+};
 pub use handler::{
     Handler,
 };
 pub use io::{
+    MapNonBlock,
     TryRead,
     TryWrite,
+    ReadVectored,
+    WriteVectored,
     Evented,
     TryAccept,
 };
@@ -129,14 +147,32 @@ pub use net::{
 };
 #[cfg(unix)]
 pub use net::unix;
+#[cfg(target_os = "linux")]
+pub use net::sctp;
 
+#[cfg(feature = "metrics")]
+pub use metrics::Metrics;
+#[cfg(feature = "metrics")]
+pub use poll::PollStats;
 pub use notify::{
     NotifyError,
 };
 pub use poll::{
-    Poll
+    EventsIter,
+    Poll,
+    Registered,
+    Registration,
+    ShardedPoll,
+};
+pub use record::{
+    Recorded,
+    Recorder,
 };
 pub use timer::{
+    Clock,
+    ManualClock,
+    MonotonicClock,
+    Timer,
     Timeout,
     TimerError,
     TimerResult
@@ -152,6 +188,7 @@ pub use sys::{
 pub mod prelude {
     pub use super::{
         EventLoop,
+        MapNonBlock,
         TryRead,
         TryWrite,
     };
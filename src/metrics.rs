@@ -0,0 +1,41 @@
+/// Counters describing what an `EventLoop` has been doing, available when
+/// mio is built with the `metrics` feature. Without the feature none of
+/// them are maintained.
+///
+/// Durations are in nanoseconds. Counters accumulate until reset with
+/// `EventLoop::reset_metrics`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Metrics {
+    /// Calls to the poller that returned, with or without events
+    pub polls: u64,
+    /// Events returned by the selector, including internal wakeups
+    pub events: u64,
+    /// Most events returned by a single poll
+    pub max_events_per_poll: u64,
+    /// Time spent in the selector
+    pub poll_ns: u64,
+    /// Longest single poll
+    pub max_poll_ns: u64,
+    /// Messages queued in the notify channel at the start of the last
+    /// iteration
+    pub notify_depth: u64,
+    /// Most messages found queued at the start of an iteration
+    pub max_notify_depth: u64,
+    /// How far the timer wheel lagged behind the clock when last advanced
+    pub timer_lag_ns: u64,
+    /// Largest timer lag observed
+    pub max_timer_lag_ns: u64,
+}
+
+impl Metrics {
+    /// Average number of events returned per poll. A value close to
+    /// `EventLoopConfig::io_events_capacity` means the poller is too small
+    /// to drain the ready handles in one go.
+    pub fn events_per_poll(&self) -> f64 {
+        if self.polls == 0 {
+            return 0.0;
+        }
+
+        self.events as f64 / self.polls as f64
+    }
+}
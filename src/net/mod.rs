@@ -1,11 +1,15 @@
 //! Networking primitives
 //!
-use std::net::AddrParseError;
+use std::{fmt, io};
+use std::net::{AddrParseError, SocketAddr};
 use std::str::FromStr;
 
 pub mod tcp;
 pub mod udp;
 
+#[cfg(target_os = "linux")]
+pub mod sctp;
+
 #[cfg(unix)]
 pub mod unix;
 
@@ -31,3 +35,16 @@ impl FromStr for IpAddr {
             })
     }
 }
+
+/// Displays a socket address in `Debug` output, or `?` if it cannot be
+/// retrieved, e.g. the peer of an unconnected socket
+struct DebugAddr(io::Result<SocketAddr>);
+
+impl fmt::Display for DebugAddr {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Ok(ref addr) => write!(fmt, "{}", addr),
+            Err(_) => write!(fmt, "?"),
+        }
+    }
+}
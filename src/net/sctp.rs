@@ -0,0 +1,296 @@
+//! SCTP sockets (Linux only)
+//!
+//! `SctpStream` and `SctpListener` provide one-to-one style associations,
+//! mirroring the TCP types. `SctpEndpoint` is a one-to-many style socket that
+//! multiplexes many associations over a single descriptor.
+//!
+//! All receive paths report the stream number and payload protocol
+//! identifier of each message. Association events (such as an association
+//! coming up or shutting down) are delivered in-band, with
+//! `SctpRecvInfo::notification` set; the buffer then contains a raw
+//! `struct sctp_notification`.
+use {io, sys, Evented, EventSet, PollOpt, Selector, Token, TryAccept};
+use tcp::Shutdown;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+pub use sys::SctpRecvInfo;
+
+/*
+ *
+ * ===== SctpStream =====
+ *
+ */
+
+#[derive(Debug)]
+pub struct SctpStream {
+    sys: sys::SctpSocket,
+}
+
+impl SctpStream {
+    pub fn connect(addr: &SocketAddr) -> io::Result<SctpStream> {
+        let sys = try!(match *addr {
+            SocketAddr::V4(..) => sys::SctpSocket::stream_v4(),
+            SocketAddr::V6(..) => sys::SctpSocket::stream_v6(),
+        });
+
+        try!(sys.connect(addr));
+        Ok(SctpStream { sys: sys })
+    }
+
+    /// Sends `buf` as a single message on the given stream. Writes through
+    /// `std::io::Write` always use stream 0.
+    pub fn send(&self, buf: &[u8], stream: u16, ppid: u32) -> io::Result<Option<usize>> {
+        self.sys.send(buf, None, stream, ppid)
+    }
+
+    /// Receives a single message
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<Option<SctpRecvInfo>> {
+        self.sys.recv(buf)
+            .map(|opt| opt.map(|(info, _)| info))
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.sys.peer_addr()
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sys.local_addr()
+    }
+
+    pub fn try_clone(&self) -> io::Result<SctpStream> {
+        self.sys.try_clone()
+            .map(|sys| SctpStream { sys: sys })
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.sys.shutdown(how)
+    }
+
+    pub fn take_socket_error(&self) -> io::Result<()> {
+        self.sys.take_socket_error()
+    }
+}
+
+impl Read for SctpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.sys.read(buf)
+    }
+}
+
+impl Write for SctpStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sys.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sys.flush()
+    }
+}
+
+impl Evented for SctpStream {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+/*
+ *
+ * ===== SctpListener =====
+ *
+ */
+
+#[derive(Debug)]
+pub struct SctpListener {
+    sys: sys::SctpSocket,
+}
+
+impl SctpListener {
+    pub fn bind(addr: &SocketAddr) -> io::Result<SctpListener> {
+        let sys = try!(match *addr {
+            SocketAddr::V4(..) => sys::SctpSocket::stream_v4(),
+            SocketAddr::V6(..) => sys::SctpSocket::stream_v6(),
+        });
+
+        try!(sys.bind(addr));
+        try!(sys.listen(1024));
+        Ok(SctpListener { sys: sys })
+    }
+
+    /// Accepts a new `SctpStream`.
+    ///
+    /// Returns `Ok(None)` when no association is pending.
+    pub fn accept(&self) -> io::Result<Option<SctpStream>> {
+        self.sys.accept()
+            .map(|opt| opt.map(|sys| SctpStream { sys: sys }))
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sys.local_addr()
+    }
+
+    pub fn try_clone(&self) -> io::Result<SctpListener> {
+        self.sys.try_clone()
+            .map(|sys| SctpListener { sys: sys })
+    }
+}
+
+impl Evented for SctpListener {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+impl TryAccept for SctpListener {
+    type Output = SctpStream;
+
+    fn accept(&self) -> io::Result<Option<SctpStream>> {
+        SctpListener::accept(self)
+    }
+}
+
+/*
+ *
+ * ===== SctpEndpoint =====
+ *
+ */
+
+/// A one-to-many style SCTP socket. Associations are set up implicitly by
+/// sending to a new peer, or by peers connecting to the bound address.
+#[derive(Debug)]
+pub struct SctpEndpoint {
+    sys: sys::SctpSocket,
+}
+
+impl SctpEndpoint {
+    /// Binds a new endpoint and starts accepting associations
+    pub fn bind(addr: &SocketAddr) -> io::Result<SctpEndpoint> {
+        let sys = try!(match *addr {
+            SocketAddr::V4(..) => sys::SctpSocket::seqpacket_v4(),
+            SocketAddr::V6(..) => sys::SctpSocket::seqpacket_v6(),
+        });
+
+        try!(sys.bind(addr));
+        try!(sys.listen(1024));
+        Ok(SctpEndpoint { sys: sys })
+    }
+
+    pub fn send_to(&self, buf: &[u8], target: &SocketAddr, stream: u16, ppid: u32) -> io::Result<Option<usize>> {
+        self.sys.send(buf, Some(target), stream, ppid)
+    }
+
+    pub fn recv_from(&self, buf: &mut [u8]) -> io::Result<Option<(SctpRecvInfo, Option<SocketAddr>)>> {
+        self.sys.recv(buf)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.sys.local_addr()
+    }
+
+    pub fn try_clone(&self) -> io::Result<SctpEndpoint> {
+        self.sys.try_clone()
+            .map(|sys| SctpEndpoint { sys: sys })
+    }
+}
+
+impl Evented for SctpEndpoint {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+/*
+ *
+ * ===== UNIX ext =====
+ *
+ */
+
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+use unix::Socket;
+
+impl AsRawFd for SctpStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for SctpStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+impl FromRawFd for SctpStream {
+    unsafe fn from_raw_fd(fd: RawFd) -> SctpStream {
+        SctpStream { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for SctpListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for SctpListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+impl FromRawFd for SctpListener {
+    unsafe fn from_raw_fd(fd: RawFd) -> SctpListener {
+        SctpListener { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for SctpEndpoint {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for SctpEndpoint {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+impl FromRawFd for SctpEndpoint {
+    unsafe fn from_raw_fd(fd: RawFd) -> SctpEndpoint {
+        SctpEndpoint { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+impl Socket for SctpStream {
+}
+
+impl Socket for SctpListener {
+}
+
+impl Socket for SctpEndpoint {
+}
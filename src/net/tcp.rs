@@ -1,6 +1,8 @@
-use {io, sys, Evented, EventSet, PollOpt, Selector, Token, TryAccept};
+use {io, sys, Evented, EventSet, PollOpt, ReadVectored, Selector, Token, TryAccept, WriteVectored};
+use std::fmt;
 use std::io::{Read, Write};
 use std::net::SocketAddr;
+use std::sync::Mutex;
 
 /*
  *
@@ -26,7 +28,7 @@ impl TcpSocket {
 
     pub fn connect(self, addr: &SocketAddr) -> io::Result<(TcpStream, bool)> {
         let complete = try!(self.sys.connect(addr));
-        Ok((From::from(self.sys), complete))
+        Ok((TcpStream::cached(self.sys), complete))
     }
 
     pub fn bind(&self, addr: &SocketAddr) -> io::Result<()> {
@@ -72,6 +74,51 @@ impl TcpSocket {
     pub fn set_keepalive(&self, seconds: Option<u32>) -> io::Result<()> {
         self.sys.set_keepalive(seconds)
     }
+
+    /// Sets `IP_FREEBIND`, allowing the socket to be bound to an address
+    /// that is not (yet) configured on any local interface.
+    ///
+    /// Must be set before `bind`.
+    #[cfg(target_os = "linux")]
+    pub fn set_freebind(&self, on: bool) -> io::Result<()> {
+        self.sys.set_freebind(on)
+    }
+
+    /// Returns whether `IP_FREEBIND` is set on the socket.
+    #[cfg(target_os = "linux")]
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.sys.freebind()
+    }
+
+    /// Sets `IP_TRANSPARENT`, allowing the socket to bind to and accept
+    /// traffic for non-local addresses, as used by transparent proxies.
+    /// Requires `CAP_NET_ADMIN`.
+    ///
+    /// Must be set before `bind`.
+    #[cfg(target_os = "linux")]
+    pub fn set_transparent(&self, on: bool) -> io::Result<()> {
+        self.sys.set_transparent(on)
+    }
+
+    /// Attaches a classic BPF program that selects which socket of a
+    /// `SO_REUSEPORT` group receives each incoming packet or connection
+    /// (`SO_ATTACH_REUSEPORT_CBPF`). The program returns the index of the
+    /// socket within the group; out of range values fall back to the default
+    /// hash.
+    ///
+    /// Must be set on a member of the group after `set_reuseport(true)`.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cbpf(&self, filter: &[::unix::bpf::SockFilter]) -> io::Result<()> {
+        self.sys.attach_reuseport_cbpf(filter)
+    }
+
+    /// Attaches a loaded eBPF program, given by its file descriptor, to the
+    /// socket's `SO_REUSEPORT` group (`SO_ATTACH_REUSEPORT_EBPF`). Behaves
+    /// like `attach_reuseport_cbpf`.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog: RawFd) -> io::Result<()> {
+        self.sys.attach_reuseport_ebpf(prog)
+    }
 }
 
 impl Evented for TcpSocket {
@@ -100,9 +147,11 @@ impl From<sys::TcpSocket> for TcpSocket {
  *
  */
 
-#[derive(Debug)]
 pub struct TcpStream {
     sys: sys::TcpSocket,
+    // Filled when the stream is accepted or connected, or on first use
+    local: Mutex<Option<SocketAddr>>,
+    peer: Mutex<Option<SocketAddr>>,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -126,12 +175,39 @@ impl TcpStream {
             .map(|(stream, _)| stream)
     }
 
+    fn new(sys: sys::TcpSocket) -> TcpStream {
+        TcpStream {
+            sys: sys,
+            local: Mutex::new(None),
+            peer: Mutex::new(None),
+        }
+    }
+
+    fn cached(sys: sys::TcpSocket) -> TcpStream {
+        let mut stream = TcpStream::new(sys);
+        stream.refresh_addrs();
+        stream
+    }
+
+    /// Returns the address of the remote peer.
+    ///
+    /// Addresses are looked up when the stream is accepted or connected and
+    /// served from a cache afterwards. An address that was not known yet,
+    /// such as the peer of a connection still in progress, is looked up on
+    /// the next call and cached once the lookup succeeds.
     pub fn peer_addr(&self) -> io::Result<SocketAddr> {
-        self.sys.peer_addr()
+        cached_addr(&self.peer, || self.sys.peer_addr())
     }
 
+    /// Returns the local address of the stream, see `peer_addr` for caching
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.sys.local_addr()
+        cached_addr(&self.local, || self.sys.local_addr())
+    }
+
+    /// Looks up the local and peer addresses again and caches them
+    pub fn refresh_addrs(&mut self) {
+        *self.local.get_mut().unwrap() = self.sys.local_addr().ok();
+        *self.peer.get_mut().unwrap() = self.sys.peer_addr().ok();
     }
 
     pub fn try_clone(&self) -> io::Result<TcpStream> {
@@ -142,6 +218,28 @@ impl TcpStream {
         self.sys.shutdown(how)
     }
 
+    /// Writes `buf` with `MSG_MORE` set, telling the kernel more data follows
+    /// immediately, so a header and body written separately can leave in the
+    /// same segment without toggling `TCP_CORK`. Returns `Ok(None)` if the
+    /// socket is not ready for writing.
+    #[cfg(target_os = "linux")]
+    pub fn send_more(&self, buf: &[u8]) -> io::Result<Option<usize>> {
+        self.sys.send_more(buf)
+    }
+
+    /// Sends up to `len` bytes of `file`, starting at `offset`, directly
+    /// from the kernel's page cache without copying them through userspace.
+    /// The file's own position is left untouched. A `len` of zero sends
+    /// nothing.
+    ///
+    /// Returns the number of bytes sent, which may be less than `len`, or
+    /// `Ok(None)` if the socket is not ready for writing. Callers continue
+    /// from `offset` plus the returned count once the socket is writable.
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+    pub fn send_file(&self, file: RawFd, offset: u64, len: usize) -> io::Result<Option<usize>> {
+        self.sys.send_file(file, offset, len)
+    }
+
     pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
         self.sys.set_nodelay(nodelay)
     }
@@ -153,6 +251,19 @@ impl TcpStream {
     pub fn take_socket_error(&self) -> io::Result<()> {
         self.sys.take_socket_error()
     }
+
+    /// Returns the CPU on which the kernel processed the most recent packet
+    /// for this connection (`SO_INCOMING_CPU`).
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<u32> {
+        self.sys.incoming_cpu()
+    }
+
+    /// Sets the `SO_INCOMING_CPU` hint for this connection.
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: u32) -> io::Result<()> {
+        self.sys.set_incoming_cpu(cpu)
+    }
 }
 
 impl Read for TcpStream {
@@ -171,6 +282,18 @@ impl Write for TcpStream {
     }
 }
 
+impl ReadVectored for TcpStream {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        self.sys.read_bufs(bufs)
+    }
+}
+
+impl WriteVectored for TcpStream {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        self.sys.write_bufs(bufs)
+    }
+}
+
 impl Evented for TcpStream {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.sys.register(selector, token, interest, opts)
@@ -185,9 +308,25 @@ impl Evented for TcpStream {
     }
 }
 
+// Returns the address in `slot`, looking it up and filling the slot if
+// it is empty
+fn cached_addr<F>(slot: &Mutex<Option<SocketAddr>>, lookup: F) -> io::Result<SocketAddr>
+    where F: FnOnce() -> io::Result<SocketAddr>
+{
+    let mut slot = slot.lock().unwrap();
+
+    if let Some(addr) = *slot {
+        return Ok(addr);
+    }
+
+    let addr = try!(lookup());
+    *slot = Some(addr);
+    Ok(addr)
+}
+
 impl From<sys::TcpSocket> for TcpStream {
     fn from(sys: sys::TcpSocket) -> TcpStream {
-        TcpStream { sys: sys }
+        TcpStream::new(sys)
     }
 }
 
@@ -197,9 +336,10 @@ impl From<sys::TcpSocket> for TcpStream {
  *
  */
 
-#[derive(Debug)]
 pub struct TcpListener {
     sys: sys::TcpSocket,
+    local: Option<SocketAddr>,
+    cache_addrs: bool,
 }
 
 impl TcpListener {
@@ -227,23 +367,68 @@ impl TcpListener {
     pub fn accept(&self) -> io::Result<Option<TcpStream>> {
         self.sys.accept()
             .map(|opt| {
-                opt.map(|sys| TcpStream { sys: sys })
+                opt.map(|sys| {
+                    if self.cache_addrs {
+                        TcpStream::cached(sys)
+                    } else {
+                        TcpStream::new(sys)
+                    }
+                })
             })
     }
 
+    /// Whether accepted streams look up and cache their addresses right
+    /// away. On by default; turning it off saves two syscalls per `accept`,
+    /// and the addresses are then looked up on first use instead.
+    pub fn set_cache_addrs(&mut self, cache: bool) {
+        self.cache_addrs = cache;
+    }
+
+    /// Returns the local address of the listener, cached when it was
+    /// created
     pub fn local_addr(&self) -> io::Result<SocketAddr> {
-        self.sys.local_addr()
+        match self.local {
+            Some(addr) => Ok(addr),
+            None => self.sys.local_addr(),
+        }
     }
 
     pub fn try_clone(&self) -> io::Result<TcpListener> {
+        let cache_addrs = self.cache_addrs;
+
         self.sys.try_clone()
-            .map(From::from)
+            .map(|sys| {
+                let mut listener = TcpListener::from(sys);
+                listener.cache_addrs = cache_addrs;
+                listener
+            })
+    }
+
+    /// Returns the `SO_INCOMING_CPU` value of the listening socket.
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<u32> {
+        self.sys.incoming_cpu()
+    }
+
+    /// Sets `SO_INCOMING_CPU` on the listening socket. When several
+    /// `SO_REUSEPORT` listeners share a port, the kernel prefers the one whose
+    /// value matches the CPU that received the connection request, which lets
+    /// each per-core event loop accept the connections steered to its core.
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: u32) -> io::Result<()> {
+        self.sys.set_incoming_cpu(cpu)
     }
 }
 
 impl From<sys::TcpSocket> for TcpListener {
     fn from(sys: sys::TcpSocket) -> TcpListener {
-        TcpListener { sys: sys }
+        let local = sys.local_addr().ok();
+
+        TcpListener {
+            sys: sys,
+            local: local,
+            cache_addrs: true,
+        }
     }
 }
 
@@ -276,7 +461,26 @@ impl TryAccept for TcpListener {
  */
 
 #[cfg(unix)]
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+#[cfg(unix)]
+use unix::Socket;
+
+#[cfg(unix)]
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "TcpStream {{ fd: {}, local: {}, peer: {} }}",
+               self.as_raw_fd(), super::DebugAddr(self.local_addr()), super::DebugAddr(self.peer_addr()))
+    }
+}
+
+#[cfg(unix)]
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "TcpListener {{ fd: {}, local: {} }}",
+               self.as_raw_fd(), super::DebugAddr(self.local_addr()))
+    }
+}
 
 #[cfg(unix)]
 impl AsRawFd for TcpSocket {
@@ -285,6 +489,13 @@ impl AsRawFd for TcpSocket {
     }
 }
 
+#[cfg(unix)]
+impl IntoRawFd for TcpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 #[cfg(unix)]
 impl FromRawFd for TcpSocket {
     unsafe fn from_raw_fd(fd: RawFd) -> TcpSocket {
@@ -299,10 +510,17 @@ impl AsRawFd for TcpStream {
     }
 }
 
+#[cfg(unix)]
+impl IntoRawFd for TcpStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 #[cfg(unix)]
 impl FromRawFd for TcpStream {
     unsafe fn from_raw_fd(fd: RawFd) -> TcpStream {
-        TcpStream { sys: FromRawFd::from_raw_fd(fd) }
+        TcpStream::new(FromRawFd::from_raw_fd(fd))
     }
 }
 
@@ -313,9 +531,29 @@ impl AsRawFd for TcpListener {
     }
 }
 
+#[cfg(unix)]
+impl IntoRawFd for TcpListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 #[cfg(unix)]
 impl FromRawFd for TcpListener {
     unsafe fn from_raw_fd(fd: RawFd) -> TcpListener {
-        TcpListener { sys: FromRawFd::from_raw_fd(fd) }
+        let sys: sys::TcpSocket = FromRawFd::from_raw_fd(fd);
+        From::from(sys)
     }
 }
+
+#[cfg(unix)]
+impl Socket for TcpSocket {
+}
+
+#[cfg(unix)]
+impl Socket for TcpStream {
+}
+
+#[cfg(unix)]
+impl Socket for TcpListener {
+}
@@ -1,8 +1,8 @@
 use {io, sys, Evented, EventSet, IpAddr, PollOpt, Selector, Token};
 use bytes::{Buf, MutBuf};
+use std::fmt;
 use std::net::SocketAddr;
 
-#[derive(Debug)]
 pub struct UdpSocket {
     sys: sys::UdpSocket,
 }
@@ -50,10 +50,33 @@ impl UdpSocket {
         self.sys.send_to(buf, target)
     }
 
+    /// Like `send_to`, but sets `MSG_MORE`: the data is held back and joined
+    /// with subsequent sends into a single datagram, which is sent by the
+    /// first send without the flag.
+    #[cfg(target_os = "linux")]
+    pub fn send_to_more<B: Buf>(&self, buf: &mut B, target: &SocketAddr) -> io::Result<Option<()>> {
+        self.sys.send_to_more(buf, target)
+    }
+
     pub fn recv_from<B: MutBuf>(&self, buf: &mut B) -> io::Result<Option<SocketAddr>> {
         self.sys.recv_from(buf)
     }
 
+    /// Enables reporting of the TTL (IPv4, `IP_RECVTTL`) or hop limit (IPv6,
+    /// `IPV6_RECVHOPLIMIT`) of received datagrams, returned by
+    /// `recv_from_ttl`. The option matching the socket's address family is
+    /// used, so it must be called after the socket is created with `v4` or
+    /// `v6`.
+    pub fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        self.sys.set_recv_ttl(on)
+    }
+
+    /// Like `recv_from`, but also returns the TTL or hop limit the datagram
+    /// arrived with, if `set_recv_ttl` has been enabled.
+    pub fn recv_from_ttl<B: MutBuf>(&self, buf: &mut B) -> io::Result<Option<(SocketAddr, Option<u8>)>> {
+        self.sys.recv_from_ttl(buf)
+    }
+
     pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
         self.sys.set_broadcast(on)
     }
@@ -73,6 +96,61 @@ impl UdpSocket {
     pub fn set_multicast_time_to_live(&self, ttl: i32) -> io::Result<()> {
         self.sys.set_multicast_time_to_live(ttl)
     }
+
+    /// Sets `IPV6_MULTICAST_HOPS`, the IPv6 counterpart of
+    /// `set_multicast_time_to_live`.
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        self.sys.set_multicast_hops_v6(hops)
+    }
+
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        self.sys.multicast_hops_v6()
+    }
+
+    /// Sets `IP_FREEBIND`, allowing the socket to be bound to an address
+    /// that is not (yet) configured on any local interface.
+    ///
+    /// Must be set before `bind`.
+    #[cfg(target_os = "linux")]
+    pub fn set_freebind(&self, on: bool) -> io::Result<()> {
+        self.sys.set_freebind(on)
+    }
+
+    /// Returns whether `IP_FREEBIND` is set on the socket.
+    #[cfg(target_os = "linux")]
+    pub fn freebind(&self) -> io::Result<bool> {
+        self.sys.freebind()
+    }
+
+    /// Sets `IP_TRANSPARENT`, allowing the socket to bind to and accept
+    /// traffic for non-local addresses, as used by transparent proxies.
+    /// Requires `CAP_NET_ADMIN`.
+    ///
+    /// Must be set before `bind`.
+    #[cfg(target_os = "linux")]
+    pub fn set_transparent(&self, on: bool) -> io::Result<()> {
+        self.sys.set_transparent(on)
+    }
+
+    /// Attaches a classic BPF program that selects which socket of a
+    /// `SO_REUSEPORT` group receives each incoming packet or connection
+    /// (`SO_ATTACH_REUSEPORT_CBPF`). The program returns the index of the
+    /// socket within the group; out of range values fall back to the default
+    /// hash.
+    ///
+    /// Must be set on a member of the group after `set_reuseport(true)`.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cbpf(&self, filter: &[::unix::bpf::SockFilter]) -> io::Result<()> {
+        self.sys.attach_reuseport_cbpf(filter)
+    }
+
+    /// Attaches a loaded eBPF program, given by its file descriptor, to the
+    /// socket's `SO_REUSEPORT` group (`SO_ATTACH_REUSEPORT_EBPF`). Behaves
+    /// like `attach_reuseport_cbpf`.
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog: RawFd) -> io::Result<()> {
+        self.sys.attach_reuseport_ebpf(prog)
+    }
 }
 
 impl Evented for UdpSocket {
@@ -102,7 +180,18 @@ impl From<sys::UdpSocket> for UdpSocket {
  */
 
 #[cfg(unix)]
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+#[cfg(unix)]
+use unix::Socket;
+
+#[cfg(unix)]
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "UdpSocket {{ fd: {}, local: {} }}",
+               self.as_raw_fd(), super::DebugAddr(self.local_addr()))
+    }
+}
 
 #[cfg(unix)]
 impl AsRawFd for UdpSocket {
@@ -111,9 +200,20 @@ impl AsRawFd for UdpSocket {
     }
 }
 
+#[cfg(unix)]
+impl IntoRawFd for UdpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 #[cfg(unix)]
 impl FromRawFd for UdpSocket {
     unsafe fn from_raw_fd(fd: RawFd) -> UdpSocket {
         UdpSocket { sys: FromRawFd::from_raw_fd(fd) }
     }
 }
+
+#[cfg(unix)]
+impl Socket for UdpSocket {
+}
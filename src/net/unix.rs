@@ -1,7 +1,13 @@
-use {io, sys, Evented, EventSet, Io, PollOpt, Selector, Token, TryAccept};
+use {io, sys, Evented, EventSet, Io, PollOpt, ReadVectored, Selector, Token, TryAccept, WriteVectored};
+use std::fmt;
 use std::io::{Read, Write};
 use std::path::Path;
 
+pub use sys::{ControlMessage, ControlMessageIter, ControlMessages, RecvMsg, Socket};
+
+#[cfg(target_os = "linux")]
+pub use sys::bpf;
+
 #[derive(Debug)]
 pub struct UnixSocket {
     sys: sys::UnixSocket,
@@ -63,7 +69,6 @@ impl From<sys::UnixSocket> for UnixSocket {
  *
  */
 
-#[derive(Debug)]
 pub struct UnixStream {
     sys: sys::UnixSocket,
 }
@@ -75,10 +80,35 @@ impl UnixStream {
             .map(|(sock, _)| sock)
     }
 
+    /// Returns a pair of connected streams, e.g. to run a client and a
+    /// server in one process without binding an address. How much one end
+    /// may write before the other reads can be bounded with
+    /// `Socket::set_send_buffer_size`, down to the kernel's minimum.
+    pub fn pair() -> io::Result<(UnixStream, UnixStream)> {
+        sys::UnixSocket::pair()
+            .map(|(a, b)| (From::from(a), From::from(b)))
+    }
+
     pub fn try_clone(&self) -> io::Result<UnixStream> {
         self.sys.try_clone()
             .map(From::from)
     }
+
+    /// Writes `buf` with `MSG_MORE` set, telling the kernel more data follows
+    /// immediately. Returns `Ok(None)` if the socket is not ready for
+    /// writing.
+    #[cfg(target_os = "linux")]
+    pub fn send_more(&self, buf: &[u8]) -> io::Result<Option<usize>> {
+        self.sys.send_more(buf)
+    }
+
+    /// Returns the security context (e.g. the SELinux label) of the peer
+    /// process, as reported by `SO_PEERSEC`. The label is returned as raw
+    /// bytes and may include a trailing nul.
+    #[cfg(target_os = "linux")]
+    pub fn peer_security_context(&self) -> io::Result<Vec<u8>> {
+        self.sys.peer_security_context()
+    }
 }
 
 impl Read for UnixStream {
@@ -97,6 +127,18 @@ impl Write for UnixStream {
     }
 }
 
+impl ReadVectored for UnixStream {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        self.sys.read_bufs(bufs)
+    }
+}
+
+impl WriteVectored for UnixStream {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        self.sys.write_bufs(bufs)
+    }
+}
+
 impl Evented for UnixStream {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.sys.register(selector, token, interest, opts)
@@ -123,7 +165,6 @@ impl From<sys::UnixSocket> for UnixStream {
  *
  */
 
-#[derive(Debug)]
 pub struct UnixListener {
     sys: sys::UnixSocket,
 }
@@ -197,6 +238,12 @@ impl Read for PipeReader {
     }
 }
 
+impl ReadVectored for PipeReader {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        self.io.read_bufs(bufs)
+    }
+}
+
 impl Evented for PipeReader {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.io.register(selector, token, interest, opts)
@@ -232,6 +279,12 @@ impl Write for PipeWriter {
     }
 }
 
+impl WriteVectored for PipeWriter {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        self.io.write_bufs(bufs)
+    }
+}
+
 impl Evented for PipeWriter {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.io.register(selector, token, interest, opts)
@@ -252,13 +305,880 @@ impl From<Io> for PipeWriter {
     }
 }
 
+/*
+ *
+ * ===== Splice =====
+ *
+ */
+
+/// Moves up to `len` bytes from `src` to `dst` without copying them through
+/// userspace (`splice(2)`). One of the two must be a pipe.
+///
+/// Returns `Ok(Some(0))` at end of stream and `Ok(None)` if either side is
+/// not ready.
+#[cfg(target_os = "linux")]
+pub fn splice<S: AsRawFd + ?Sized, D: AsRawFd + ?Sized>(src: &S, dst: &D, len: usize) -> io::Result<Option<usize>> {
+    sys::splice(src.as_raw_fd(), dst.as_raw_fd(), len)
+        .map(Some)
+        .or_else(io::to_non_block)
+}
+
+/// Copies up to `len` bytes from one pipe to another without consuming them
+/// from `src` (`tee(2)`), e.g. to mirror proxied traffic.
+///
+/// Returns `Ok(None)` if `src` is empty or `dst` is full.
+#[cfg(target_os = "linux")]
+pub fn tee(src: &PipeReader, dst: &PipeWriter, len: usize) -> io::Result<Option<usize>> {
+    sys::tee(src.as_raw_fd(), dst.as_raw_fd(), len)
+        .map(Some)
+        .or_else(io::to_non_block)
+}
+
+/// Zero-copy forwarding from one descriptor to another, such as the two
+/// sockets of a TCP proxy, through an intermediate kernel pipe.
+///
+/// Call `fill` when the source is readable and `drain` when the destination
+/// is writable. While `pending` is non-zero the destination should be
+/// registered for writable events; once the pipe is full `fill` returns
+/// `Ok(None)` until it has been drained.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct Splice {
+    rd: PipeReader,
+    wr: PipeWriter,
+    pending: usize,
+}
+
+#[cfg(target_os = "linux")]
+impl Splice {
+    pub fn new() -> io::Result<Splice> {
+        let (rd, wr) = try!(pipe());
+
+        Ok(Splice {
+            rd: rd,
+            wr: wr,
+            pending: 0,
+        })
+    }
+
+    /// Moves up to `len` bytes from `src` into the pipe. Returns
+    /// `Ok(Some(0))` once `src` reaches end of stream.
+    pub fn fill<S: AsRawFd + ?Sized>(&mut self, src: &S, len: usize) -> io::Result<Option<usize>> {
+        let res = try!(splice(src, &self.wr, len));
+
+        if let Some(cnt) = res {
+            self.pending += cnt;
+        }
+
+        Ok(res)
+    }
+
+    /// Moves as much of the buffered data as possible from the pipe to `dst`
+    pub fn drain<D: AsRawFd + ?Sized>(&mut self, dst: &D) -> io::Result<Option<usize>> {
+        if self.pending == 0 {
+            return Ok(Some(0));
+        }
+
+        let res = try!(splice(&self.rd, dst, self.pending));
+
+        if let Some(cnt) = res {
+            self.pending -= cnt;
+        }
+
+        Ok(res)
+    }
+
+    /// Number of bytes buffered in the pipe, waiting to be drained
+    pub fn pending(&self) -> usize {
+        self.pending
+    }
+}
+
+/*
+ *
+ * ===== Netlink =====
+ *
+ */
+
+/// A non-blocking netlink socket.
+///
+/// Only the socket mechanics are handled; building and parsing netlink
+/// messages is left to the user.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct NetlinkSocket {
+    sys: sys::NetlinkSocket,
+}
+
+#[cfg(target_os = "linux")]
+impl NetlinkSocket {
+    /// Returns a new, unbound, non-blocking netlink socket for the given
+    /// netlink protocol (for example `NETLINK_ROUTE`).
+    pub fn new(protocol: i32) -> io::Result<NetlinkSocket> {
+        sys::NetlinkSocket::new(protocol)
+            .map(From::from)
+    }
+
+    /// Returns a new netlink socket bound to `pid` and subscribed to the
+    /// multicast `groups` bitmask. A `pid` of 0 lets the kernel assign one.
+    pub fn bound(protocol: i32, pid: u32, groups: u32) -> io::Result<NetlinkSocket> {
+        let sock = try!(NetlinkSocket::new(protocol));
+        try!(sock.bind(pid, groups));
+        Ok(sock)
+    }
+
+    pub fn bind(&self, pid: u32, groups: u32) -> io::Result<()> {
+        self.sys.bind(pid, groups)
+    }
+
+    /// Returns the `(pid, groups)` pair that the socket is bound to
+    pub fn local_addr(&self) -> io::Result<(u32, u32)> {
+        self.sys.local_addr()
+    }
+
+    /// Subscribes to the multicast group with the given number. Unlike the
+    /// `groups` bitmask passed to `bind`, this works for groups above 32.
+    pub fn add_membership(&self, group: u32) -> io::Result<()> {
+        self.sys.add_membership(group)
+    }
+
+    pub fn drop_membership(&self, group: u32) -> io::Result<()> {
+        self.sys.drop_membership(group)
+    }
+
+    pub fn try_clone(&self) -> io::Result<NetlinkSocket> {
+        self.sys.try_clone()
+            .map(From::from)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Read for NetlinkSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.sys.read(buf)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Write for NetlinkSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sys.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sys.flush()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Evented for NetlinkSocket {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl From<sys::NetlinkSocket> for NetlinkSocket {
+    fn from(sys: sys::NetlinkSocket) -> NetlinkSocket {
+        NetlinkSocket { sys: sys }
+    }
+}
+
+/*
+ *
+ * ===== TUN/TAP =====
+ *
+ */
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub use sys::TunKind;
+
+/// A non-blocking TUN (layer 3) or TAP (layer 2) virtual network device.
+///
+/// Each read returns a single packet (or frame), and each write injects one.
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[derive(Debug)]
+pub struct TunDevice {
+    sys: sys::TunDevice,
+}
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl TunDevice {
+    /// Opens a TUN device. See [open](#method.open).
+    pub fn tun(name: &str) -> io::Result<TunDevice> {
+        TunDevice::open(TunKind::Tun, name, false)
+    }
+
+    /// Opens a TAP device. See [open](#method.open).
+    pub fn tap(name: &str) -> io::Result<TunDevice> {
+        TunDevice::open(TunKind::Tap, name, false)
+    }
+
+    /// Opens a new, non-blocking TUN or TAP device.
+    ///
+    /// On Linux, this opens `/dev/net/tun` and creates (or attaches to) the
+    /// interface `name`. An empty name, or one containing `%d`, lets the
+    /// kernel pick. When `packet_info` is set, each packet is prefixed with
+    /// the 4 byte flags / protocol header.
+    ///
+    /// On the BSDs, `name` must refer to an existing device node under `/dev`
+    /// and `packet_info` is not supported.
+    pub fn open(kind: TunKind, name: &str, packet_info: bool) -> io::Result<TunDevice> {
+        sys::TunDevice::open(kind, name, packet_info)
+            .map(|sys| TunDevice { sys: sys })
+    }
+
+    /// Returns the name of the interface, as assigned by the kernel
+    pub fn name(&self) -> &str {
+        self.sys.name()
+    }
+}
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl Read for TunDevice {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.sys.read(buf)
+    }
+}
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl Write for TunDevice {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sys.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sys.flush()
+    }
+}
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl Evented for TunDevice {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+/*
+ *
+ * ===== Vsock =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub use sys::VsockAddr;
+
+#[cfg(target_os = "linux")]
+use tcp::Shutdown;
+
+/// Context id that binds to any address
+#[cfg(target_os = "linux")]
+pub const VMADDR_CID_ANY: u32 = 0xffffffff;
+
+/// Context id of the host, as seen from a guest
+#[cfg(target_os = "linux")]
+pub const VMADDR_CID_HOST: u32 = 2;
+
+/// A non-blocking vsock stream, used for communication between virtual
+/// machines and their host.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct VsockStream {
+    sys: sys::VsockSocket,
+}
+
+#[cfg(target_os = "linux")]
+impl VsockStream {
+    /// Starts a non-blocking connect to the given address. The stream
+    /// becomes writable once the connection is established.
+    pub fn connect(addr: &VsockAddr) -> io::Result<VsockStream> {
+        let sys = try!(sys::VsockSocket::stream());
+        try!(sys.connect(addr));
+        Ok(VsockStream { sys: sys })
+    }
+
+    pub fn peer_addr(&self) -> io::Result<VsockAddr> {
+        self.sys.peer_addr()
+    }
+
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        self.sys.local_addr()
+    }
+
+    pub fn try_clone(&self) -> io::Result<VsockStream> {
+        self.sys.try_clone()
+            .map(|sys| VsockStream { sys: sys })
+    }
+
+    pub fn shutdown(&self, how: Shutdown) -> io::Result<()> {
+        self.sys.shutdown(how)
+    }
+
+    pub fn take_socket_error(&self) -> io::Result<()> {
+        self.sys.take_socket_error()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Read for VsockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.sys.read(buf)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Write for VsockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.sys.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sys.flush()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Evented for VsockStream {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+/// A non-blocking vsock listener
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct VsockListener {
+    sys: sys::VsockSocket,
+}
+
+#[cfg(target_os = "linux")]
+impl VsockListener {
+    pub fn bind(addr: &VsockAddr) -> io::Result<VsockListener> {
+        let sys = try!(sys::VsockSocket::stream());
+        try!(sys.bind(addr));
+        try!(sys.listen(256));
+        Ok(VsockListener { sys: sys })
+    }
+
+    /// Accepts a new `VsockStream`.
+    ///
+    /// Returns `Ok(None)` when no connection is pending.
+    pub fn accept(&self) -> io::Result<Option<VsockStream>> {
+        self.sys.accept()
+            .map(|opt| opt.map(|sys| VsockStream { sys: sys }))
+    }
+
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        self.sys.local_addr()
+    }
+
+    pub fn try_clone(&self) -> io::Result<VsockListener> {
+        self.sys.try_clone()
+            .map(|sys| VsockListener { sys: sys })
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Evented for VsockListener {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl TryAccept for VsockListener {
+    type Output = VsockStream;
+
+    fn accept(&self) -> io::Result<Option<VsockStream>> {
+        VsockListener::accept(self)
+    }
+}
+
+/*
+ *
+ * ===== Message queues =====
+ *
+ */
+
+/// A non-blocking POSIX message queue.
+///
+/// The queue is readable when it holds at least one message and writable
+/// when it has room for another.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+pub struct MessageQueue {
+    sys: sys::MessageQueue,
+}
+
+#[cfg(target_os = "linux")]
+impl MessageQueue {
+    /// Opens an existing queue for reading and writing. `name` must start
+    /// with a `/`.
+    pub fn open(name: &str) -> io::Result<MessageQueue> {
+        sys::MessageQueue::open(name)
+            .map(|sys| MessageQueue { sys: sys })
+    }
+
+    /// Opens the named queue, creating it with the given capacity if it
+    /// does not already exist.
+    pub fn create(name: &str, max_msgs: usize, msg_size: usize) -> io::Result<MessageQueue> {
+        sys::MessageQueue::create(name, max_msgs, msg_size, false)
+            .map(|sys| MessageQueue { sys: sys })
+    }
+
+    /// Creates a new queue, failing if one already exists with that name.
+    pub fn create_new(name: &str, max_msgs: usize, msg_size: usize) -> io::Result<MessageQueue> {
+        sys::MessageQueue::create(name, max_msgs, msg_size, true)
+            .map(|sys| MessageQueue { sys: sys })
+    }
+
+    /// Removes the named queue. Open descriptors remain valid.
+    pub fn unlink(name: &str) -> io::Result<()> {
+        sys::MessageQueue::unlink(name)
+    }
+
+    /// Sends a message with the given priority.
+    ///
+    /// Returns `Ok(None)` if the queue is full.
+    pub fn send(&self, msg: &[u8], prio: u32) -> io::Result<Option<()>> {
+        self.sys.send(msg, prio)
+    }
+
+    /// Receives the oldest, highest priority message, returning its length
+    /// and priority. `buf` must be at least as large as the queue's message
+    /// size.
+    ///
+    /// Returns `Ok(None)` if the queue is empty.
+    pub fn receive(&self, buf: &mut [u8]) -> io::Result<Option<(usize, u32)>> {
+        self.sys.receive(buf)
+    }
+
+    /// Returns the `(max_msgs, msg_size, current_msgs)` attributes of the
+    /// queue
+    pub fn attributes(&self) -> io::Result<(usize, usize, usize)> {
+        self.sys.attributes()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Evented for MessageQueue {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+/*
+ *
+ * ===== FileWatch =====
+ *
+ */
+
+/// Watches a file or directory for changes using kqueue's `EVFILT_VNODE`.
+///
+/// The watch becomes readable when changes are pending; call
+/// [poll](#method.poll) to retrieve (and clear) them.
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub struct FileWatch {
+    sys: sys::FileWatch,
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl FileWatch {
+    pub fn new<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<FileWatch> {
+        sys::FileWatch::new(path)
+            .map(|sys| FileWatch { sys: sys })
+    }
+
+    /// Returns the changes observed since the last call. The returned set is
+    /// empty if nothing happened.
+    pub fn poll(&self) -> io::Result<FileEvents> {
+        self.sys.poll()
+            .map(FileEvents)
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl Evented for FileWatch {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl fmt::Debug for FileWatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "FileWatch {{ fd: {} }}", self.sys.file_fd())
+    }
+}
+
+/// The set of changes reported by a `FileWatch`
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[derive(Copy, PartialEq, Eq, Clone)]
+pub struct FileEvents(u32);
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl FileEvents {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The file was unlinked
+    #[inline]
+    pub fn is_delete(&self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    /// The file contents were written to
+    #[inline]
+    pub fn is_write(&self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    /// The file was extended
+    #[inline]
+    pub fn is_extend(&self) -> bool {
+        self.0 & 0x04 != 0
+    }
+
+    /// The file attributes changed
+    #[inline]
+    pub fn is_attrib(&self) -> bool {
+        self.0 & 0x08 != 0
+    }
+
+    /// The link count changed
+    #[inline]
+    pub fn is_link(&self) -> bool {
+        self.0 & 0x10 != 0
+    }
+
+    /// The file was renamed
+    #[inline]
+    pub fn is_rename(&self) -> bool {
+        self.0 & 0x20 != 0
+    }
+
+    /// Access to the file was revoked, or the file system was unmounted
+    #[inline]
+    pub fn is_revoke(&self) -> bool {
+        self.0 & 0x40 != 0
+    }
+
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl fmt::Debug for FileEvents {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(fmt, "(empty)");
+        }
+
+        let mut one = false;
+        let flags = [
+            (self.is_delete(), "Delete"),
+            (self.is_write(),  "Write"),
+            (self.is_extend(), "Extend"),
+            (self.is_attrib(), "Attrib"),
+            (self.is_link(),   "Link"),
+            (self.is_rename(), "Rename"),
+            (self.is_revoke(), "Revoke")];
+
+        for &(set, msg) in flags.iter() {
+            if set {
+                if one { try!(write!(fmt, " | ")) }
+                try!(write!(fmt, "{}", msg));
+
+                one = true
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/*
+ *
+ * ===== ProcWatch =====
+ *
+ */
+
+/// Watches a process for fork, exec, and exit events using kqueue's
+/// `EVFILT_PROC`.
+///
+/// The watch becomes readable when events are pending; call
+/// [poll](#method.poll) to retrieve (and clear) them. Once the process has
+/// exited, no further events are reported.
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub struct ProcWatch {
+    sys: sys::ProcWatch,
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl ProcWatch {
+    pub fn new(pid: i32) -> io::Result<ProcWatch> {
+        sys::ProcWatch::new(pid)
+            .map(|sys| ProcWatch { sys: sys })
+    }
+
+    /// Returns the events observed since the last call. The returned set is
+    /// empty if nothing happened.
+    pub fn poll(&self) -> io::Result<ProcEvents> {
+        self.sys.poll()
+            .map(ProcEvents)
+    }
+
+    pub fn pid(&self) -> i32 {
+        self.sys.pid()
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl Evented for ProcWatch {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.sys.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.sys.deregister(selector)
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl fmt::Debug for ProcWatch {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "ProcWatch {{ pid: {} }}", self.sys.pid())
+    }
+}
+
+/// The set of events reported by a `ProcWatch`
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+#[derive(Copy, PartialEq, Eq, Clone)]
+pub struct ProcEvents(u32);
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl ProcEvents {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The process exited
+    #[inline]
+    pub fn is_exit(&self) -> bool {
+        self.0 & 0x80000000 != 0
+    }
+
+    /// The process forked
+    #[inline]
+    pub fn is_fork(&self) -> bool {
+        self.0 & 0x40000000 != 0
+    }
+
+    /// The process executed a new image
+    #[inline]
+    pub fn is_exec(&self) -> bool {
+        self.0 & 0x20000000 != 0
+    }
+
+    #[inline]
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl fmt::Debug for ProcEvents {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return write!(fmt, "(empty)");
+        }
+
+        let mut one = false;
+        let flags = [
+            (self.is_exit(), "Exit"),
+            (self.is_fork(), "Fork"),
+            (self.is_exec(), "Exec")];
+
+        for &(set, msg) in flags.iter() {
+            if set {
+                if one { try!(write!(fmt, " | ")) }
+                try!(write!(fmt, "{}", msg));
+
+                one = true
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /*
  *
  * ===== Conversions =====
  *
  */
 
-use std::os::unix::io::{RawFd, AsRawFd, FromRawFd};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+
+impl Socket for UnixSocket {
+}
+
+impl Socket for UnixStream {
+}
+
+impl Socket for UnixListener {
+}
 
 impl AsRawFd for UnixSocket {
     fn as_raw_fd(&self) -> RawFd {
@@ -266,30 +1186,60 @@ impl AsRawFd for UnixSocket {
     }
 }
 
+impl IntoRawFd for UnixSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 impl FromRawFd for UnixSocket {
     unsafe fn from_raw_fd(fd: RawFd) -> UnixSocket {
         UnixSocket { sys: FromRawFd::from_raw_fd(fd) }
     }
 }
 
+impl fmt::Debug for UnixStream {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "UnixStream {{ fd: {} }}", self.as_raw_fd())
+    }
+}
+
 impl AsRawFd for UnixStream {
     fn as_raw_fd(&self) -> RawFd {
         self.sys.as_raw_fd()
     }
 }
 
+impl IntoRawFd for UnixStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 impl FromRawFd for UnixStream {
     unsafe fn from_raw_fd(fd: RawFd) -> UnixStream {
         UnixStream { sys: FromRawFd::from_raw_fd(fd) }
     }
 }
 
+impl fmt::Debug for UnixListener {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "UnixListener {{ fd: {} }}", self.as_raw_fd())
+    }
+}
+
 impl AsRawFd for UnixListener {
     fn as_raw_fd(&self) -> RawFd {
         self.sys.as_raw_fd()
     }
 }
 
+impl IntoRawFd for UnixListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
 impl FromRawFd for UnixListener {
     unsafe fn from_raw_fd(fd: RawFd) -> UnixListener {
         UnixListener { sys: FromRawFd::from_raw_fd(fd) }
@@ -302,6 +1252,12 @@ impl AsRawFd for PipeReader {
     }
 }
 
+impl IntoRawFd for PipeReader {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
+
 impl FromRawFd for PipeReader {
     unsafe fn from_raw_fd(fd: RawFd) -> PipeReader {
         PipeReader { io: FromRawFd::from_raw_fd(fd) }
@@ -314,8 +1270,145 @@ impl AsRawFd for PipeWriter {
     }
 }
 
+impl IntoRawFd for PipeWriter {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
+
 impl FromRawFd for PipeWriter {
     unsafe fn from_raw_fd(fd: RawFd) -> PipeWriter {
         PipeWriter { io: FromRawFd::from_raw_fd(fd) }
     }
 }
+
+#[cfg(target_os = "linux")]
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IntoRawFd for NetlinkSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FromRawFd for NetlinkSocket {
+    unsafe fn from_raw_fd(fd: RawFd) -> NetlinkSocket {
+        NetlinkSocket { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl AsRawFd for TunDevice {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AsRawFd for VsockStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IntoRawFd for VsockStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FromRawFd for VsockStream {
+    unsafe fn from_raw_fd(fd: RawFd) -> VsockStream {
+        VsockStream { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AsRawFd for VsockListener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IntoRawFd for VsockListener {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FromRawFd for VsockListener {
+    unsafe fn from_raw_fd(fd: RawFd) -> VsockListener {
+        VsockListener { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl AsRawFd for MessageQueue {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl IntoRawFd for MessageQueue {
+    fn into_raw_fd(self) -> RawFd {
+        self.sys.into_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl FromRawFd for MessageQueue {
+    unsafe fn from_raw_fd(fd: RawFd) -> MessageQueue {
+        MessageQueue { sys: FromRawFd::from_raw_fd(fd) }
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl AsRawFd for FileWatch {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+impl AsRawFd for ProcWatch {
+    fn as_raw_fd(&self) -> RawFd {
+        self.sys.as_raw_fd()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Socket for NetlinkSocket {
+}
+
+#[cfg(target_os = "linux")]
+impl Socket for VsockStream {
+}
+
+#[cfg(target_os = "linux")]
+impl Socket for VsockListener {
+}
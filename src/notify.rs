@@ -1,9 +1,9 @@
 use {sys, Evented, EventSet, PollOpt, Selector, Token};
 use util::BoundedQueue;
 use std::{fmt, cmp, io};
-use std::sync::Arc;
-use std::sync::atomic::AtomicIsize;
-use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize};
+use std::sync::atomic::Ordering::{Relaxed, SeqCst};
 
 const SLEEP: isize = -1;
 const CLOSED: isize = -2;
@@ -12,7 +12,8 @@ const CLOSED: isize = -2;
 /// event loop is not currently sleeping, avoid using an OS wake-up strategy
 /// (eventfd, pipe, ...). Backed by a pre-allocated lock free MPMC queue.
 ///
-/// TODO: Use more efficient wake-up strategy if available
+/// Only a send that finds the loop asleep wakes it, so any number of sends
+/// between two polls cost at most one wakeup.
 pub struct Notify<M: Send> {
     inner: Arc<NotifyInner<M>>
 }
@@ -35,11 +36,22 @@ impl<M: Send> Notify<M> {
         self.inner.notify(value)
     }
 
+    /// Like `notify`, but waits for room in the queue if it is full
+    pub fn notify_blocking(&self, value: M) -> Result<(), NotifyError<M>> {
+        self.inner.notify_blocking(value)
+    }
+
     #[inline]
     pub fn poll(&self) -> Option<M> {
         self.inner.poll()
     }
 
+    /// Number of messages sent but not yet picked up by `check`
+    #[cfg(feature = "metrics")]
+    pub fn pending(&self) -> usize {
+        cmp::max(self.inner.state.load(Relaxed), 0) as usize
+    }
+
     #[inline]
     pub fn cleanup(&self) {
         self.inner.cleanup();
@@ -49,6 +61,17 @@ impl<M: Send> Notify<M> {
     pub fn close(&self) {
         self.inner.close();
     }
+
+    #[inline]
+    pub fn shutdown(&self) -> io::Result<()> {
+        self.inner.shutdown()
+    }
+
+    /// Returns whether a shutdown was requested since the last call
+    #[inline]
+    pub fn take_shutdown(&self) -> bool {
+        self.inner.shutdown.swap(false, SeqCst)
+    }
 }
 
 impl<M: Send> Clone for Notify<M> {
@@ -71,7 +94,14 @@ unsafe impl<M: Send> Send for Notify<M> { }
 struct NotifyInner<M> {
     state: AtomicIsize,
     queue: BoundedQueue<M>,
-    awaken: sys::Awakener
+    awaken: sys::Awakener,
+    // Set when another thread asks the event loop to stop
+    shutdown: AtomicBool,
+    // Senders waiting for room in the queue, signalled as messages are
+    // taken off it
+    blocked: AtomicUsize,
+    room_lock: Mutex<()>,
+    room: Condvar,
 }
 
 impl<M: Send> NotifyInner<M> {
@@ -79,7 +109,11 @@ impl<M: Send> NotifyInner<M> {
         Ok(NotifyInner {
             state: AtomicIsize::new(0),
             queue: BoundedQueue::with_capacity(capacity),
-            awaken: try!(sys::Awakener::new())
+            awaken: try!(sys::Awakener::new()),
+            shutdown: AtomicBool::new(false),
+            blocked: AtomicUsize::new(0),
+            room_lock: Mutex::new(()),
+            room: Condvar::new(),
         })
     }
 
@@ -126,7 +160,44 @@ impl<M: Send> NotifyInner<M> {
     }
 
     fn poll(&self) -> Option<M> {
-        self.queue.pop()
+        let msg = self.queue.pop();
+
+        if msg.is_some() {
+            self.wake_blocked();
+        }
+
+        msg
+    }
+
+    fn notify_blocking(&self, mut value: M) -> Result<(), NotifyError<M>> {
+        match self.notify(value) {
+            Err(NotifyError::Full(v)) => value = v,
+            res => return res,
+        }
+
+        let mut lock = self.room_lock.lock().unwrap();
+        self.blocked.fetch_add(1, SeqCst);
+
+        // Retried with the lock held, so that a message taken off the queue
+        // after a failed attempt always finds this sender waiting
+        let mut res = self.notify(value);
+
+        while let Err(NotifyError::Full(v)) = res {
+            lock = self.room.wait(lock).unwrap();
+            res = self.notify(v);
+        }
+
+        self.blocked.fetch_sub(1, SeqCst);
+        drop(lock);
+        res
+    }
+
+    // Wakes up senders waiting in `notify_blocking`, if any
+    fn wake_blocked(&self) {
+        if self.blocked.load(SeqCst) > 0 {
+            let _lock = self.room_lock.lock().unwrap();
+            self.room.notify_all();
+        }
     }
 
     fn notify(&self, value: M) -> Result<(), NotifyError<M>> {
@@ -180,11 +251,26 @@ impl<M: Send> NotifyInner<M> {
         while let Some(m) = self.queue.pop() {
             drop(m);
         }
+
+        // Blocked senders find the queue closed on their next attempt
+        self.wake_blocked();
     }
 
     fn cleanup(&self) {
         self.awaken.cleanup();
     }
+
+    fn shutdown(&self) -> io::Result<()> {
+        self.shutdown.store(true, SeqCst);
+
+        if self.state.load(Relaxed) == CLOSED {
+            return Ok(());
+        }
+
+        // Always wake the loop up; if it is busy, the spurious wakeup is
+        // simply cleaned up on the next iteration
+        self.awaken.wakeup()
+    }
 }
 
 impl<M: Send> Evented for Notify<M> {
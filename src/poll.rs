@@ -1,12 +1,64 @@
 use {sys, Evented, Token};
 use event::{EventSet, IoEvent, PollOpt};
-use std::{fmt, io};
+#[cfg(feature = "testing")]
+use faults::{Faults, FaultState};
+use instrument;
+use std::{cmp, fmt, io, slice};
+use std::iter::Cloned;
+use std::ops::{Deref, DerefMut};
+use std::os::unix::io::RawFd;
+use std::time::{Duration, Instant};
 
 pub use sys::{Events};
 
 pub struct Poll {
     selector: sys::Selector,
     events: sys::Events,
+    spin: Option<Duration>,
+    injected: Option<Box<Injected>>,
+    #[cfg(feature = "metrics")]
+    stats: PollStats,
+}
+
+// Events reported by `poll` besides the selector's. Only allocated once
+// faults or synthetic events are used.
+struct Injected {
+    #[cfg(feature = "testing")]
+    faults: Option<FaultState>,
+    // Synthetic events for the next poll
+    queued: Vec<IoEvent>,
+    // Events added to those of the last poll
+    events: Vec<IoEvent>,
+}
+
+impl Injected {
+    // Rolls the faults due before polling, returning whether to skip waiting
+    #[cfg(feature = "testing")]
+    fn before_poll(&mut self) -> io::Result<bool> {
+        match self.faults {
+            Some(ref mut faults) => faults.before_poll(),
+            None => Ok(false),
+        }
+    }
+
+    #[cfg(not(feature = "testing"))]
+    #[inline]
+    fn before_poll(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+
+    // Adds a spurious event to those of the last poll, if one is due
+    #[cfg(feature = "testing")]
+    fn after_poll(&mut self, events: &Events) {
+        if let Some(evt) = self.faults.as_mut().and_then(|faults| faults.after_poll(events)) {
+            self.events.push(evt);
+        }
+    }
+
+    #[cfg(not(feature = "testing"))]
+    #[inline]
+    fn after_poll(&mut self, _: &Events) {
+    }
 }
 
 impl Poll {
@@ -14,9 +66,63 @@ impl Poll {
         Ok(Poll {
             selector: try!(sys::Selector::new()),
             events: sys::Events::new(),
+            spin: None,
+            injected: None,
+            #[cfg(feature = "metrics")]
+            stats: PollStats::default(),
         })
     }
 
+    /// Returns a new `Poll` that reports at most `events` readiness events
+    /// per call to `poll`
+    pub fn with_capacity(events: usize) -> io::Result<Poll> {
+        Ok(Poll {
+            selector: try!(sys::Selector::new()),
+            events: sys::Events::with_capacity(events),
+            spin: None,
+            injected: None,
+            #[cfg(feature = "metrics")]
+            stats: PollStats::default(),
+        })
+    }
+
+    /// Makes `poll` busy-poll the selector for up to `spin` before blocking,
+    /// trading CPU time for lower wakeup latency. Off (`None`) by default.
+    pub fn set_spin(&mut self, spin: Option<Duration>) {
+        self.spin = spin;
+    }
+
+    /// Injects the given faults into every subsequent call to `poll`, for
+    /// testing; `None` turns injection off again. Only available with the
+    /// `testing` feature.
+    #[cfg(feature = "testing")]
+    pub fn set_faults(&mut self, faults: Option<Faults>) {
+        self.injected_mut().faults = faults.map(FaultState::new);
+    }
+
+    /// Queues a synthetic event for `token`, reported by the next call to
+    /// `poll` after the selector's own events. `poll` does not block while
+    /// synthetic events are queued.
+    ///
+    /// Together with `util::MockStream` (`testing` feature), this drives
+    /// handlers through scripted scenarios without real sockets.
+    pub fn inject(&mut self, token: Token, events: EventSet) {
+        self.injected_mut().queued.push(IoEvent::new(events, token));
+    }
+
+    fn injected_mut(&mut self) -> &mut Injected {
+        if self.injected.is_none() {
+            self.injected = Some(Box::new(Injected {
+                #[cfg(feature = "testing")]
+                faults: None,
+                queued: Vec::new(),
+                events: Vec::new(),
+            }));
+        }
+
+        self.injected.as_mut().unwrap()
+    }
+
     pub fn register<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
         where E: Evented
     {
@@ -39,6 +145,19 @@ impl Poll {
         Ok(())
     }
 
+    /// Registers `io` and hands it back wrapped in a guard that deregisters
+    /// it when dropped
+    pub fn register_owned<E>(&mut self, io: E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<Registered<E>>
+        where E: Evented
+    {
+        try!(self.register(&io, token, interest, opts));
+
+        Ok(Registered {
+            io: Some(io),
+            selector: self.selector.share(),
+        })
+    }
+
     pub fn deregister<E: ?Sized>(&mut self, io: &E) -> io::Result<()>
         where E: Evented
     {
@@ -50,13 +169,245 @@ impl Poll {
         Ok(())
     }
 
+    /// Deregisters every handle in `ios`, e.g. when shutting down. Each
+    /// handle is attempted even if an earlier one fails; the first error is
+    /// returned. On kqueue the removals are submitted together with the
+    /// next poll.
+    pub fn deregister_all<E: ?Sized>(&mut self, ios: &[&E]) -> io::Result<()>
+        where E: Evented
+    {
+        trace!("deregistering {} IO handles with poller", ios.len());
+
+        let mut res = Ok(());
+
+        for io in ios {
+            if let Err(e) = io.deregister(&mut self.selector) {
+                if res.is_ok() {
+                    res = Err(e);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns the counters kept by `poll` since the poller was created or
+    /// `reset_stats` was last called. Only available with the `metrics`
+    /// feature.
+    #[cfg(feature = "metrics")]
+    pub fn stats(&self) -> &PollStats {
+        &self.stats
+    }
+
+    #[cfg(feature = "metrics")]
+    pub fn reset_stats(&mut self) {
+        self.stats = PollStats::default();
+    }
+
+    #[cfg(feature = "metrics")]
     pub fn poll(&mut self, timeout_ms: usize) -> io::Result<usize> {
+        instrument::poll_begin(timeout_ms);
+
+        let start = Instant::now();
+        let res = self.poll_events(timeout_ms);
+
+        instrument::poll_end(&res);
+
+        let stats = &mut self.stats;
+        stats.last_poll = start.elapsed();
+
+        match res {
+            Ok(cnt) => {
+                stats.wakeups += 1;
+                stats.events += cnt as u64;
+            }
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => {
+                stats.interrupts += 1;
+            }
+            Err(_) => {}
+        }
+
+        res
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    pub fn poll(&mut self, timeout_ms: usize) -> io::Result<usize> {
+        instrument::poll_begin(timeout_ms);
+        let res = self.poll_events(timeout_ms);
+        instrument::poll_end(&res);
+        res
+    }
+
+    fn poll_events(&mut self, mut timeout_ms: usize) -> io::Result<usize> {
+        if let Some(ref mut injected) = self.injected {
+            injected.events.clear();
+
+            if try!(injected.before_poll()) {
+                timeout_ms = 0;
+            }
+
+            if !injected.queued.is_empty() {
+                timeout_ms = 0;
+            }
+        }
+
+        let cnt = try!(self.poll_selector(timeout_ms));
+
+        if let Some(ref mut injected) = self.injected {
+            let injected = &mut **injected;
+
+            injected.events.extend(injected.queued.drain(..));
+            injected.after_poll(&self.events);
+
+            return Ok(cnt + injected.events.len());
+        }
+
+        Ok(cnt)
+    }
+
+    fn poll_selector(&mut self, mut timeout_ms: usize) -> io::Result<usize> {
+        if let (Some(spin), true) = (self.spin, timeout_ms > 0) {
+            // Never spin past the caller's timeout
+            let spin = cmp::min(spin, Duration::from_millis(timeout_ms as u64));
+            let start = Instant::now();
+
+            loop {
+                self.count_select();
+                try!(self.selector.select(&mut self.events, 0));
+
+                if self.events.len() > 0 {
+                    return Ok(self.events.len());
+                }
+
+                let elapsed = start.elapsed();
+
+                if elapsed >= spin {
+                    let elapsed_ms = elapsed.as_secs() as usize * 1_000 +
+                        elapsed.subsec_nanos() as usize / 1_000_000;
+                    timeout_ms = timeout_ms.saturating_sub(elapsed_ms);
+                    break;
+                }
+            }
+        }
+
+        self.count_select();
         try!(self.selector.select(&mut self.events, timeout_ms));
         Ok(self.events.len())
     }
 
     pub fn event(&self, idx: usize) -> IoEvent {
-        self.events.get(idx)
+        if idx < self.events.len() {
+            return self.events.get(idx);
+        }
+
+        self.injected()[idx - self.events.len()]
+    }
+
+    #[cfg(feature = "metrics")]
+    fn count_select(&mut self) {
+        self.stats.selects += 1;
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline]
+    fn count_select(&mut self) {
+    }
+
+    // Synthetic and spurious events appended to the selector's
+    fn injected(&self) -> &[IoEvent] {
+        match self.injected {
+            Some(ref injected) => &injected.events[..],
+            None => &[],
+        }
+    }
+
+    /// Keeps track of the token each handle is registered under, so that
+    /// `deregister_token` can remove handles that are not at hand. Costs a
+    /// locked map update per registration change, so it is off unless
+    /// called; `EventLoop` calls it when configured with a dispatch limit or
+    /// `catch_panics`.
+    pub fn track_tokens(&mut self) {
+        self.selector.track_tokens();
+    }
+
+    /// Deregisters every handle registered under `token` since
+    /// `track_tokens` was called. Fails if tokens are not tracked.
+    pub fn deregister_token(&mut self, token: Token) -> io::Result<()> {
+        trace!("deregistering token with poller; token={:?}", token);
+        self.selector.deregister_token(token)
+    }
+
+    /// Whether any handle is registered under `token` since `track_tokens`
+    /// was called. Fails if tokens are not tracked.
+    pub fn has_token(&self, token: Token) -> io::Result<bool> {
+        self.selector.has_token(token)
+    }
+
+    /// Lists the handles currently registered, as recorded by the OS
+    /// selector. Meant for diagnostics; only supported with epoll.
+    pub fn registrations(&self) -> io::Result<Vec<Registration>> {
+        self.selector.registrations()
+    }
+
+    /// Iterates over the events returned by the last call to `poll`. Each
+    /// event is converted from the selector's buffer as it is reached.
+    pub fn events(&self) -> EventsIter {
+        EventsIter {
+            events: &self.events,
+            injected: self.injected(),
+            pos: 0,
+        }
+    }
+}
+
+/// A handle registered with a `Poll`, see `Poll::registrations`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Registration {
+    /// The registered file descriptor
+    pub fd: RawFd,
+    pub token: Token,
+    pub interest: EventSet,
+    pub opts: PollOpt,
+    /// The event mask held by the selector, e.g. the `epoll_event.events`
+    /// bits. A oneshot registration that has fired keeps no interest.
+    pub sys_events: u32,
+}
+
+/// Iterator over the events of a `Poll`, see `Poll::events`
+pub struct EventsIter<'a> {
+    events: &'a sys::Events,
+    injected: &'a [IoEvent],
+    pos: usize,
+}
+
+impl<'a> Iterator for EventsIter<'a> {
+    type Item = IoEvent;
+
+    fn next(&mut self) -> Option<IoEvent> {
+        let len = self.events.len();
+
+        let evt = if self.pos < len {
+            self.events.get(self.pos)
+        } else if self.pos - len < self.injected.len() {
+            self.injected[self.pos - len]
+        } else {
+            return None;
+        };
+
+        self.pos += 1;
+        Some(evt)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let rem = self.events.len() + self.injected.len() - self.pos;
+        (rem, Some(rem))
+    }
+}
+
+impl<'a> fmt::Debug for EventsIter<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "EventsIter {{ remaining: {} }}", self.events.len() + self.injected.len() - self.pos)
     }
 }
 
@@ -66,3 +417,221 @@ impl fmt::Debug for Poll {
     }
 }
 
+/// Counters kept by a `Poll` built with the `metrics` feature, see
+/// `Poll::stats`
+#[cfg(feature = "metrics")]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PollStats {
+    /// Calls into the OS selector (`epoll_wait`, `kevent`). Busy-polling
+    /// makes several per call to `poll`.
+    pub selects: u64,
+    /// Calls to `poll` that failed with `ErrorKind::Interrupted`, e.g. on
+    /// `EINTR`. The event loop retries these on its next iteration.
+    pub interrupts: u64,
+    /// Calls to `poll` that returned, with or without events
+    pub wakeups: u64,
+    /// Events reported over all wakeups
+    pub events: u64,
+    /// Time spent in the last call to `poll`, including any wait
+    pub last_poll: Duration,
+}
+
+#[cfg(feature = "metrics")]
+impl PollStats {
+    /// Average number of events reported per wakeup. A value close to the
+    /// poller's event capacity means it is too small to drain the ready
+    /// handles in one go.
+    pub fn events_per_wakeup(&self) -> f64 {
+        if self.wakeups == 0 {
+            return 0.0;
+        }
+
+        self.events as f64 / self.wakeups as f64
+    }
+}
+
+/// An IO handle that is deregistered from its `Poll` when dropped.
+///
+/// Returned by `register_owned`. The guard dereferences to the handle, and
+/// may outlive the `Poll` or `EventLoop` that created it.
+pub struct Registered<E: Evented> {
+    io: Option<E>,
+    selector: sys::Selector,
+}
+
+impl<E: Evented> Registered<E> {
+    /// Deregisters the handle and returns it. If deregistering fails, the
+    /// error is returned along with the guard, which still owns the handle.
+    pub fn into_inner(mut self) -> Result<E, (io::Error, Registered<E>)> {
+        if let Err(err) = self.deregister() {
+            return Err((err, self));
+        }
+
+        Ok(self.io.take().unwrap())
+    }
+
+    fn deregister(&mut self) -> io::Result<()> {
+        try!(self.io.as_ref().unwrap().deregister(&mut self.selector));
+
+        // On kqueue this also submits changes still queued by the poller the
+        // handle was registered with, ahead of the removal
+        self.selector.flush()
+    }
+}
+
+impl<E: Evented> Deref for Registered<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.io.as_ref().unwrap()
+    }
+}
+
+impl<E: Evented> DerefMut for Registered<E> {
+    fn deref_mut(&mut self) -> &mut E {
+        self.io.as_mut().unwrap()
+    }
+}
+
+impl<E: Evented> Drop for Registered<E> {
+    fn drop(&mut self) {
+        if self.io.is_some() {
+            if let Err(err) = self.deregister() {
+                debug!("failed to deregister IO handle; err={:?}", err);
+            }
+        }
+    }
+}
+
+impl<E: Evented + fmt::Debug> fmt::Debug for Registered<E> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Registered({:?})", self.io.as_ref().unwrap())
+    }
+}
+
+
+/// A poller that spreads registrations over several OS selectors, for
+/// processes with very large numbers of registered handles.
+///
+/// A handle lives in the shard picked by its token (`token % shards`), so
+/// the same token must be passed to every call for that handle. The shard
+/// selectors are nested in a root selector, and `poll` collects the events
+/// of every shard that is ready.
+pub struct ShardedPoll {
+    root: sys::Selector,
+    root_events: sys::Events,
+    shards: Vec<Poll>,
+    events: Vec<IoEvent>,
+}
+
+impl ShardedPoll {
+    /// Returns a new `ShardedPoll` with `shards` selectors. Fails with
+    /// `ErrorKind::InvalidInput` if `shards` is zero.
+    pub fn new(shards: usize) -> io::Result<ShardedPoll> {
+        ShardedPoll::build(shards, Poll::new)
+    }
+
+    /// Returns a new `ShardedPoll` in which each shard reports at most
+    /// `events` readiness events per call to `poll`
+    pub fn with_capacity(shards: usize, events: usize) -> io::Result<ShardedPoll> {
+        ShardedPoll::build(shards, || Poll::with_capacity(events))
+    }
+
+    fn build<F>(shards: usize, new_shard: F) -> io::Result<ShardedPoll>
+        where F: Fn() -> io::Result<Poll>
+    {
+        if shards == 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "a ShardedPoll needs at least one shard"));
+        }
+
+        let mut root = try!(sys::Selector::new());
+        let mut polls = Vec::with_capacity(shards);
+
+        for i in 0..shards {
+            let shard = try!(new_shard());
+            try!(Evented::register(&shard.selector, &mut root, Token(i), EventSet::readable(), PollOpt::level()));
+            polls.push(shard);
+        }
+
+        Ok(ShardedPoll {
+            root: root,
+            root_events: sys::Events::with_capacity(shards),
+            shards: polls,
+            events: Vec::new(),
+        })
+    }
+
+    /// Number of shards
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// The shard that handles registered under `token` are placed in
+    pub fn shard(&self, token: Token) -> usize {
+        token.as_usize() % self.shards.len()
+    }
+
+    pub fn register<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
+        where E: Evented
+    {
+        let idx = self.shard(token);
+        let shard = &mut self.shards[idx];
+
+        try!(shard.register(io, token, interest, opts));
+        shard.selector.flush()
+    }
+
+    pub fn reregister<E: ?Sized>(&mut self, io: &E, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()>
+        where E: Evented
+    {
+        let idx = self.shard(token);
+        let shard = &mut self.shards[idx];
+
+        try!(shard.reregister(io, token, interest, opts));
+        shard.selector.flush()
+    }
+
+    /// Deregisters `io`, which must have been registered under `token`
+    pub fn deregister<E: ?Sized>(&mut self, io: &E, token: Token) -> io::Result<()>
+        where E: Evented
+    {
+        let idx = self.shard(token);
+        let shard = &mut self.shards[idx];
+
+        try!(shard.deregister(io));
+        shard.selector.flush()
+    }
+
+    /// Waits for events on any shard. A shard with more pending events than
+    /// its capacity stays ready and is drained further by the next call.
+    pub fn poll(&mut self, timeout_ms: usize) -> io::Result<usize> {
+        self.events.clear();
+
+        try!(self.root.select(&mut self.root_events, timeout_ms));
+
+        for i in 0..self.root_events.len() {
+            let idx = self.root_events.get(i).token.as_usize();
+            let shard = &mut self.shards[idx];
+
+            try!(shard.poll(0));
+            self.events.extend(shard.events());
+        }
+
+        Ok(self.events.len())
+    }
+
+    pub fn event(&self, idx: usize) -> IoEvent {
+        self.events[idx]
+    }
+
+    /// Iterates over the events returned by the last call to `poll`
+    pub fn events(&self) -> Cloned<slice::Iter<IoEvent>> {
+        self.events.iter().cloned()
+    }
+}
+
+impl fmt::Debug for ShardedPoll {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "ShardedPoll {{ shards: {} }}", self.shards.len())
+    }
+}
@@ -0,0 +1,103 @@
+use {EventSet, Token};
+use std::io::{self, Write};
+use std::time::Instant;
+use std::{fmt, str};
+
+/// Logs the IO events an `EventLoop` delivers to its handler, so that a
+/// problematic run can be replayed offline with `EventLoop::replay`, see
+/// `EventLoop::set_recorder`.
+///
+/// Each delivery is written as a line of text holding the nanoseconds since
+/// the recorder was created, the token and the raw event bits. Lines are
+/// written as events are dispatched, so slow sinks should be wrapped in a
+/// `BufWriter`.
+pub struct Recorder {
+    out: Box<Write + Send>,
+    start: Instant,
+}
+
+impl Recorder {
+    pub fn new<W: Write + Send + 'static>(out: W) -> Recorder {
+        Recorder {
+            out: Box::new(out),
+            start: Instant::now(),
+        }
+    }
+
+    /// Logs the delivery of `events` for `token`
+    pub fn record(&mut self, token: Token, events: EventSet) -> io::Result<()> {
+        let elapsed = self.start.elapsed();
+
+        let rec = Recorded {
+            at_ns: elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64,
+            token: token,
+            events: events,
+        };
+
+        writeln!(self.out, "{}", rec)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Recorder {{ ... }}")
+    }
+}
+
+/// An IO event delivery logged by a `Recorder`
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Recorded {
+    /// Nanoseconds between creating the recorder and the delivery
+    pub at_ns: u64,
+    pub token: Token,
+    pub events: EventSet,
+}
+
+impl fmt::Display for Recorded {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{} {} {:#x}", self.at_ns, self.token.as_usize(), self.events.bits())
+    }
+}
+
+impl str::FromStr for Recorded {
+    type Err = io::Error;
+
+    fn from_str(line: &str) -> io::Result<Recorded> {
+        let mut fields = line.split_whitespace();
+
+        let at_ns = try!(field(fields.next(), 10));
+        let token = try!(field(fields.next(), 10));
+        let bits = try!(field(fields.next().map(|s| s.trim_left_matches("0x")), 16));
+
+        if fields.next().is_some() || bits & !EventSet::all().bits() as u64 != 0 {
+            return Err(malformed());
+        }
+
+        let mut events = EventSet::none();
+
+        for &evt in &[EventSet::readable(), EventSet::writable(), EventSet::error(), EventSet::hup()] {
+            if bits & evt.bits() as u64 != 0 {
+                events.insert(evt);
+            }
+        }
+
+        Ok(Recorded {
+            at_ns: at_ns,
+            token: Token(token as usize),
+            events: events,
+        })
+    }
+}
+
+fn field(s: Option<&str>, radix: u32) -> io::Result<u64> {
+    s.and_then(|s| u64::from_str_radix(s, radix).ok())
+        .ok_or_else(malformed)
+}
+
+fn malformed() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed event record")
+}
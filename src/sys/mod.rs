@@ -1,14 +1,48 @@
 #[cfg(unix)]
 pub use self::unix::{
     Awakener,
+    ControlMessage,
+    ControlMessageIter,
+    ControlMessages,
     Events,
     Io,
+    RecvMsg,
     Selector,
+    Socket,
     TcpSocket,
     UdpSocket,
     UnixSocket,
     pipe,
 };
 
+#[cfg(target_os = "linux")]
+pub use self::unix::{
+    bpf,
+    MessageQueue,
+    NetlinkSocket,
+    SctpRecvInfo,
+    SctpSocket,
+    TimerFd,
+    VsockAddr,
+    VsockSocket,
+    splice,
+    tee,
+};
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub use self::unix::{TunDevice, TunKind};
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub use self::unix::{FileWatch, ProcWatch};
+
 #[cfg(unix)]
 mod unix;
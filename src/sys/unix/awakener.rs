@@ -1,8 +1,13 @@
+#[cfg(target_os = "linux")]
+pub use self::eventfd::Awakener;
+
+#[cfg(not(target_os = "linux"))]
 pub use self::pipe::Awakener;
 
 /// Default *nix awakener implementation
+#[cfg(not(target_os = "linux"))]
 mod pipe {
-    use {io, Evented, EventSet, PollOpt, Selector, Token, TryRead, TryWrite};
+    use {io, Evented, EventSet, MapNonBlock, PollOpt, Selector, Token, TryRead, TryWrite};
     use unix::{self, PipeReader, PipeWriter};
     use std::mem;
     use std::cell::UnsafeCell;
@@ -33,7 +38,8 @@ mod pipe {
             // is thread safe.
             unsafe {
                 let wr: &mut PipeWriter = mem::transmute(self.writer.get());
-                wr.try_write(b"0x01").map(|_| ())
+                // A full pipe already guarantees a pending wakeup
+                wr.try_write(b"0x01").map_non_block().map(|_| ())
             }
         }
 
@@ -49,7 +55,7 @@ mod pipe {
 
                     // Consume data until all bytes are purged
                     match rd.try_read(&mut buf) {
-                        Ok(Some(i)) if i > 0 => {},
+                        Ok(i) if i > 0 => {},
                         _ => return,
                     }
                 }
@@ -76,20 +82,15 @@ mod pipe {
     }
 }
 
-/*
-
-TODO: Bring back eventfd awakener.
-      Blocked on carllerche/nix-rust#98
+/// Linux awakener backed by a counting `eventfd`. Any number of wakeups
+/// between two polls collapse into a single readable event, and one read
+/// resets the counter.
+#[cfg(target_os = "linux")]
 mod eventfd {
-    use {io, Io, TryRead, TryWrite};
+    use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+    use sys::unix::{cvt, ffi, from_nix_error, nix};
     use std::mem;
-    use std::os::unix::io::{RawFd, AsRawFd};
-
-    const MARK: &'static [u8] = b"0x000x000x000x000x000x000x000x01";
-
-    mod nix {
-        pub use nix::sys::eventfd::*;
-    }
+    use std::os::unix::io::AsRawFd;
 
     pub struct Awakener {
         io: Io,
@@ -97,44 +98,41 @@ mod eventfd {
 
     impl Awakener {
         pub fn new() -> io::Result<Awakener> {
-            Ok(Awakener {
-                io: Io::new(try!(eventfd())),
-            })
+            let fd = try!(cvt(unsafe {
+                ffi::eventfd(0, ffi::EFD_CLOEXEC | ffi::EFD_NONBLOCK)
+            }));
+
+            Ok(Awakener { io: Io::from_raw_fd(fd) })
         }
 
         pub fn wakeup(&self) -> io::Result<()> {
-            unsafe {
-                let io: &mut Io = mem::transmute(&self.io);
+            let buf: [u8; 8] = unsafe { mem::transmute(1u64) };
 
-                io.write_slice(MARK)
-                    .map(|_| ())
+            match nix::write(self.io.as_raw_fd(), &buf) {
+                Ok(_) => Ok(()),
+                // The counter is saturated, so a wakeup is already pending
+                Err(nix::Error::Sys(nix::EAGAIN)) => Ok(()),
+                Err(e) => Err(from_nix_error(e)),
             }
         }
 
-        pub fn as_raw_fd(&self) -> Fd {
-            self.io.as_raw_fd()
-        }
-
         pub fn cleanup(&self) {
             let mut buf = [0; 8];
+            let _ = nix::read(self.io.as_raw_fd(), &mut buf);
+        }
+    }
 
-            loop {
-                unsafe {
-                    let io: &mut Io = mem::transmute(&self.io);
+    impl Evented for Awakener {
+        fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+            self.io.register(selector, token, interest, opts)
+        }
 
-                    // Consume data until all bytes are purged
-                    match io.read_slice(&mut buf) {
-                        Ok(Some(i)) if i > 0 => {},
-                        _ => return,
-                    }
-                }
-            }
+        fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+            self.io.reregister(selector, token, interest, opts)
         }
-    }
 
-    fn eventfd() -> io::Result<Fd> {
-        nix::eventfd(0, nix::EFD_CLOEXEC | nix::EFD_NONBLOCK)
-            .map_err(super::from_nix_error)
+        fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+            self.io.deregister(selector)
+        }
     }
 }
- */
@@ -0,0 +1,144 @@
+//! Classic BPF socket filters
+
+use std::fmt;
+
+/// A single classic BPF instruction, laid out as the kernel's
+/// `struct sock_filter`
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct SockFilter {
+    pub code: u16,
+    pub jt: u8,
+    pub jf: u8,
+    pub k: u32,
+}
+
+// Instruction classes
+pub const BPF_LD: u16 = 0x00;
+pub const BPF_JMP: u16 = 0x05;
+pub const BPF_RET: u16 = 0x06;
+
+// Load sizes
+pub const BPF_W: u16 = 0x00;
+pub const BPF_H: u16 = 0x08;
+pub const BPF_B: u16 = 0x10;
+
+// Load modes
+pub const BPF_ABS: u16 = 0x20;
+
+// Jump conditions
+pub const BPF_JEQ: u16 = 0x10;
+
+// Operand sources
+pub const BPF_K: u16 = 0x00;
+
+impl SockFilter {
+    /// Returns a non-branching instruction
+    pub fn stmt(code: u16, k: u32) -> SockFilter {
+        SockFilter { code: code, jt: 0, jf: 0, k: k }
+    }
+
+    /// Returns a conditional jump, skipping `jt` instructions when the
+    /// condition holds and `jf` instructions otherwise
+    pub fn jump(code: u16, k: u32, jt: u8, jf: u8) -> SockFilter {
+        SockFilter { code: code, jt: jt, jf: jf, k: k }
+    }
+}
+
+impl fmt::Debug for SockFilter {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{{ 0x{:02x}, {}, {}, 0x{:08x} }}", self.code, self.jt, self.jf, self.k)
+    }
+}
+
+/// Builds the most common kind of filter: accept a packet only when every
+/// field checked matches the expected value, and drop it otherwise.
+///
+/// Offsets are relative to the start of the data the socket sees, e.g. the
+/// link layer header for packet sockets.
+///
+/// ```
+/// use mio::unix::bpf::FilterBuilder;
+///
+/// // Only accept IPv4 frames on an `AF_PACKET` socket
+/// let filter = FilterBuilder::new()
+///     .require_u16(12, 0x0800)
+///     .build();
+///
+/// assert_eq!(filter.len(), 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FilterBuilder {
+    checks: Vec<(u16, u32, u32)>,
+    accept_len: u32,
+}
+
+impl FilterBuilder {
+    pub fn new() -> FilterBuilder {
+        FilterBuilder {
+            checks: Vec::new(),
+            accept_len: 0xffff_ffff,
+        }
+    }
+
+    /// Requires the byte at `offset` to equal `value`
+    pub fn require_u8(mut self, offset: u32, value: u8) -> FilterBuilder {
+        self.checks.push((BPF_B, offset, value as u32));
+        self
+    }
+
+    /// Requires the big endian 16 bit value at `offset` to equal `value`
+    pub fn require_u16(mut self, offset: u32, value: u16) -> FilterBuilder {
+        self.checks.push((BPF_H, offset, value as u32));
+        self
+    }
+
+    /// Requires the big endian 32 bit value at `offset` to equal `value`
+    pub fn require_u32(mut self, offset: u32, value: u32) -> FilterBuilder {
+        self.checks.push((BPF_W, offset, value));
+        self
+    }
+
+    /// Truncates accepted packets to `len` bytes. By default packets are
+    /// accepted whole.
+    pub fn truncate(mut self, len: u32) -> FilterBuilder {
+        self.accept_len = len;
+        self
+    }
+
+    /// Returns the compiled program.
+    ///
+    /// # Panics
+    ///
+    /// Panics if more than 127 checks were added, as the jump to the drop
+    /// instruction would no longer fit in a single branch.
+    pub fn build(&self) -> Vec<SockFilter> {
+        let n = self.checks.len();
+        assert!(n <= 127, "too many filter checks");
+
+        let mut prog = Vec::with_capacity(2 * n + 2);
+
+        for (i, &(size, offset, value)) in self.checks.iter().enumerate() {
+            // On mismatch, skip the remaining checks and the accept
+            let skip = (2 * (n - i) - 1) as u8;
+
+            prog.push(SockFilter::stmt(BPF_LD | size | BPF_ABS, offset));
+            prog.push(SockFilter::jump(BPF_JMP | BPF_JEQ | BPF_K, value, 0, skip));
+        }
+
+        prog.push(SockFilter::stmt(BPF_RET | BPF_K, self.accept_len));
+        prog.push(SockFilter::stmt(BPF_RET | BPF_K, 0));
+
+        prog
+    }
+
+    /// Returns a program that accepts every packet
+    pub fn accept_all() -> Vec<SockFilter> {
+        FilterBuilder::new().build()
+    }
+
+    /// Returns a program that drops every packet
+    pub fn drop_all() -> Vec<SockFilter> {
+        vec![SockFilter::stmt(BPF_RET | BPF_K, 0)]
+    }
+}
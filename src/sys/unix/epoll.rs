@@ -1,19 +1,67 @@
-use {io, EventSet, PollOpt, Token};
+use {io, Evented, EventSet, PollOpt, Token};
 use event::IoEvent;
+use poll::Registration;
 use nix::sys::epoll::*;
-use nix::unistd::close;
-use std::os::unix::io::RawFd;
+use instrument;
+use sys::unix::Io;
+use sys::unix::tokens::Tokens;
+use std::sync::Arc;
+use std::os::unix::io::{AsRawFd, RawFd};
 
 #[derive(Debug)]
 pub struct Selector {
-    epfd: RawFd
+    epfd: Arc<Io>,
+    tokens: Tokens,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
         let epfd = try!(epoll_create().map_err(super::from_nix_error));
 
-        Ok(Selector { epfd: epfd })
+        Ok(Selector {
+            epfd: Arc::new(Io::from_raw_fd(epfd)),
+            tokens: Tokens::default(),
+        })
+    }
+
+    /// Returns a selector operating on the same OS handle, which stays open
+    /// until every selector sharing it is dropped
+    pub fn share(&self) -> Selector {
+        Selector {
+            epfd: self.epfd.clone(),
+            tokens: self.tokens.clone(),
+        }
+    }
+
+    /// Keeps track of the token each descriptor is registered under, for
+    /// `deregister_token`
+    pub fn track_tokens(&mut self) {
+        self.tokens.enable();
+    }
+
+    /// Deregisters every descriptor registered under `token`. Only those
+    /// registered after `track_tokens` was called are known.
+    pub fn deregister_token(&mut self, token: Token) -> io::Result<()> {
+        for fd in try!(self.tokens.fds(token)) {
+            // Closing a descriptor removes it from the set already
+            if let Err(err) = self.deregister(fd) {
+                debug!("failed to deregister; fd={}; token={:?}; err={:?}", fd, token, err);
+                self.tokens.remove(fd);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether any descriptor is registered under `token`, as far as
+    /// tracked since `track_tokens` was called
+    pub fn has_token(&self, token: Token) -> io::Result<bool> {
+        self.tokens.contains(token)
+    }
+
+    /// Submits queued interest changes; epoll applies them immediately
+    pub fn flush(&mut self) -> io::Result<()> {
+        Ok(())
     }
 
     /// Wait for events from the OS
@@ -33,38 +81,53 @@ impl Selector {
         };
 
         // Wait for epoll events for at most timeout_ms milliseconds
-        let cnt = try!(epoll_wait(self.epfd, dst, timeout_ms)
+        let cnt = try!(epoll_wait(self.epfd.as_raw_fd(), dst, timeout_ms)
                            .map_err(super::from_nix_error));
 
         unsafe { evts.events.set_len(cnt); }
 
+        instrument::select(timeout_ms, cnt);
+
         Ok(())
     }
 
     /// Register event interests for the given IO handle with the OS
     pub fn register(&mut self, fd: RawFd, token: Token, interests: EventSet, opts: PollOpt) -> io::Result<()> {
+        instrument::register("register", fd, token, interests, opts);
+
         let info = EpollEvent {
             events: ioevent_to_epoll(interests, opts),
             data: token.as_usize() as u64
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlAdd, fd, &info)
-            .map_err(super::from_nix_error)
+        try!(epoll_ctl(self.epfd.as_raw_fd(), EpollOp::EpollCtlAdd, fd, &info)
+                .map_err(super::from_nix_error));
+
+        self.tokens.insert(fd, token);
+        super::leaks::registered(self.epfd.as_raw_fd(), fd);
+        Ok(())
     }
 
     /// Register event interests for the given IO handle with the OS
     pub fn reregister(&mut self, fd: RawFd, token: Token, interests: EventSet, opts: PollOpt) -> io::Result<()> {
+        instrument::register("reregister", fd, token, interests, opts);
+
         let info = EpollEvent {
             events: ioevent_to_epoll(interests, opts),
             data: token.as_usize() as u64
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlMod, fd, &info)
-            .map_err(super::from_nix_error)
+        try!(epoll_ctl(self.epfd.as_raw_fd(), EpollOp::EpollCtlMod, fd, &info)
+                .map_err(super::from_nix_error));
+
+        self.tokens.insert(fd, token);
+        Ok(())
     }
 
     /// Deregister event interests for the given IO handle with the OS
     pub fn deregister(&mut self, fd: RawFd) -> io::Result<()> {
+        instrument::deregister(fd);
+
         // The &info argument should be ignored by the system,
         // but linux < 2.6.9 required it to be not null.
         // For compatibility, we provide a dummy EpollEvent.
@@ -73,8 +136,102 @@ impl Selector {
             data: 0
         };
 
-        epoll_ctl(self.epfd, EpollOp::EpollCtlDel, fd, &info)
-            .map_err(super::from_nix_error)
+        try!(epoll_ctl(self.epfd.as_raw_fd(), EpollOp::EpollCtlDel, fd, &info)
+                .map_err(super::from_nix_error));
+
+        self.tokens.remove(fd);
+        super::leaks::deregistered(self.epfd.as_raw_fd(), fd);
+        Ok(())
+    }
+}
+
+impl Selector {
+    /// Lists the descriptors in the epoll set as reported by the kernel in
+    /// `/proc/self/fdinfo`
+    pub fn registrations(&self) -> io::Result<Vec<Registration>> {
+        use std::fs::File;
+        use std::io::Read;
+
+        let mut info = String::new();
+        let path = format!("/proc/self/fdinfo/{}", self.epfd.as_raw_fd());
+        try!(File::open(&path).and_then(|mut f| f.read_to_string(&mut info)));
+
+        let mut regs = vec![];
+
+        // Each registration is a line such as
+        // `tfd:        5 events:       19 data:                7  pos:0 ...`
+        for line in info.lines() {
+            let mut fields = line.split_whitespace();
+
+            if fields.next() != Some("tfd:") {
+                continue;
+            }
+
+            let fd = fields.next().and_then(|v| v.parse().ok());
+            let events = fields.nth(1).and_then(|v| u32::from_str_radix(v, 16).ok());
+            let data = fields.nth(1).and_then(|v| u64::from_str_radix(v, 16).ok());
+
+            match (fd, events, data) {
+                (Some(fd), Some(events), Some(data)) => {
+                    regs.push(epoll_to_registration(fd, events, data));
+                }
+                _ => {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "malformed epoll fdinfo"));
+                }
+            }
+        }
+
+        Ok(regs)
+    }
+}
+
+fn epoll_to_registration(fd: RawFd, events: u32, data: u64) -> Registration {
+    let kind = EpollEventKind::from_bits_truncate(events);
+    let mut interest = EventSet::none();
+
+    if kind.contains(EPOLLIN) {
+        interest.insert(EventSet::readable());
+    }
+
+    if kind.contains(EPOLLOUT) {
+        interest.insert(EventSet::writable());
+    }
+
+    if kind.contains(EPOLLRDHUP) {
+        interest.insert(EventSet::hup());
+    }
+
+    let mut opts = if kind.contains(EPOLLET) {
+        PollOpt::edge()
+    } else {
+        PollOpt::level()
+    };
+
+    if kind.contains(EPOLLONESHOT) {
+        opts = opts | PollOpt::oneshot();
+    }
+
+    Registration {
+        fd: fd,
+        token: Token(data as usize),
+        interest: interest,
+        opts: opts,
+        sys_events: events,
+    }
+}
+
+// An epoll set is itself pollable; it reads as ready while it has events
+impl Evented for Selector {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.epfd.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.epfd.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.epfd.deregister(selector)
     }
 }
 
@@ -108,20 +265,18 @@ fn ioevent_to_epoll(interest: EventSet, opts: PollOpt) -> EpollEventKind {
     kind
 }
 
-impl Drop for Selector {
-    fn drop(&mut self) {
-        let _ = close(self.epfd);
-    }
-}
-
 pub struct Events {
     events: Vec<EpollEvent>,
 }
 
 impl Events {
     pub fn new() -> Events {
+        Events::with_capacity(1024)
+    }
+
+    pub fn with_capacity(cap: usize) -> Events {
         Events {
-            events: Vec::with_capacity(1024),
+            events: Vec::with_capacity(cap),
         }
     }
 
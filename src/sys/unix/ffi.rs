@@ -0,0 +1,572 @@
+//! Raw constants, types, and functions not (yet) exposed by nix or libc.
+
+#![allow(non_camel_case_types, dead_code)]
+
+pub use libc::{c_int, c_uint, c_void, socklen_t};
+
+/*
+ *
+ * ===== Netlink =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const AF_NETLINK: c_int = 16;
+
+#[cfg(target_os = "linux")]
+pub const SOL_NETLINK: c_int = 270;
+
+#[cfg(target_os = "linux")]
+pub const NETLINK_ADD_MEMBERSHIP: c_int = 1;
+
+#[cfg(target_os = "linux")]
+pub const NETLINK_DROP_MEMBERSHIP: c_int = 2;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct sockaddr_nl {
+    pub nl_family: u16,
+    pub nl_pad: u16,
+    pub nl_pid: u32,
+    pub nl_groups: u32,
+}
+
+/*
+ *
+ * ===== Socket flags =====
+ *
+ */
+
+pub const AF_UNIX: c_int = 1;
+
+pub const SOCK_STREAM: c_int = 1;
+
+#[cfg(target_os = "linux")]
+pub const SOCK_RAW: c_int = 3;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOCK_NONBLOCK: c_int = 0o4000;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOCK_CLOEXEC: c_int = 0o2000000;
+
+extern {
+    pub fn socketpair(domain: c_int, ty: c_int, protocol: c_int, sv: *mut c_int) -> c_int;
+}
+
+/*
+ *
+ * ===== TUN/TAP =====
+ *
+ */
+
+pub const IFNAMSIZ: usize = 16;
+
+#[cfg(target_os = "linux")]
+pub const TUNSETIFF: ::libc::c_ulong = 0x400454ca;
+
+#[cfg(target_os = "linux")]
+pub const IFF_TUN: i16 = 0x0001;
+
+#[cfg(target_os = "linux")]
+pub const IFF_TAP: i16 = 0x0002;
+
+#[cfg(target_os = "linux")]
+pub const IFF_NO_PI: i16 = 0x1000;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct ifreq_flags {
+    pub ifr_name: [u8; IFNAMSIZ],
+    pub ifr_flags: i16,
+    // Pad to the size of the kernel's `struct ifreq` union
+    pub _pad: [u8; 22],
+}
+
+extern {
+    pub fn ioctl(fd: c_int, req: ::libc::c_ulong, ...) -> c_int;
+}
+
+/*
+ *
+ * ===== VSOCK =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const AF_VSOCK: c_int = 40;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct sockaddr_vm {
+    pub svm_family: u16,
+    pub svm_reserved1: u16,
+    pub svm_port: u32,
+    pub svm_cid: u32,
+    pub svm_zero: [u8; 4],
+}
+
+/*
+ *
+ * ===== sendmsg / recvmsg / readv / writev =====
+ *
+ */
+
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct iovec {
+    pub iov_base: *mut c_void,
+    pub iov_len: ::libc::size_t,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct msghdr {
+    pub msg_name: *mut c_void,
+    pub msg_namelen: socklen_t,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: ::libc::size_t,
+    pub msg_control: *mut c_void,
+    pub msg_controllen: ::libc::size_t,
+    pub msg_flags: c_int,
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[repr(C)]
+pub struct msghdr {
+    pub msg_name: *mut c_void,
+    pub msg_namelen: socklen_t,
+    pub msg_iov: *mut iovec,
+    pub msg_iovlen: c_int,
+    pub msg_control: *mut c_void,
+    pub msg_controllen: socklen_t,
+    pub msg_flags: c_int,
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[repr(C)]
+pub struct cmsghdr {
+    pub cmsg_len: ::libc::size_t,
+    pub cmsg_level: c_int,
+    pub cmsg_type: c_int,
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[repr(C)]
+pub struct cmsghdr {
+    pub cmsg_len: socklen_t,
+    pub cmsg_level: c_int,
+    pub cmsg_type: c_int,
+}
+
+pub const SCM_RIGHTS: c_int = 0x01;
+
+#[cfg(target_os = "linux")]
+pub const MSG_MORE: c_int = 0x8000;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MSG_CMSG_CLOEXEC: c_int = 0x40000000;
+
+extern {
+    pub fn sendmsg(fd: c_int, msg: *const msghdr, flags: c_int) -> ::libc::ssize_t;
+    pub fn recvmsg(fd: c_int, msg: *mut msghdr, flags: c_int) -> ::libc::ssize_t;
+    pub fn readv(fd: c_int, iov: *const iovec, iovcnt: c_int) -> ::libc::ssize_t;
+    pub fn writev(fd: c_int, iov: *const iovec, iovcnt: c_int) -> ::libc::ssize_t;
+}
+
+/*
+ *
+ * ===== SCTP =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const SOCK_SEQPACKET: c_int = 5;
+
+#[cfg(target_os = "linux")]
+pub const IPPROTO_SCTP: c_int = 132;
+
+#[cfg(target_os = "linux")]
+pub const SCTP_SNDRCV: c_int = 1;
+
+#[cfg(target_os = "linux")]
+pub const SCTP_EVENTS: c_int = 11;
+
+#[cfg(target_os = "linux")]
+pub const MSG_NOTIFICATION: c_int = 0x8000;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct sctp_sndrcvinfo {
+    pub sinfo_stream: u16,
+    pub sinfo_ssn: u16,
+    pub sinfo_flags: u16,
+    pub sinfo_ppid: u32,
+    pub sinfo_context: u32,
+    pub sinfo_timetolive: u32,
+    pub sinfo_tsn: u32,
+    pub sinfo_cumtsn: u32,
+    pub sinfo_assoc_id: i32,
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct sctp_event_subscribe {
+    pub sctp_data_io_event: u8,
+    pub sctp_association_event: u8,
+    pub sctp_address_event: u8,
+    pub sctp_send_failure_event: u8,
+    pub sctp_peer_error_event: u8,
+    pub sctp_shutdown_event: u8,
+    pub sctp_partial_delivery_event: u8,
+    pub sctp_adaptation_layer_event: u8,
+    pub sctp_authentication_event: u8,
+    pub sctp_sender_dry_event: u8,
+}
+
+/*
+ *
+ * ===== POSIX message queues =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const O_RDWR: c_int = 0o2;
+
+#[cfg(target_os = "linux")]
+pub const O_CREAT: c_int = 0o100;
+
+#[cfg(target_os = "linux")]
+pub const O_EXCL: c_int = 0o200;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const O_NONBLOCK: c_int = 0o4000;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const O_CLOEXEC: c_int = 0o2000000;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct mq_attr {
+    pub mq_flags: ::libc::c_long,
+    pub mq_maxmsg: ::libc::c_long,
+    pub mq_msgsize: ::libc::c_long,
+    pub mq_curmsgs: ::libc::c_long,
+    pub __reserved: [::libc::c_long; 4],
+}
+
+#[cfg(target_os = "linux")]
+#[link(name = "rt")]
+extern {
+    pub fn mq_open(name: *const ::libc::c_char, oflag: c_int, ...) -> c_int;
+    pub fn mq_send(mqd: c_int, msg: *const ::libc::c_char, len: ::libc::size_t, prio: c_uint) -> c_int;
+    pub fn mq_receive(mqd: c_int, msg: *mut ::libc::c_char, len: ::libc::size_t, prio: *mut c_uint) -> ::libc::ssize_t;
+    pub fn mq_getattr(mqd: c_int, attr: *mut mq_attr) -> c_int;
+    pub fn mq_unlink(name: *const ::libc::c_char) -> c_int;
+}
+
+/*
+ *
+ * ===== Socket options =====
+ *
+ */
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SOL_SOCKET: c_int = 1;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_TYPE: c_int = 3;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_DONTROUTE: c_int = 5;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_SNDBUF: c_int = 7;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_RCVBUF: c_int = 8;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_OOBINLINE: c_int = 10;
+
+#[cfg(target_os = "linux")]
+pub const SO_PRIORITY: c_int = 12;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_RCVLOWAT: c_int = 18;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SOL_SOCKET: c_int = 0xffff;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_DONTROUTE: c_int = 0x0010;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_SNDBUF: c_int = 0x1001;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_RCVBUF: c_int = 0x1002;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_RCVLOWAT: c_int = 0x1004;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_TYPE: c_int = 0x1008;
+
+#[cfg(target_os = "freebsd")]
+pub const SO_PROTOCOL: c_int = 0x1016;
+
+#[cfg(target_os = "freebsd")]
+pub const SO_REUSEPORT_LB: c_int = 0x00010000;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_OOBINLINE: c_int = 0x0100;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_BINDTODEVICE: c_int = 25;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_ACCEPTCONN: c_int = 30;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const SO_ACCEPTCONN: c_int = 0x0002;
+
+#[cfg(target_os = "linux")]
+pub const SO_PEERSEC: c_int = 31;
+
+#[cfg(target_os = "linux")]
+pub const SO_ATTACH_FILTER: c_int = 26;
+
+#[cfg(target_os = "linux")]
+pub const SO_DETACH_FILTER: c_int = 27;
+
+#[cfg(target_os = "linux")]
+pub const SO_MARK: c_int = 36;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_PROTOCOL: c_int = 38;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const SO_DOMAIN: c_int = 39;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+pub struct sock_fprog {
+    pub len: u16,
+    pub filter: *const c_void,
+}
+
+#[cfg(target_os = "linux")]
+pub const SO_BUSY_POLL: c_int = 46;
+
+#[cfg(target_os = "linux")]
+pub const SO_INCOMING_CPU: c_int = 49;
+
+#[cfg(target_os = "linux")]
+pub const SO_ATTACH_REUSEPORT_CBPF: c_int = 51;
+
+#[cfg(target_os = "linux")]
+pub const SO_ATTACH_REUSEPORT_EBPF: c_int = 52;
+
+pub const IPPROTO_IP: c_int = 0;
+
+pub const IPPROTO_IPV6: c_int = 41;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IP_TOS: c_int = 1;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPV6_TCLASS: c_int = 67;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IP_TOS: c_int = 3;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IPV6_TCLASS: c_int = 36;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IP_TTL: c_int = 2;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IP_RECVTTL: c_int = 12;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPV6_UNICAST_HOPS: c_int = 16;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPV6_MULTICAST_HOPS: c_int = 18;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPV6_RECVHOPLIMIT: c_int = 51;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const IPV6_HOPLIMIT: c_int = 52;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub const IP_RECVTTL: c_int = 24;
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub const IP_RECVTTL: c_int = 65;
+
+#[cfg(target_os = "openbsd")]
+pub const IP_RECVTTL: c_int = 31;
+
+#[cfg(target_os = "netbsd")]
+pub const IP_RECVTTL: c_int = 23;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IPV6_UNICAST_HOPS: c_int = 4;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IPV6_MULTICAST_HOPS: c_int = 10;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IPV6_RECVHOPLIMIT: c_int = 37;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const IPV6_HOPLIMIT: c_int = 47;
+
+#[cfg(target_os = "linux")]
+pub const IP_FREEBIND: c_int = 15;
+
+#[cfg(target_os = "linux")]
+pub const IP_TRANSPARENT: c_int = 19;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub const IP_BOUND_IF: c_int = 25;
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub const IPV6_BOUND_IF: c_int = 125;
+
+extern {
+    pub fn if_nametoindex(name: *const ::libc::c_char) -> c_uint;
+    pub fn if_indextoname(index: c_uint, name: *mut ::libc::c_char) -> *mut ::libc::c_char;
+}
+
+/*
+ *
+ * ===== sendfile =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+extern {
+    pub fn sendfile64(out_fd: c_int, in_fd: c_int, offset: *mut i64, count: ::libc::size_t) -> ::libc::ssize_t;
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+extern {
+    pub fn sendfile(fd: c_int, s: c_int, offset: i64, len: *mut i64, hdtr: *mut c_void, flags: c_int) -> c_int;
+}
+
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+extern {
+    pub fn sendfile(fd: c_int, s: c_int, offset: i64, nbytes: ::libc::size_t,
+                    hdtr: *mut c_void, sbytes: *mut i64, flags: c_int) -> c_int;
+}
+
+/*
+ *
+ * ===== splice / tee =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const SPLICE_F_MOVE: c_uint = 1;
+
+#[cfg(target_os = "linux")]
+pub const SPLICE_F_NONBLOCK: c_uint = 2;
+
+#[cfg(target_os = "linux")]
+extern {
+    pub fn splice(fd_in: c_int, off_in: *mut i64, fd_out: c_int, off_out: *mut i64,
+                  len: ::libc::size_t, flags: c_uint) -> ::libc::ssize_t;
+    pub fn tee(fd_in: c_int, fd_out: c_int, len: ::libc::size_t, flags: c_uint) -> ::libc::ssize_t;
+}
+
+/*
+ *
+ * ===== timerfd =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const CLOCK_MONOTONIC: c_int = 1;
+
+#[cfg(target_os = "linux")]
+pub const TFD_NONBLOCK: c_int = 0o4000;
+
+#[cfg(target_os = "linux")]
+pub const TFD_CLOEXEC: c_int = 0o2000000;
+
+#[cfg(target_os = "linux")]
+pub const TFD_TIMER_ABSTIME: c_int = 1;
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct timespec {
+    pub tv_sec: ::libc::c_long,
+    pub tv_nsec: ::libc::c_long,
+}
+
+#[cfg(target_os = "linux")]
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct itimerspec {
+    pub it_interval: timespec,
+    pub it_value: timespec,
+}
+
+#[cfg(target_os = "linux")]
+extern {
+    pub fn timerfd_create(clockid: c_int, flags: c_int) -> c_int;
+    pub fn timerfd_settime(fd: c_int, flags: c_int, new_value: *const itimerspec, old_value: *mut itimerspec) -> c_int;
+}
+
+/*
+ *
+ * ===== eventfd =====
+ *
+ */
+
+#[cfg(target_os = "linux")]
+pub const EFD_NONBLOCK: c_int = 0o4000;
+
+#[cfg(target_os = "linux")]
+pub const EFD_CLOEXEC: c_int = 0o2000000;
+
+#[cfg(target_os = "linux")]
+extern {
+    pub fn eventfd(initval: c_uint, flags: c_int) -> c_int;
+}
+
+/*
+ *
+ * ===== fcntl =====
+ *
+ */
+
+pub const F_GETFD: c_int = 1;
+
+pub const F_SETFD: c_int = 2;
+
+pub const F_GETFL: c_int = 3;
+
+pub const F_SETFL: c_int = 4;
+
+pub const FD_CLOEXEC: c_int = 1;
+
+// The Linux and Android value is defined with the message queue flags
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub const O_NONBLOCK: c_int = 0x0004;
+
+extern {
+    pub fn fcntl(fd: c_int, cmd: c_int, ...) -> c_int;
+}
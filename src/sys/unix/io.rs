@@ -1,6 +1,8 @@
-use {io, Evented, EventSet, PollOpt, Selector, Token};
+use {io, Evented, EventSet, PollOpt, ReadVectored, Selector, Token, WriteVectored};
+use sys::unix::ffi;
 use std::io::{Read, Write};
-use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::mem;
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
 
 /*
  *
@@ -17,6 +19,34 @@ impl Io {
     pub fn from_raw_fd(fd: RawFd) -> Io {
         Io { fd: fd }
     }
+
+    /// Takes ownership of a descriptor created elsewhere, such as an
+    /// inherited socket or a device, switching it to non-blocking,
+    /// close-on-exec mode so that it is ready to be registered
+    pub fn adopt(fd: RawFd) -> io::Result<Io> {
+        let io = Io::from_raw_fd(fd);
+
+        try!(io.set_nonblocking(true));
+        try!(io.set_cloexec(true));
+
+        Ok(io)
+    }
+
+    pub fn set_nonblocking(&self, on: bool) -> io::Result<()> {
+        super::set_nonblocking(self.fd, on)
+    }
+
+    pub fn set_cloexec(&self, on: bool) -> io::Result<()> {
+        super::set_cloexec(self.fd, on)
+    }
+
+    /// Debug builds report handles dropped while still registered with a
+    /// selector that is alive, by logging at debug level. This makes such
+    /// drops on the calling thread panic instead. Release builds do not
+    /// track registrations.
+    pub fn set_panic_on_leak(on: bool) {
+        super::leaks::set_panic(on);
+    }
 }
 
 impl From<RawFd> for Io {
@@ -37,6 +67,17 @@ impl AsRawFd for Io {
     }
 }
 
+impl IntoRawFd for Io {
+    fn into_raw_fd(self) -> RawFd {
+        let fd = self.fd;
+
+        // Relinquish ownership without closing the fd
+        super::leaks::released(fd);
+        mem::forget(self);
+        fd
+    }
+}
+
 impl Evented for Io {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         selector.register(self.fd, token, interest, opts)
@@ -73,9 +114,50 @@ impl Write for Io {
     }
 }
 
+impl ReadVectored for Io {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        let iov: Vec<ffi::iovec> = bufs.iter_mut()
+            .map(|b| ffi::iovec { iov_base: b.as_mut_ptr() as *mut _, iov_len: b.len() as _ })
+            .collect();
+
+        let res = unsafe { ffi::readv(self.as_raw_fd(), iov.as_ptr(), iov_count(iov.len())) };
+
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res as usize)
+        }
+    }
+}
+
+impl WriteVectored for Io {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        let iov: Vec<ffi::iovec> = bufs.iter()
+            .map(|b| ffi::iovec { iov_base: b.as_ptr() as *mut _, iov_len: b.len() as _ })
+            .collect();
+
+        let res = unsafe { ffi::writev(self.as_raw_fd(), iov.as_ptr(), iov_count(iov.len())) };
+
+        if res < 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(res as usize)
+        }
+    }
+}
+
+// Anything beyond `IOV_MAX` (1024 on all supported platforms) is left for
+// the next call, matching the short read / write semantics callers already
+// handle.
+fn iov_count(len: usize) -> ffi::c_int {
+    ::std::cmp::min(len, 1024) as ffi::c_int
+}
+
 impl Drop for Io {
     fn drop(&mut self) {
         use nix::unistd::close;
+
+        super::leaks::closed(self.fd);
         let _ = close(self.as_raw_fd());
     }
 }
@@ -1,30 +1,104 @@
-use {io, EventSet, PollOpt, Token};
+//! kqueue selector, shared by macOS, iOS and the BSDs.
+//!
+//! Only `EVFILT_READ` and `EVFILT_WRITE` are used, as their behavior is
+//! the same on every platform. `EVFILT_USER` is missing on OpenBSD and older
+//! NetBSD releases, so the event loop is woken up through a pipe instead,
+//! and timers are driven by the `kevent` timeout rather than
+//! `EVFILT_TIMER`, whose units and limits differ between the BSDs.
+use {io, Evented, EventSet, PollOpt, Token};
 use event::IoEvent;
+use instrument;
+use poll::Registration;
 use nix::sys::event::{EventFilter, EventFlag, FilterFlag, KEvent, kqueue, kevent};
-use nix::sys::event::{EV_ADD, EV_CLEAR, EV_DELETE, EV_DISABLE, EV_ENABLE, EV_EOF, EV_ONESHOT};
-use std::{fmt, slice};
-use std::os::unix::io::RawFd;
+use nix::sys::event::{EV_ADD, EV_CLEAR, EV_DELETE, EV_DISABLE, EV_ENABLE, EV_EOF, EV_ERROR, EV_ONESHOT};
+use std::{fmt, mem, slice};
+use sys::unix::Io;
+use sys::unix::tokens::Tokens;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Number of interest changes queued before they are submitted on their own
+const CHANGES: usize = 1024;
 
 #[derive(Debug)]
 pub struct Selector {
-    kq: RawFd,
-    changes: Events
+    kq: Arc<Io>,
+    // Interest changes not yet submitted. Shared with the selectors returned
+    // by `share`, so that changes reach the kqueue in the order they were
+    // made whichever selector submits them.
+    changes: Arc<Mutex<Vec<KEvent>>>,
+    // Changes taken out of `changes` while `select` waits
+    submit: Vec<KEvent>,
+    tokens: Tokens,
 }
 
 impl Selector {
     pub fn new() -> io::Result<Selector> {
         Ok(Selector {
-            kq: try!(kqueue().map_err(super::from_nix_error)),
-            changes: Events::new()
+            kq: Arc::new(Io::from_raw_fd(try!(kqueue().map_err(super::from_nix_error)))),
+            changes: Arc::new(Mutex::new(Vec::with_capacity(CHANGES))),
+            submit: Vec::with_capacity(CHANGES),
+            tokens: Tokens::default(),
         })
     }
 
+    /// Returns a selector operating on the same OS handle, which stays open
+    /// until every selector sharing it is dropped. Changes queued through
+    /// any of them are submitted by the next `flush` or `select` of any.
+    pub fn share(&self) -> Selector {
+        Selector {
+            kq: self.kq.clone(),
+            changes: self.changes.clone(),
+            submit: Vec::new(),
+            tokens: self.tokens.clone(),
+        }
+    }
+
+    /// Keeps track of the token each descriptor is registered under, for
+    /// `deregister_token`
+    pub fn track_tokens(&mut self) {
+        self.tokens.enable();
+    }
+
+    /// Deregisters every descriptor registered under `token`. Only those
+    /// registered after `track_tokens` was called are known.
+    pub fn deregister_token(&mut self, token: Token) -> io::Result<()> {
+        for fd in try!(self.tokens.fds(token)) {
+            try!(self.deregister(fd));
+        }
+
+        Ok(())
+    }
+
+    /// Whether any descriptor is registered under `token`, as far as
+    /// tracked since `track_tokens` was called
+    pub fn has_token(&self, token: Token) -> io::Result<bool> {
+        self.tokens.contains(token)
+    }
+
+    /// Submits queued interest changes without waiting for events
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut changes = self.changes.lock().unwrap();
+        submit(&self.kq, &mut changes)
+    }
+
     pub fn select(&mut self, evts: &mut Events, timeout_ms: usize) -> io::Result<()> {
-        let cnt = try!(kevent(self.kq, self.changes.as_slice(), evts.as_mut_slice(), timeout_ms)
-                                  .map_err(super::from_nix_error));
+        // Take the queued changes so that the lock is not held while waiting
+        mem::swap(&mut self.submit, &mut *self.changes.lock().unwrap());
 
-        self.changes.sys_events.clear();
+        let res = kevent(self.kq.as_raw_fd(), &self.submit[..], evts.as_mut_slice(), timeout_ms);
+
+        if res.is_err() {
+            // Retry the changes with the next call, ahead of any queued since
+            let mut changes = self.changes.lock().unwrap();
+            self.submit.extend(changes.drain(..));
+            mem::swap(&mut self.submit, &mut *changes);
+        }
+
+        self.submit.clear();
+
+        let cnt = try!(res.map_err(super::from_nix_error));
 
         unsafe {
             evts.sys_events.set_len(cnt);
@@ -32,28 +106,52 @@ impl Selector {
 
         evts.coalesce();
 
+        instrument::select(timeout_ms, evts.len());
+
         Ok(())
     }
 
+    /// kqueue offers no portable way to list its registered events
+    pub fn registrations(&self) -> io::Result<Vec<Registration>> {
+        Err(io::Error::new(io::ErrorKind::Other, "listing registrations is not supported by kqueue"))
+    }
+
     pub fn register(&mut self, fd: RawFd, token: Token, interests: EventSet, opts: PollOpt) -> io::Result<()> {
         trace!("registering; token={:?}; interests={:?}", token, interests);
+        instrument::register("register", fd, token, interests, opts);
 
-        try!(self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_READ, interests.contains(EventSet::readable()), opts));
-        try!(self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_WRITE, interests.contains(EventSet::writable()), opts));
+        try!(self.ev_register_all(fd, token, interests, opts));
 
+        self.tokens.insert(fd, token);
+        super::leaks::registered(self.kq.as_raw_fd(), fd);
         Ok(())
     }
 
     pub fn reregister(&mut self, fd: RawFd, token: Token, interests: EventSet, opts: PollOpt) -> io::Result<()> {
-        // Just need to call register here since EV_ADD is a mod if already
-        // registered
-        self.register(fd, token, interests, opts)
+        instrument::register("reregister", fd, token, interests, opts);
+
+        // EV_ADD modifies the filters if they are already registered
+        try!(self.ev_register_all(fd, token, interests, opts));
+
+        self.tokens.insert(fd, token);
+        Ok(())
     }
 
     pub fn deregister(&mut self, fd: RawFd) -> io::Result<()> {
+        instrument::deregister(fd);
+
         try!(self.ev_push(fd, 0, EventFilter::EVFILT_READ, EV_DELETE));
         try!(self.ev_push(fd, 0, EventFilter::EVFILT_WRITE, EV_DELETE));
 
+        self.tokens.remove(fd);
+        super::leaks::deregistered(self.kq.as_raw_fd(), fd);
+        Ok(())
+    }
+
+    fn ev_register_all(&mut self, fd: RawFd, token: Token, interests: EventSet, opts: PollOpt) -> io::Result<()> {
+        try!(self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_READ, interests.contains(EventSet::readable()), opts));
+        try!(self.ev_register(fd, token.as_usize(), EventFilter::EVFILT_WRITE, interests.contains(EventSet::writable()), opts));
+
         Ok(())
     }
 
@@ -78,9 +176,13 @@ impl Selector {
     }
 
     fn ev_push(&mut self, fd: RawFd, token: usize, filter: EventFilter, flags: EventFlag) -> io::Result<()> {
-        try!(self.maybe_flush_changes());
+        let mut changes = self.changes.lock().unwrap();
+
+        if changes.len() >= CHANGES {
+            try!(submit(&self.kq, &mut changes));
+        }
 
-        self.changes.sys_events.push(
+        changes.push(
             KEvent {
                 ident: fd as ::libc::uintptr_t,
                 filter: filter,
@@ -92,16 +194,34 @@ impl Selector {
 
         Ok(())
     }
+}
 
-    fn maybe_flush_changes(&mut self) -> io::Result<()> {
-        if self.changes.is_full() {
-            try!(kevent(self.kq, self.changes.as_slice(), &mut [], 0)
-                    .map_err(super::from_nix_error));
+// Submits `changes` without waiting for events. They are dropped even if
+// one fails, as retrying would fail the same way.
+fn submit(kq: &Io, changes: &mut Vec<KEvent>) -> io::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
 
-            self.changes.sys_events.clear();
-        }
+    let res = kevent(kq.as_raw_fd(), &changes[..], &mut [], 0);
+    changes.clear();
 
-        Ok(())
+    try!(res.map_err(super::from_nix_error));
+    Ok(())
+}
+
+// A kqueue is itself pollable; it reads as ready while it has events
+impl Evented for Selector {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.kq.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.kq.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.kq.deregister(selector)
     }
 }
 
@@ -113,10 +233,14 @@ pub struct Events {
 
 impl Events {
     pub fn new() -> Events {
+        Events::with_capacity(1024)
+    }
+
+    pub fn with_capacity(cap: usize) -> Events {
         Events {
-            sys_events: Vec::with_capacity(1024),
-            events: Vec::with_capacity(1024),
-            event_map: HashMap::with_capacity(1024)
+            sys_events: Vec::with_capacity(cap),
+            events: Vec::with_capacity(cap),
+            event_map: HashMap::with_capacity(cap)
         }
     }
 
@@ -134,6 +258,17 @@ impl Events {
         self.event_map.clear();
 
         for e in self.sys_events.iter() {
+            // Changes submitted along with the poll that could not be applied
+            // come back as `EV_ERROR` entries, e.g. `ENOENT` when deleting the
+            // filters of a descriptor that was already closed. They carry the
+            // change's `udata` rather than a token, so they are not events.
+            if e.flags.contains(EV_ERROR) {
+                trace!("kevent change failed; fd={}; errno={}", e.ident, e.data);
+                continue;
+            }
+
+            // `udata` is a pointer sized integer whose signedness differs
+            // between the BSDs (`intptr_t` on NetBSD)
             let token = Token(e.udata as usize);
             let len = self.events.len();
 
@@ -164,18 +299,6 @@ impl Events {
         }
     }
 
-    #[inline]
-    fn is_full(&self) -> bool {
-        self.sys_events.len() == self.sys_events.capacity()
-    }
-
-    fn as_slice(&self) -> &[KEvent] {
-        unsafe {
-            let ptr = (&self.sys_events[..]).as_ptr();
-            slice::from_raw_parts(ptr, self.sys_events.len())
-        }
-    }
-
     fn as_mut_slice(&mut self) -> &mut [KEvent] {
         unsafe {
             let ptr = (&mut self.sys_events[..]).as_mut_ptr();
@@ -0,0 +1,134 @@
+//! Bookkeeping of which descriptors are registered with which selectors, to
+//! report handles that are closed while still registered. Only active in
+//! debug builds.
+//!
+//! Closing a descriptor removes it from the selectors already, and handlers
+//! owning their handles are commonly dropped before their event loop, so
+//! such drops are only logged at debug level unless `set_panic` is on.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::sync::{Mutex, Once, ONCE_INIT};
+use std::sync::atomic::{AtomicBool, ATOMIC_BOOL_INIT, Ordering};
+use std::thread;
+
+thread_local!(static PANIC: Cell<bool> = Cell::new(false));
+
+// Set by the first registration, so that processes that never register
+// anything do not take the registry lock on every close
+static USED: AtomicBool = ATOMIC_BOOL_INIT;
+
+struct Registry {
+    // Registered descriptor -> selectors it is registered with
+    fds: HashMap<RawFd, Vec<RawFd>>,
+    // Selector -> descriptors registered with it
+    selectors: HashMap<RawFd, Vec<RawFd>>,
+}
+
+fn with_registry<F: FnOnce(&mut Registry) -> R, R>(f: F) -> R {
+    static INIT: Once = ONCE_INIT;
+    static mut REGISTRY: *const Mutex<Registry> = 0 as *const Mutex<Registry>;
+
+    unsafe {
+        INIT.call_once(|| {
+            let registry = Box::new(Mutex::new(Registry {
+                fds: HashMap::new(),
+                selectors: HashMap::new(),
+            }));
+
+            REGISTRY = Box::into_raw(registry);
+        });
+
+        f(&mut (*REGISTRY).lock().unwrap())
+    }
+}
+
+/// Sets whether closing a registered handle on the calling thread panics
+/// rather than logs
+pub fn set_panic(on: bool) {
+    PANIC.with(|panic| panic.set(on));
+}
+
+pub fn registered(selector: RawFd, fd: RawFd) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+
+    USED.store(true, Ordering::Relaxed);
+
+    with_registry(|reg| {
+        reg.fds.entry(fd).or_insert_with(Vec::new).push(selector);
+        reg.selectors.entry(selector).or_insert_with(Vec::new).push(fd);
+    });
+}
+
+pub fn deregistered(selector: RawFd, fd: RawFd) {
+    if !is_used() {
+        return;
+    }
+
+    with_registry(|reg| {
+        remove(&mut reg.fds, fd, selector);
+        remove(&mut reg.selectors, selector, fd);
+    });
+}
+
+/// Called as `fd` is closed. Forgets about it as a selector, and reports it
+/// if it is still registered with one.
+pub fn closed(fd: RawFd) {
+    if let Some(selectors) = forget(fd) {
+        let msg = format!("IO handle dropped while registered; fd={}; selectors={:?}", fd, selectors);
+
+        if PANIC.with(|panic| panic.get()) && !thread::panicking() {
+            panic!("{}", msg);
+        }
+
+        debug!("{}", msg);
+    }
+}
+
+/// Called as ownership of `fd` is given up with `into_raw_fd`. Whoever owns
+/// it now may close it without going through `closed`.
+pub fn released(fd: RawFd) {
+    let _ = forget(fd);
+}
+
+// Removes `fd` both as a selector and as a registered descriptor, returning
+// the selectors it was still registered with
+fn forget(fd: RawFd) -> Option<Vec<RawFd>> {
+    if !is_used() {
+        return None;
+    }
+
+    with_registry(|reg| {
+        if let Some(fds) = reg.selectors.remove(&fd) {
+            for registered in fds {
+                remove(&mut reg.fds, registered, fd);
+            }
+        }
+
+        reg.fds.remove(&fd)
+    })
+}
+
+fn is_used() -> bool {
+    cfg!(debug_assertions) && USED.load(Ordering::Relaxed)
+}
+
+fn remove(map: &mut HashMap<RawFd, Vec<RawFd>>, key: RawFd, val: RawFd) {
+    let empty = match map.get_mut(&key) {
+        Some(vals) => {
+            if let Some(pos) = vals.iter().position(|&v| v == val) {
+                vals.swap_remove(pos);
+            }
+
+            vals.is_empty()
+        }
+        None => return,
+    };
+
+    if empty {
+        map.remove(&key);
+    }
+}
@@ -1,30 +1,116 @@
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 mod epoll;
 
-#[cfg(target_os = "linux")]
+#[cfg(any(target_os = "linux", target_os = "android"))]
 pub use self::epoll::{Events, Selector};
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
 mod kqueue;
 
-#[cfg(any(target_os = "macos", target_os = "ios"))]
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
 pub use self::kqueue::{Events, Selector};
 
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+mod watch;
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub use self::watch::{FileWatch, ProcWatch};
+
 mod awakener;
+mod ffi;
 mod io;
+mod leaks;
+mod msg;
 mod net;
 mod socket;
 mod tcp;
+mod tokens;
 mod udp;
 mod uds;
 
+#[cfg(target_os = "linux")]
+pub mod bpf;
+
+#[cfg(target_os = "linux")]
+mod mq;
+
+#[cfg(target_os = "linux")]
+mod netlink;
+
+#[cfg(target_os = "linux")]
+mod sctp;
+
+#[cfg(target_os = "linux")]
+mod splice;
+
+#[cfg(target_os = "linux")]
+mod timerfd;
+
+#[cfg(target_os = "linux")]
+mod vsock;
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+mod tun;
+
 pub use self::awakener::Awakener;
 pub use self::io::Io;
+pub use self::msg::{ControlMessage, ControlMessageIter, ControlMessages, RecvMsg};
 pub use self::socket::Socket;
 pub use self::tcp::TcpSocket;
 pub use self::udp::UdpSocket;
 pub use self::uds::UnixSocket;
 
+#[cfg(target_os = "linux")]
+pub use self::mq::MessageQueue;
+
+#[cfg(target_os = "linux")]
+pub use self::netlink::NetlinkSocket;
+
+#[cfg(target_os = "linux")]
+pub use self::sctp::{SctpRecvInfo, SctpSocket};
+
+#[cfg(target_os = "linux")]
+pub use self::splice::{splice, tee};
+
+#[cfg(target_os = "linux")]
+pub use self::timerfd::TimerFd;
+
+#[cfg(target_os = "linux")]
+pub use self::vsock::{VsockAddr, VsockSocket};
+
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+pub use self::tun::{TunDevice, TunKind};
+
+use std::os::unix::io::RawFd;
+
 pub fn pipe() -> ::io::Result<(Io, Io)> {
     use nix::fcntl::{O_NONBLOCK, O_CLOEXEC};
     use nix::unistd::pipe2;
@@ -39,12 +125,130 @@ pub fn from_nix_error(err: ::nix::Error) -> ::io::Error {
     ::io::Error::from_raw_os_error(err.errno() as i32)
 }
 
+/// Converts the return value of a raw libc call into an `io::Result`
+pub fn cvt(res: ffi::c_int) -> ::io::Result<ffi::c_int> {
+    if res == -1 {
+        Err(::io::Error::last_os_error())
+    } else {
+        Ok(res)
+    }
+}
+
+/// Sets or clears `O_NONBLOCK` on `fd`
+pub fn set_nonblocking(fd: RawFd, on: bool) -> ::io::Result<()> {
+    set_flag(fd, ffi::F_GETFL, ffi::F_SETFL, ffi::O_NONBLOCK, on)
+}
+
+/// Sets or clears `FD_CLOEXEC` on `fd`
+pub fn set_cloexec(fd: RawFd, on: bool) -> ::io::Result<()> {
+    set_flag(fd, ffi::F_GETFD, ffi::F_SETFD, ffi::FD_CLOEXEC, on)
+}
+
+fn set_flag(fd: RawFd, get: ffi::c_int, set: ffi::c_int, flag: ffi::c_int, on: bool) -> ::io::Result<()> {
+    let flags = try!(cvt(unsafe { ffi::fcntl(fd, get) }));
+
+    let new = if on {
+        flags | flag
+    } else {
+        flags & !flag
+    };
+
+    if new != flags {
+        try!(cvt(unsafe { ffi::fcntl(fd, set, new) }));
+    }
+
+    Ok(())
+}
+
+/// Sets a socket option that nix does not expose
+pub fn setsockopt<T>(fd: RawFd, level: ffi::c_int, name: ffi::c_int, val: &T) -> ::io::Result<()> {
+    use std::mem;
+
+    cvt(unsafe {
+        ::libc::setsockopt(fd, level, name,
+                           val as *const T as *const ffi::c_void,
+                           mem::size_of::<T>() as ffi::socklen_t)
+    }).map(|_| ())
+}
+
+/// Sets a socket option whose value is a variable length byte string
+pub fn setsockopt_bytes(fd: RawFd, level: ffi::c_int, name: ffi::c_int, val: &[u8]) -> ::io::Result<()> {
+    cvt(unsafe {
+        ::libc::setsockopt(fd, level, name,
+                           val.as_ptr() as *const ffi::c_void,
+                           val.len() as ffi::socklen_t)
+    }).map(|_| ())
+}
+
+/// Gets a socket option whose value is a variable length byte string,
+/// returning the number of bytes written to `buf`
+pub fn getsockopt_bytes(fd: RawFd, level: ffi::c_int, name: ffi::c_int, buf: &mut [u8]) -> ::io::Result<usize> {
+    let mut len = buf.len() as ffi::socklen_t;
+
+    try!(cvt(unsafe {
+        ::libc::getsockopt(fd, level, name,
+                           buf.as_mut_ptr() as *mut ffi::c_void,
+                           &mut len)
+    }));
+
+    Ok(len as usize)
+}
+
+/// Installs a classic BPF program through one of the `SOL_SOCKET` filter
+/// options
+#[cfg(target_os = "linux")]
+pub fn setsockopt_filter(fd: RawFd, name: ffi::c_int, filter: &[bpf::SockFilter]) -> ::io::Result<()> {
+    if filter.len() > ::std::u16::MAX as usize {
+        return Err(::io::Error::new(::io::ErrorKind::InvalidInput, "filter program too long"));
+    }
+
+    let prog = ffi::sock_fprog {
+        len: filter.len() as u16,
+        filter: filter.as_ptr() as *const ffi::c_void,
+    };
+
+    setsockopt(fd, ffi::SOL_SOCKET, name, &prog)
+}
+
+/// Returns the address family (`AF_INET`, `AF_INET6`, ...) of a socket
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+pub fn socket_family(fd: RawFd) -> ::io::Result<ffi::c_int> {
+    use std::mem;
+
+    unsafe {
+        let mut storage: ::libc::sockaddr_storage = mem::zeroed();
+        let mut len = mem::size_of::<::libc::sockaddr_storage>() as ffi::socklen_t;
+
+        try!(cvt(::libc::getsockname(fd, &mut storage as *mut _ as *mut ::libc::sockaddr, &mut len)));
+
+        Ok(storage.ss_family as ffi::c_int)
+    }
+}
+
+/// Gets a socket option that nix does not expose
+pub fn getsockopt<T: Copy>(fd: RawFd, level: ffi::c_int, name: ffi::c_int) -> ::io::Result<T> {
+    use std::mem;
+
+    unsafe {
+        let mut val: T = mem::zeroed();
+        let mut len = mem::size_of::<T>() as ffi::socklen_t;
+
+        try!(cvt(::libc::getsockopt(fd, level, name,
+                                    &mut val as *mut T as *mut ffi::c_void,
+                                    &mut len)));
+
+        Ok(val)
+    }
+}
+
 mod nix {
     pub use nix::{
         c_int,
         Error,
     };
     pub use nix::errno::{EINPROGRESS, EAGAIN};
+    #[cfg(target_os = "android")]
+    pub use nix::errno::EINVAL;
     pub use nix::fcntl::{fcntl, FcntlArg, O_NONBLOCK};
     pub use nix::sys::socket::{
         sockopt,
@@ -70,11 +274,14 @@ mod nix {
         listen,
         recvfrom,
         sendto,
+        sockaddr_storage_to_addr,
         setsockopt,
         socket,
         shutdown,
         Shutdown,
     };
+    #[cfg(target_os = "android")]
+    pub use nix::sys::socket::{accept, SockFlag};
     pub use nix::sys::time::TimeVal;
     pub use nix::unistd::{
         read,
@@ -0,0 +1,116 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use sys::unix::ffi;
+use std::mem;
+use std::ffi::CString;
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+
+#[derive(Debug)]
+pub struct MessageQueue {
+    // On Linux, message queue descriptors are regular file descriptors and
+    // `mq_close` is equivalent to `close`.
+    io: Io,
+}
+
+impl MessageQueue {
+    pub fn open(name: &str) -> io::Result<MessageQueue> {
+        let name = try!(to_cstring(name));
+        let flags = ffi::O_RDWR | ffi::O_NONBLOCK | ffi::O_CLOEXEC;
+
+        let fd = try!(super::cvt(unsafe { ffi::mq_open(name.as_ptr(), flags) }));
+
+        Ok(MessageQueue { io: Io::from_raw_fd(fd) })
+    }
+
+    pub fn create(name: &str, max_msgs: usize, msg_size: usize, exclusive: bool) -> io::Result<MessageQueue> {
+        let name = try!(to_cstring(name));
+        let mut flags = ffi::O_RDWR | ffi::O_CREAT | ffi::O_NONBLOCK | ffi::O_CLOEXEC;
+
+        if exclusive {
+            flags |= ffi::O_EXCL;
+        }
+
+        let mut attr: ffi::mq_attr = unsafe { mem::zeroed() };
+        attr.mq_maxmsg = max_msgs as ::libc::c_long;
+        attr.mq_msgsize = msg_size as ::libc::c_long;
+
+        let mode: ::libc::mode_t = 0o600;
+
+        let fd = try!(super::cvt(unsafe {
+            ffi::mq_open(name.as_ptr(), flags, mode as ffi::c_uint, &mut attr as *mut ffi::mq_attr)
+        }));
+
+        Ok(MessageQueue { io: Io::from_raw_fd(fd) })
+    }
+
+    pub fn unlink(name: &str) -> io::Result<()> {
+        let name = try!(to_cstring(name));
+        super::cvt(unsafe { ffi::mq_unlink(name.as_ptr()) })
+            .map(|_| ())
+    }
+
+    pub fn send(&self, msg: &[u8], prio: u32) -> io::Result<Option<()>> {
+        super::cvt(unsafe {
+            ffi::mq_send(self.as_raw_fd(), msg.as_ptr() as *const _, msg.len() as ::libc::size_t, prio)
+        }).map(|_| Some(())).or_else(io::to_non_block)
+    }
+
+    pub fn receive(&self, buf: &mut [u8]) -> io::Result<Option<(usize, u32)>> {
+        let mut prio = 0;
+
+        let res = unsafe {
+            ffi::mq_receive(self.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len() as ::libc::size_t, &mut prio)
+        };
+
+        if res < 0 {
+            return io::to_non_block(io::Error::last_os_error());
+        }
+
+        Ok(Some((res as usize, prio)))
+    }
+
+    /// Returns `(max_msgs, msg_size, current_msgs)`
+    pub fn attributes(&self) -> io::Result<(usize, usize, usize)> {
+        let mut attr: ffi::mq_attr = unsafe { mem::zeroed() };
+
+        try!(super::cvt(unsafe { ffi::mq_getattr(self.as_raw_fd(), &mut attr) }));
+
+        Ok((attr.mq_maxmsg as usize, attr.mq_msgsize as usize, attr.mq_curmsgs as usize))
+    }
+}
+
+fn to_cstring(name: &str) -> io::Result<CString> {
+    CString::new(name)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "queue name contains a nul byte"))
+}
+
+impl Evented for MessageQueue {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
+
+impl FromRawFd for MessageQueue {
+    unsafe fn from_raw_fd(fd: RawFd) -> MessageQueue {
+        MessageQueue { io: Io::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for MessageQueue {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for MessageQueue {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
@@ -0,0 +1,337 @@
+use {io};
+use sys::unix::{ffi, net, nix};
+use std::{mem, ptr};
+use std::net::SocketAddr;
+use std::os::unix::io::RawFd;
+
+/*
+ *
+ * ===== Control message layout =====
+ *
+ */
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[inline]
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<usize>();
+    (len + align - 1) & !(align - 1)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[inline]
+fn cmsg_align(len: usize) -> usize {
+    let align = mem::size_of::<u32>();
+    (len + align - 1) & !(align - 1)
+}
+
+/// Number of bytes a control message with `len` bytes of data occupies
+#[inline]
+pub fn cmsg_space(len: usize) -> usize {
+    cmsg_align(mem::size_of::<ffi::cmsghdr>()) + cmsg_align(len)
+}
+
+#[inline]
+fn cmsg_len(len: usize) -> usize {
+    cmsg_align(mem::size_of::<ffi::cmsghdr>()) + len
+}
+
+/// Appends a control message to `buf`
+pub fn push_cmsg(buf: &mut Vec<u8>, level: i32, ty: i32, data: &[u8]) {
+    let start = buf.len();
+    let hdr_len = cmsg_align(mem::size_of::<ffi::cmsghdr>());
+
+    buf.extend((0..cmsg_space(data.len())).map(|_| 0));
+
+    unsafe {
+        let hdr = buf[start..].as_mut_ptr() as *mut ffi::cmsghdr;
+        (*hdr).cmsg_len = cmsg_len(data.len()) as _;
+        (*hdr).cmsg_level = level;
+        (*hdr).cmsg_type = ty;
+    }
+
+    let dst = &mut buf[start + hdr_len..start + hdr_len + data.len()];
+
+    for (d, s) in dst.iter_mut().zip(data) {
+        *d = *s;
+    }
+}
+
+/// Iterates the control messages contained in a buffer filled by `recvmsg`
+pub struct CmsgIter<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> CmsgIter<'a> {
+    pub fn new(buf: &'a [u8]) -> CmsgIter<'a> {
+        CmsgIter { buf: buf }
+    }
+}
+
+impl<'a> Iterator for CmsgIter<'a> {
+    type Item = (i32, i32, &'a [u8]);
+
+    fn next(&mut self) -> Option<(i32, i32, &'a [u8])> {
+        let hdr_len = cmsg_align(mem::size_of::<ffi::cmsghdr>());
+
+        if self.buf.len() < hdr_len {
+            return None;
+        }
+
+        let (len, level, ty) = unsafe {
+            let hdr = ptr::read_unaligned(self.buf.as_ptr() as *const ffi::cmsghdr);
+            (hdr.cmsg_len as usize, hdr.cmsg_level, hdr.cmsg_type)
+        };
+
+        if len < hdr_len || len > self.buf.len() {
+            return None;
+        }
+
+        let data = &self.buf[hdr_len..len];
+        let next = cmsg_align(len);
+
+        self.buf = if next >= self.buf.len() { &[] } else { &self.buf[next..] };
+
+        Some((level, ty, data))
+    }
+}
+
+/*
+ *
+ * ===== Typed control messages =====
+ *
+ */
+
+/// A parsed control message received alongside a datagram or stream data
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlMessage<'a> {
+    /// File descriptors passed over a Unix socket (`SCM_RIGHTS`). The
+    /// receiver owns them and is responsible for closing them.
+    Rights(Vec<RawFd>),
+    /// TTL of a received IPv4 packet, see `UdpSocket::set_recv_ttl`
+    Ttl(u8),
+    /// Hop limit of a received IPv6 packet, see `UdpSocket::set_recv_ttl`
+    HopLimit(u8),
+    /// Any other control message, undecoded
+    Other {
+        level: i32,
+        ty: i32,
+        data: &'a [u8],
+    },
+}
+
+/// Builds the ancillary data passed to `Socket::send_msg`
+#[derive(Debug, Clone)]
+pub struct ControlMessages {
+    buf: Vec<u8>,
+}
+
+impl ControlMessages {
+    pub fn new() -> ControlMessages {
+        ControlMessages { buf: Vec::new() }
+    }
+
+    /// Appends file descriptors to pass over a Unix socket (`SCM_RIGHTS`)
+    pub fn rights(&mut self, fds: &[RawFd]) -> &mut ControlMessages {
+        let data = unsafe {
+            ::std::slice::from_raw_parts(fds.as_ptr() as *const u8, fds.len() * mem::size_of::<RawFd>())
+        };
+
+        self.raw(ffi::SOL_SOCKET, ffi::SCM_RIGHTS, data)
+    }
+
+    /// Appends a control message with the given level, type and payload,
+    /// for types not covered by a typed method
+    pub fn raw(&mut self, level: i32, ty: i32, data: &[u8]) -> &mut ControlMessages {
+        push_cmsg(&mut self.buf, level, ty, data);
+        self
+    }
+
+    /// Returns the encoded control messages
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buf
+    }
+
+    /// Returns the space needed to receive a control message carrying
+    /// `len` bytes of data, for sizing the buffer passed to `recv_msg`
+    pub fn space(len: usize) -> usize {
+        cmsg_space(len)
+    }
+
+    /// Parses the control messages in `buf`, typically the first
+    /// `RecvMsg::control_len` bytes of the buffer passed to `recv_msg`
+    pub fn parse(buf: &[u8]) -> ControlMessageIter {
+        ControlMessageIter { inner: CmsgIter::new(buf) }
+    }
+}
+
+pub struct ControlMessageIter<'a> {
+    inner: CmsgIter<'a>,
+}
+
+impl<'a> Iterator for ControlMessageIter<'a> {
+    type Item = ControlMessage<'a>;
+
+    fn next(&mut self) -> Option<ControlMessage<'a>> {
+        self.inner.next().map(|(level, ty, data)| {
+            if level == ffi::SOL_SOCKET && ty == ffi::SCM_RIGHTS {
+                let fds = data.chunks(mem::size_of::<RawFd>())
+                    .filter(|c| c.len() == mem::size_of::<RawFd>())
+                    .map(|c| unsafe { ptr::read_unaligned(c.as_ptr() as *const RawFd) })
+                    .collect();
+
+                return ControlMessage::Rights(fds);
+            }
+
+            if level == ffi::IPPROTO_IP && ty == TTL_CMSG {
+                if let Some(ttl) = cmsg_int(data) {
+                    return ControlMessage::Ttl(ttl);
+                }
+            }
+
+            if level == ffi::IPPROTO_IPV6 && ty == ffi::IPV6_HOPLIMIT {
+                if let Some(hops) = cmsg_int(data) {
+                    return ControlMessage::HopLimit(hops);
+                }
+            }
+
+            ControlMessage::Other { level: level, ty: ty, data: data }
+        })
+    }
+}
+
+// The control message carrying the TTL of a received IPv4 datagram
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const TTL_CMSG: i32 = ffi::IP_TTL;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const TTL_CMSG: i32 = ffi::IP_RECVTTL;
+
+// The BSDs deliver the IPv4 TTL as a single byte, everything else as an int
+fn cmsg_int(data: &[u8]) -> Option<u8> {
+    if data.len() == 1 {
+        Some(data[0])
+    } else if data.len() >= mem::size_of::<ffi::c_int>() {
+        let val: ffi::c_int = unsafe { ptr::read_unaligned(data.as_ptr() as *const _) };
+        Some(val as u8)
+    } else {
+        None
+    }
+}
+
+/*
+ *
+ * ===== sendmsg / recvmsg =====
+ *
+ */
+
+// Received descriptors must not leak into child processes. Linux and
+// Android mark them atomically, elsewhere they are marked after the call.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const RECV_CLOEXEC: i32 = ffi::MSG_CMSG_CLOEXEC;
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const RECV_CLOEXEC: i32 = 0;
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[inline]
+fn cloexec_rights(_: &[u8]) {
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn cloexec_rights(control: &[u8]) {
+    for cmsg in ControlMessages::parse(control) {
+        if let ControlMessage::Rights(fds) = cmsg {
+            for fd in fds {
+                // The message is already consumed, failing here would only
+                // leak the descriptors instead
+                let _ = super::set_cloexec(fd, true);
+            }
+        }
+    }
+}
+
+/// The result of a successful `recv_msg`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvMsg {
+    pub len: usize,
+    pub addr: Option<SocketAddr>,
+    pub control_len: usize,
+    pub flags: i32,
+}
+
+pub fn sendmsg(fd: RawFd, bufs: &[&[u8]], addr: Option<&SocketAddr>, control: &[u8], flags: i32) -> io::Result<usize> {
+    let mut iov: Vec<ffi::iovec> = bufs.iter()
+        .map(|b| ffi::iovec { iov_base: b.as_ptr() as *mut _, iov_len: b.len() as _ })
+        .collect();
+
+    let addr = addr.map(net::to_nix_addr);
+
+    let mut msg: ffi::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = iov.as_mut_ptr();
+    msg.msg_iovlen = iov.len() as _;
+
+    if let Some(ref addr) = addr {
+        let (ptr, len) = unsafe { addr.as_ffi_pair() };
+        msg.msg_name = ptr as *const _ as *mut _;
+        msg.msg_namelen = len;
+    }
+
+    if !control.is_empty() {
+        msg.msg_control = control.as_ptr() as *mut _;
+        msg.msg_controllen = control.len() as _;
+    }
+
+    let res = unsafe { ffi::sendmsg(fd, &msg, flags | nix::MSG_DONTWAIT.bits()) };
+
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res as usize)
+    }
+}
+
+pub fn recvmsg(fd: RawFd, bufs: &mut [&mut [u8]], control: &mut [u8], flags: i32) -> io::Result<RecvMsg> {
+    let mut iov: Vec<ffi::iovec> = bufs.iter_mut()
+        .map(|b| ffi::iovec { iov_base: b.as_mut_ptr() as *mut _, iov_len: b.len() as _ })
+        .collect();
+
+    let mut storage: ::libc::sockaddr_storage = unsafe { mem::zeroed() };
+
+    let mut msg: ffi::msghdr = unsafe { mem::zeroed() };
+    msg.msg_name = &mut storage as *mut _ as *mut _;
+    msg.msg_namelen = mem::size_of::<::libc::sockaddr_storage>() as ffi::socklen_t;
+    msg.msg_iov = iov.as_mut_ptr();
+    msg.msg_iovlen = iov.len() as _;
+
+    if !control.is_empty() {
+        msg.msg_control = control.as_mut_ptr() as *mut _;
+        msg.msg_controllen = control.len() as _;
+    }
+
+    let flags = flags | nix::MSG_DONTWAIT.bits() | RECV_CLOEXEC;
+    let res = unsafe { ffi::recvmsg(fd, &mut msg, flags) };
+
+    if res < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    cloexec_rights(&control[..msg.msg_controllen as usize]);
+
+    let addr = if msg.msg_namelen == 0 {
+        None
+    } else {
+        unsafe { nix::sockaddr_storage_to_addr(&storage, msg.msg_namelen as usize) }
+            .ok()
+            .and_then(|addr| match addr {
+                nix::SockAddr::Inet(ref addr) => Some(addr.to_std()),
+                _ => None,
+            })
+    };
+
+    Ok(RecvMsg {
+        len: res as usize,
+        addr: addr,
+        control_len: msg.msg_controllen as usize,
+        flags: msg.msg_flags,
+    })
+}
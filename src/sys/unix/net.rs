@@ -2,8 +2,11 @@ use {io};
 use sys::unix::{nix, Io};
 use std::net::SocketAddr;
 use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(target_os = "android")]
+use std::os::unix::io::IntoRawFd;
 pub use net::tcp::Shutdown;
 
+#[cfg(not(target_os = "android"))]
 pub fn socket(family: nix::AddressFamily, ty: nix::SockType, nonblock: bool) -> io::Result<RawFd> {
     let opts = if nonblock {
         nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC
@@ -15,6 +18,29 @@ pub fn socket(family: nix::AddressFamily, ty: nix::SockType, nonblock: bool) ->
         .map_err(super::from_nix_error)
 }
 
+// Kernels before 2.6.27, as shipped with early Android releases, reject the
+// `SOCK_NONBLOCK` and `SOCK_CLOEXEC` type flags with `EINVAL`. The socket is
+// then created again without them and the flags are set with `fcntl`. If
+// that fails too, the `EINVAL` had another cause and is returned.
+#[cfg(target_os = "android")]
+pub fn socket(family: nix::AddressFamily, ty: nix::SockType, nonblock: bool) -> io::Result<RawFd> {
+    let opts = if nonblock {
+        nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC
+    } else {
+        nix::SOCK_CLOEXEC
+    };
+
+    let err = match nix::socket(family, ty, opts) {
+        Err(err @ nix::Error::Sys(nix::EINVAL)) => err,
+        res => return res.map_err(super::from_nix_error),
+    };
+
+    match nix::socket(family, ty, nix::SockFlag::empty()) {
+        Ok(fd) => set_fd_flags(fd, nonblock),
+        Err(_) => Err(super::from_nix_error(err)),
+    }
+}
+
 pub fn connect(io: &Io, addr: &nix::SockAddr) -> io::Result<bool> {
     match nix::connect(io.as_raw_fd(), addr) {
         Ok(_) => Ok(true),
@@ -37,6 +63,7 @@ pub fn listen(io: &Io, backlog: usize) -> io::Result<()> {
         .map_err(super::from_nix_error)
 }
 
+#[cfg(not(target_os = "android"))]
 pub fn accept(io: &Io, nonblock: bool) -> io::Result<RawFd> {
     let opts = if nonblock {
         nix::SOCK_NONBLOCK | nix::SOCK_CLOEXEC
@@ -48,6 +75,30 @@ pub fn accept(io: &Io, nonblock: bool) -> io::Result<RawFd> {
         .map_err(super::from_nix_error)
 }
 
+// Bionic only provides `accept4` from API level 21, so the flags of the
+// accepted socket are set afterwards
+#[cfg(target_os = "android")]
+pub fn accept(io: &Io, nonblock: bool) -> io::Result<RawFd> {
+    let fd = try!(nix::accept(io.as_raw_fd())
+                      .map_err(super::from_nix_error));
+
+    set_fd_flags(fd, nonblock)
+}
+
+// Takes ownership of `fd`, closing it on failure
+#[cfg(target_os = "android")]
+fn set_fd_flags(fd: RawFd, nonblock: bool) -> io::Result<RawFd> {
+    let io = Io::from_raw_fd(fd);
+
+    try!(io.set_cloexec(true));
+
+    if nonblock {
+        try!(io.set_nonblocking(true));
+    }
+
+    Ok(io.into_raw_fd())
+}
+
 pub fn shutdown(io: &Io, how: Shutdown) -> io::Result<()> {
     let how: nix::Shutdown = match how {
         Shutdown::Read  => nix::Shutdown::Read,
@@ -102,6 +153,113 @@ pub fn set_tcp_keepalive(io: &Io, _seconds: u32) -> io::Result<()> {
     Ok(())
 }
 
+// Both options live on the shared inet socket state, so the `IPPROTO_IP`
+// level applies to IPv6 sockets as well.
+#[cfg(target_os = "linux")]
+pub fn set_freebind(io: &Io, on: bool) -> io::Result<()> {
+    use sys::unix::ffi;
+
+    super::setsockopt(io.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_FREEBIND, &(on as ffi::c_int))
+}
+
+#[cfg(target_os = "linux")]
+pub fn freebind(io: &Io) -> io::Result<bool> {
+    use sys::unix::ffi;
+
+    super::getsockopt::<ffi::c_int>(io.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_FREEBIND)
+        .map(|on| on != 0)
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_transparent(io: &Io, on: bool) -> io::Result<()> {
+    use sys::unix::ffi;
+
+    super::setsockopt(io.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_TRANSPARENT, &(on as ffi::c_int))
+}
+
+#[cfg(target_os = "linux")]
+pub fn set_incoming_cpu(io: &Io, cpu: u32) -> io::Result<()> {
+    use sys::unix::ffi;
+
+    super::setsockopt(io.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_INCOMING_CPU, &(cpu as ffi::c_int))
+}
+
+#[cfg(target_os = "linux")]
+pub fn incoming_cpu(io: &Io) -> io::Result<u32> {
+    use sys::unix::ffi;
+
+    super::getsockopt::<ffi::c_int>(io.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_INCOMING_CPU)
+        .map(|cpu| cpu as u32)
+}
+
+#[cfg(target_os = "linux")]
+pub fn attach_reuseport_cbpf(io: &Io, filter: &[super::bpf::SockFilter]) -> io::Result<()> {
+    use sys::unix::ffi;
+
+    super::setsockopt_filter(io.as_raw_fd(), ffi::SO_ATTACH_REUSEPORT_CBPF, filter)
+}
+
+#[cfg(target_os = "linux")]
+pub fn attach_reuseport_ebpf(io: &Io, prog: RawFd) -> io::Result<()> {
+    use sys::unix::ffi;
+
+    super::setsockopt(io.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_ATTACH_REUSEPORT_EBPF, &prog)
+}
+
+#[cfg(target_os = "linux")]
+pub fn sendfile(io: &Io, file: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use sys::unix::ffi;
+
+    let mut off = offset as i64;
+    let res = unsafe { ffi::sendfile64(io.as_raw_fd(), file, &mut off, len) };
+
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res as usize)
+    }
+}
+
+// The BSD variants report partial progress alongside `EAGAIN`, which is
+// surfaced as a short send rather than an error.
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+pub fn sendfile(io: &Io, file: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use std::ptr;
+    use sys::unix::ffi;
+
+    let mut sent = len as i64;
+    let res = unsafe { ffi::sendfile(file, io.as_raw_fd(), offset as i64, &mut sent, ptr::null_mut(), 0) };
+
+    sendfile_result(res, sent)
+}
+
+// DragonFly kept FreeBSD's signature
+#[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+pub fn sendfile(io: &Io, file: RawFd, offset: u64, len: usize) -> io::Result<usize> {
+    use std::ptr;
+    use sys::unix::ffi;
+
+    let mut sent = 0;
+    let res = unsafe { ffi::sendfile(file, io.as_raw_fd(), offset as i64, len, ptr::null_mut(), &mut sent, 0) };
+
+    sendfile_result(res, sent)
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+fn sendfile_result(res: i32, sent: i64) -> io::Result<usize> {
+    if res == 0 {
+        return Ok(sent as usize);
+    }
+
+    let err = io::Error::last_os_error();
+
+    if err.kind() == io::ErrorKind::WouldBlock && sent > 0 {
+        Ok(sent as usize)
+    } else {
+        Err(err)
+    }
+}
+
 // UDP & UDS
 #[inline]
 pub fn recvfrom(io: &Io, buf: &mut [u8]) -> io::Result<(usize, nix::SockAddr)> {
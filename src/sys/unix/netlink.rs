@@ -0,0 +1,124 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use sys::unix::{ffi, Socket};
+use std::mem;
+use std::io::{Read, Write};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+
+#[derive(Debug)]
+pub struct NetlinkSocket {
+    io: Io,
+}
+
+impl NetlinkSocket {
+    /// Returns a new, unbound, non-blocking netlink socket for the given
+    /// netlink protocol (`NETLINK_ROUTE`, ...)
+    pub fn new(protocol: i32) -> io::Result<NetlinkSocket> {
+        let ty = ffi::SOCK_RAW | ffi::SOCK_NONBLOCK | ffi::SOCK_CLOEXEC;
+        let fd = try!(super::cvt(unsafe { ::libc::socket(ffi::AF_NETLINK, ty, protocol) }));
+
+        Ok(From::from(Io::from_raw_fd(fd)))
+    }
+
+    pub fn bind(&self, pid: u32, groups: u32) -> io::Result<()> {
+        let addr = to_sockaddr(pid, groups);
+
+        super::cvt(unsafe {
+            ::libc::bind(self.as_raw_fd(),
+                         &addr as *const _ as *const ::libc::sockaddr,
+                         mem::size_of::<ffi::sockaddr_nl>() as ffi::socklen_t)
+        }).map(|_| ())
+    }
+
+    /// Returns the `(pid, groups)` pair the socket is bound to
+    pub fn local_addr(&self) -> io::Result<(u32, u32)> {
+        let mut addr = to_sockaddr(0, 0);
+        let mut len = mem::size_of::<ffi::sockaddr_nl>() as ffi::socklen_t;
+
+        try!(super::cvt(unsafe {
+            ::libc::getsockname(self.as_raw_fd(),
+                                &mut addr as *mut _ as *mut ::libc::sockaddr,
+                                &mut len)
+        }));
+
+        Ok((addr.nl_pid, addr.nl_groups))
+    }
+
+    pub fn add_membership(&self, group: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_NETLINK, ffi::NETLINK_ADD_MEMBERSHIP, &group)
+    }
+
+    pub fn drop_membership(&self, group: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_NETLINK, ffi::NETLINK_DROP_MEMBERSHIP, &group)
+    }
+
+    pub fn try_clone(&self) -> io::Result<NetlinkSocket> {
+        super::net::dup(&self.io)
+            .map(From::from)
+    }
+}
+
+impl Read for NetlinkSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl Write for NetlinkSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl Evented for NetlinkSocket {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
+
+impl Socket for NetlinkSocket {
+}
+
+impl From<Io> for NetlinkSocket {
+    fn from(io: Io) -> NetlinkSocket {
+        NetlinkSocket { io: io }
+    }
+}
+
+impl FromRawFd for NetlinkSocket {
+    unsafe fn from_raw_fd(fd: RawFd) -> NetlinkSocket {
+        NetlinkSocket { io: Io::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for NetlinkSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for NetlinkSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
+
+fn to_sockaddr(pid: u32, groups: u32) -> ffi::sockaddr_nl {
+    ffi::sockaddr_nl {
+        nl_family: ffi::AF_NETLINK as u16,
+        nl_pad: 0,
+        nl_pid: pid,
+        nl_groups: groups,
+    }
+}
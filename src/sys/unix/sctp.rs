@@ -0,0 +1,230 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use sys::unix::{ffi, msg, net, Socket};
+use std::{mem, slice};
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+
+/// Metadata for a message received on an SCTP socket
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SctpRecvInfo {
+    /// Number of bytes written into the buffer
+    pub len: usize,
+    /// Stream number the message was received on
+    pub stream: u16,
+    /// Payload protocol identifier, as sent by the peer
+    pub ppid: u32,
+    /// Association the message belongs to
+    pub assoc_id: i32,
+    /// When set, the buffer contains an association event (a
+    /// `struct sctp_notification`) rather than user data
+    pub notification: bool,
+}
+
+#[derive(Debug)]
+pub struct SctpSocket {
+    io: Io,
+}
+
+impl SctpSocket {
+    /// Returns a new, unbound, non-blocking, IPv4 one-to-one style SCTP
+    /// socket
+    pub fn stream_v4() -> io::Result<SctpSocket> {
+        SctpSocket::new(::libc::AF_INET, ffi::SOCK_STREAM)
+    }
+
+    /// Returns a new, unbound, non-blocking, IPv6 one-to-one style SCTP
+    /// socket
+    pub fn stream_v6() -> io::Result<SctpSocket> {
+        SctpSocket::new(::libc::AF_INET6, ffi::SOCK_STREAM)
+    }
+
+    /// Returns a new, unbound, non-blocking, IPv4 one-to-many style SCTP
+    /// socket
+    pub fn seqpacket_v4() -> io::Result<SctpSocket> {
+        SctpSocket::new(::libc::AF_INET, ffi::SOCK_SEQPACKET)
+    }
+
+    /// Returns a new, unbound, non-blocking, IPv6 one-to-many style SCTP
+    /// socket
+    pub fn seqpacket_v6() -> io::Result<SctpSocket> {
+        SctpSocket::new(::libc::AF_INET6, ffi::SOCK_SEQPACKET)
+    }
+
+    fn new(family: ffi::c_int, ty: ffi::c_int) -> io::Result<SctpSocket> {
+        let ty = ty | ffi::SOCK_NONBLOCK | ffi::SOCK_CLOEXEC;
+        let fd = try!(super::cvt(unsafe { ::libc::socket(family, ty, ffi::IPPROTO_SCTP) }));
+        let sock = SctpSocket { io: Io::from_raw_fd(fd) };
+
+        // Always request the stream / ppid ancillary data as well as the
+        // association events.
+        let events = ffi::sctp_event_subscribe {
+            sctp_data_io_event: 1,
+            sctp_association_event: 1,
+            sctp_address_event: 0,
+            sctp_send_failure_event: 0,
+            sctp_peer_error_event: 0,
+            sctp_shutdown_event: 1,
+            sctp_partial_delivery_event: 0,
+            sctp_adaptation_layer_event: 0,
+            sctp_authentication_event: 0,
+            sctp_sender_dry_event: 0,
+        };
+
+        try!(super::setsockopt(fd, ffi::IPPROTO_SCTP, ffi::SCTP_EVENTS, &events));
+
+        Ok(sock)
+    }
+
+    pub fn connect(&self, addr: &SocketAddr) -> io::Result<bool> {
+        net::connect(&self.io, &net::to_nix_addr(addr))
+    }
+
+    pub fn bind(&self, addr: &SocketAddr) -> io::Result<()> {
+        net::bind(&self.io, &net::to_nix_addr(addr))
+    }
+
+    pub fn listen(&self, backlog: usize) -> io::Result<()> {
+        net::listen(&self.io, backlog)
+    }
+
+    pub fn accept(&self) -> io::Result<Option<SctpSocket>> {
+        net::accept(&self.io, true)
+            .map(|fd| Some(From::from(Io::from_raw_fd(fd))))
+            .or_else(io::to_non_block)
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        net::getpeername(&self.io)
+            .map(net::to_std_addr)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        net::getsockname(&self.io)
+            .map(net::to_std_addr)
+    }
+
+    pub fn try_clone(&self) -> io::Result<SctpSocket> {
+        net::dup(&self.io)
+            .map(From::from)
+    }
+
+    pub fn shutdown(&self, how: net::Shutdown) -> io::Result<()> {
+        net::shutdown(&self.io, how)
+    }
+
+    pub fn take_socket_error(&self) -> io::Result<()> {
+        net::take_socket_error(&self.io)
+    }
+
+    /// Sends `buf` on the given stream. `addr` is only needed for one-to-many
+    /// sockets.
+    pub fn send(&self, buf: &[u8], addr: Option<&SocketAddr>, stream: u16, ppid: u32) -> io::Result<Option<usize>> {
+        let mut info: ffi::sctp_sndrcvinfo = unsafe { mem::zeroed() };
+        info.sinfo_stream = stream;
+        // The ppid is opaque to the stack and sent as is (network order by
+        // convention)
+        info.sinfo_ppid = ppid.to_be();
+
+        let mut control = Vec::with_capacity(msg::cmsg_space(mem::size_of::<ffi::sctp_sndrcvinfo>()));
+        msg::push_cmsg(&mut control, ffi::IPPROTO_SCTP, ffi::SCTP_SNDRCV, as_bytes(&info));
+
+        msg::sendmsg(self.as_raw_fd(), &[buf], addr, &control, 0)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+
+    pub fn recv(&self, buf: &mut [u8]) -> io::Result<Option<(SctpRecvInfo, Option<SocketAddr>)>> {
+        let mut control = [0u8; 64];
+
+        let res = match msg::recvmsg(self.as_raw_fd(), &mut [buf], &mut control, 0) {
+            Ok(res) => res,
+            Err(e) => return io::to_non_block(e),
+        };
+
+        let mut info = SctpRecvInfo {
+            len: res.len,
+            stream: 0,
+            ppid: 0,
+            assoc_id: 0,
+            notification: res.flags & ffi::MSG_NOTIFICATION != 0,
+        };
+
+        for (level, ty, data) in msg::CmsgIter::new(&control[..res.control_len]) {
+            if level == ffi::IPPROTO_SCTP && ty == ffi::SCTP_SNDRCV &&
+                data.len() >= mem::size_of::<ffi::sctp_sndrcvinfo>() {
+
+                let sndrcv: ffi::sctp_sndrcvinfo = unsafe {
+                    ::std::ptr::read_unaligned(data.as_ptr() as *const _)
+                };
+
+                info.stream = sndrcv.sinfo_stream;
+                info.ppid = u32::from_be(sndrcv.sinfo_ppid);
+                info.assoc_id = sndrcv.sinfo_assoc_id;
+            }
+        }
+
+        Ok(Some((info, res.addr)))
+    }
+}
+
+fn as_bytes<T>(val: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(val as *const T as *const u8, mem::size_of::<T>()) }
+}
+
+impl Read for SctpSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl Write for SctpSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl Evented for SctpSocket {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
+
+impl Socket for SctpSocket {
+}
+
+impl From<Io> for SctpSocket {
+    fn from(io: Io) -> SctpSocket {
+        SctpSocket { io: io }
+    }
+}
+
+impl FromRawFd for SctpSocket {
+    unsafe fn from_raw_fd(fd: RawFd) -> SctpSocket {
+        SctpSocket { io: Io::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for SctpSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for SctpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
@@ -1,25 +1,43 @@
 use {io};
-use sys::unix::nix;
+use sys::unix::{ffi, msg, nix};
+use sys::unix::msg::{ControlMessages, RecvMsg};
+use std::net::SocketAddr;
 use std::os::unix::io::AsRawFd;
+use std::time::Duration;
 
+/// Socket options common to all socket types.
+///
+/// All methods operate directly on the underlying descriptor and are
+/// available on every socket type that implements `AsRawFd`.
 pub trait Socket : AsRawFd {
-    /// Returns the value for the `SO_LINGER` socket option.
-    fn linger(&self) -> io::Result<usize> {
+    /// Returns the value of the `SO_LINGER` socket option.
+    ///
+    /// `None` means lingering is disabled and `close` returns immediately,
+    /// sending any unsent data in the background. `Some(Duration::new(0, 0))`
+    /// means the connection is reset on close, discarding unsent data.
+    fn linger(&self) -> io::Result<Option<Duration>> {
         let linger = try!(nix::getsockopt(self.as_raw_fd(), nix::sockopt::Linger)
             .map_err(super::from_nix_error));
 
-        if linger.l_onoff > 0 {
-            Ok(linger.l_onoff as usize)
+        if linger.l_onoff != 0 {
+            Ok(Some(Duration::from_secs(linger.l_linger as u64)))
         } else {
-            Ok(0)
+            Ok(None)
         }
     }
 
-    /// Sets the value for the `SO_LINGER` socket option
-    fn set_linger(&self, dur_s: usize) -> io::Result<()> {
-        let linger = nix::linger {
-            l_onoff: (if dur_s > 0 { 1 } else { 0 }) as nix::c_int,
-            l_linger: dur_s as nix::c_int
+    /// Sets the `SO_LINGER` socket option. The timeout is truncated to whole
+    /// seconds; see `linger` for the meaning of `None` and a zero timeout.
+    fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        let linger = match dur {
+            Some(dur) => {
+                if dur.as_secs() > ::std::i32::MAX as u64 {
+                    return Err(io::Error::new(io::ErrorKind::InvalidInput, "linger timeout too large"));
+                }
+
+                nix::linger { l_onoff: 1, l_linger: dur.as_secs() as nix::c_int }
+            }
+            None => nix::linger { l_onoff: 0, l_linger: 0 },
         };
 
         nix::setsockopt(self.as_raw_fd(), nix::sockopt::Linger, &linger)
@@ -36,11 +54,38 @@ pub trait Socket : AsRawFd {
             .map_err(super::from_nix_error)
     }
 
+    /// Sets FreeBSD's `SO_REUSEPORT_LB`. Unlike `SO_REUSEPORT`, which on
+    /// FreeBSD lets sockets share an address but delivers every connection
+    /// to the last one bound, this distributes incoming connections across
+    /// all listeners in the group.
+    #[cfg(target_os = "freebsd")]
+    fn set_reuseport_lb(&self, val: bool) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_REUSEPORT_LB, &(val as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_REUSEPORT_LB` socket option.
+    #[cfg(target_os = "freebsd")]
+    fn reuseport_lb(&self) -> io::Result<bool> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_REUSEPORT_LB)
+            .map(|val| val != 0)
+    }
+
     fn set_tcp_nodelay(&self, val: bool) -> io::Result<()> {
         nix::setsockopt(self.as_raw_fd(),  nix::sockopt::TcpNoDelay, &val)
             .map_err(super::from_nix_error)
     }
 
+    /// Sets or clears `O_NONBLOCK`. Sockets created by mio are always
+    /// non-blocking; this is for sockets adopted with `from_raw_fd`.
+    fn set_nonblocking(&self, on: bool) -> io::Result<()> {
+        super::set_nonblocking(self.as_raw_fd(), on)
+    }
+
+    /// Sets or clears `FD_CLOEXEC`
+    fn set_cloexec(&self, on: bool) -> io::Result<()> {
+        super::set_cloexec(self.as_raw_fd(), on)
+    }
+
     /// Sets the `SO_RCVTIMEO` socket option to the supplied number of
     /// milliseconds.
     ///
@@ -62,4 +107,388 @@ pub trait Socket : AsRawFd {
         nix::setsockopt(self.as_raw_fd(), nix::sockopt::SendTimeout, &t)
             .map_err(super::from_nix_error)
     }
+
+    /// Sets the `SO_SNDBUF` socket option, the size of the kernel's send
+    /// buffer in bytes.
+    ///
+    /// Linux doubles the requested value to leave room for bookkeeping
+    /// overhead, and clamps it to `net.core.wmem_max`. Sizes that do not fit
+    /// in a C `int` are rejected with `ErrorKind::InvalidInput`.
+    fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        if size > ::std::i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "buffer size too large"));
+        }
+
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_SNDBUF, &(size as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_SNDBUF` socket option.
+    ///
+    /// On Linux, the value reported by the kernel is halved so that it
+    /// matches the value passed to `set_send_buffer_size`.
+    fn send_buffer_size(&self) -> io::Result<usize> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_SNDBUF)
+            .map(|size| unscale_buffer_size(size as usize))
+    }
+
+    /// Sets the `SO_RCVBUF` socket option, the size of the kernel's receive
+    /// buffer in bytes.
+    ///
+    /// Linux doubles the requested value to leave room for bookkeeping
+    /// overhead, and clamps it to `net.core.rmem_max`. Sizes that do not fit
+    /// in a C `int` are rejected with `ErrorKind::InvalidInput`.
+    fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        if size > ::std::i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "buffer size too large"));
+        }
+
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_RCVBUF, &(size as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_RCVBUF` socket option.
+    ///
+    /// On Linux, the value reported by the kernel is halved so that it
+    /// matches the value passed to `set_recv_buffer_size`.
+    fn recv_buffer_size(&self) -> io::Result<usize> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_RCVBUF)
+            .map(|size| unscale_buffer_size(size as usize))
+    }
+
+    /// Binds the socket to the named network interface, so that only
+    /// packets from that interface are received and all traffic leaves
+    /// through it, regardless of the routing table. Passing `None` removes
+    /// the binding.
+    ///
+    /// Uses `SO_BINDTODEVICE` on Linux, which requires `CAP_NET_RAW` on older
+    /// kernels, and `IP_BOUND_IF` / `IPV6_BOUND_IF` on OS X. The latter can
+    /// only be set once the address family of the socket is known.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+    fn set_device(&self, device: Option<&str>) -> io::Result<()> {
+        set_device(self.as_raw_fd(), device)
+    }
+
+    /// Returns the name of the interface the socket is bound to, if any
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "macos", target_os = "ios"))]
+    fn device(&self) -> io::Result<Option<String>> {
+        device(self.as_raw_fd())
+    }
+
+    /// Sets the `SO_MARK` firewall mark on packets sent through the socket,
+    /// for use with policy routing. Requires `CAP_NET_ADMIN`.
+    #[cfg(target_os = "linux")]
+    fn set_mark(&self, mark: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_MARK, &mark)
+    }
+
+    /// Returns the value of the `SO_MARK` socket option.
+    #[cfg(target_os = "linux")]
+    fn mark(&self) -> io::Result<u32> {
+        super::getsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_MARK)
+    }
+
+    /// Sets the `IP_TOS` field of outgoing IPv4 packets. The upper six bits
+    /// carry the DSCP value, the lower two the ECN codepoint.
+    fn set_tos(&self, tos: u8) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_TOS, &(tos as ffi::c_int))
+    }
+
+    /// Returns the value of the `IP_TOS` socket option.
+    fn tos(&self) -> io::Result<u8> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_TOS)
+            .map(|tos| tos as u8)
+    }
+
+    /// Sets the traffic class of outgoing IPv6 packets, the IPv6 equivalent
+    /// of `set_tos`.
+    fn set_tclass(&self, tclass: u8) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_TCLASS, &(tclass as ffi::c_int))
+    }
+
+    /// Returns the value of the `IPV6_TCLASS` socket option.
+    fn tclass(&self) -> io::Result<u8> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_TCLASS)
+            .map(|tclass| tclass as u8)
+    }
+
+    /// Sets `SO_BUSY_POLL`, the approximate time the kernel busy polls the
+    /// device queue for new packets when a receive would otherwise block.
+    /// The timeout is truncated to microseconds; raising it above the
+    /// `net.core.busy_read` sysctl requires `CAP_NET_ADMIN`.
+    #[cfg(target_os = "linux")]
+    fn set_busy_poll(&self, timeout: Duration) -> io::Result<()> {
+        let micros = timeout.as_secs()
+            .saturating_mul(1_000_000)
+            .saturating_add((timeout.subsec_nanos() / 1_000) as u64);
+
+        if micros > ::std::i32::MAX as u64 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "busy poll timeout too large"));
+        }
+
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_BUSY_POLL, &(micros as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_BUSY_POLL` socket option.
+    #[cfg(target_os = "linux")]
+    fn busy_poll(&self) -> io::Result<Duration> {
+        let micros = try!(super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_BUSY_POLL));
+        let micros = micros as u64;
+
+        Ok(Duration::new(micros / 1_000_000, ((micros % 1_000_000) * 1_000) as u32))
+    }
+
+    /// Sets an arbitrary socket option, passing `val` to `setsockopt(2)`
+    /// as-is. Intended for options that do not (yet) have a typed wrapper.
+    ///
+    /// # Safety
+    ///
+    /// The kernel interprets `val` according to `level` and `name`; the
+    /// caller must ensure it has the layout the option expects. Options that
+    /// change how the socket blocks or signals readiness can break the
+    /// assumptions the event loop makes about registered sockets.
+    unsafe fn set_raw_opt(&self, level: i32, name: i32, val: &[u8]) -> io::Result<()> {
+        super::setsockopt_bytes(self.as_raw_fd(), level, name, val)
+    }
+
+    /// Reads an arbitrary socket option into `buf` via `getsockopt(2)`,
+    /// returning the number of bytes written.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for interpreting the returned bytes with the
+    /// layout the option uses.
+    unsafe fn raw_opt(&self, level: i32, name: i32, buf: &mut [u8]) -> io::Result<usize> {
+        super::getsockopt_bytes(self.as_raw_fd(), level, name, buf)
+    }
+
+    /// Sets `SO_OOBINLINE`. When enabled, TCP urgent data is placed in the
+    /// normal data stream instead of being read separately with `MSG_OOB`.
+    fn set_oobinline(&self, on: bool) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_OOBINLINE, &(on as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_OOBINLINE` socket option.
+    fn oobinline(&self) -> io::Result<bool> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_OOBINLINE)
+            .map(|on| on != 0)
+    }
+
+    /// Sets `SO_PRIORITY`, the protocol-defined priority of packets sent on
+    /// the socket, used by Linux traffic control to select a queue. Values
+    /// outside 0 to 6 require `CAP_NET_ADMIN`.
+    #[cfg(target_os = "linux")]
+    fn set_priority(&self, priority: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_PRIORITY, &(priority as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_PRIORITY` socket option.
+    #[cfg(target_os = "linux")]
+    fn priority(&self) -> io::Result<u32> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_PRIORITY)
+            .map(|priority| priority as u32)
+    }
+
+    /// Attaches a classic BPF program to the socket (`SO_ATTACH_FILTER`).
+    /// The kernel runs the program on every incoming packet and drops those
+    /// for which it returns zero, before they are queued on the socket.
+    /// Attaching a new program replaces the previous one.
+    ///
+    /// See `unix::bpf::FilterBuilder` for building common programs.
+    #[cfg(target_os = "linux")]
+    fn attach_filter(&self, filter: &[super::bpf::SockFilter]) -> io::Result<()> {
+        super::setsockopt_filter(self.as_raw_fd(), ffi::SO_ATTACH_FILTER, filter)
+    }
+
+    /// Removes the filter attached with `attach_filter`
+    #[cfg(target_os = "linux")]
+    fn detach_filter(&self) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_DETACH_FILTER, &(0 as ffi::c_int))
+    }
+
+    /// Sets `SO_DONTROUTE`. When enabled, outgoing packets bypass the routing
+    /// table and are only sent to hosts on directly connected networks.
+    fn set_dontroute(&self, on: bool) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_DONTROUTE, &(on as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_DONTROUTE` socket option.
+    fn dontroute(&self) -> io::Result<bool> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_DONTROUTE)
+            .map(|on| on != 0)
+    }
+
+    /// Returns the address family the socket was created with (`AF_INET`,
+    /// `AF_INET6`, `AF_UNIX`, ...). Useful to check inherited descriptors
+    /// before wrapping them.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn domain(&self) -> io::Result<i32> {
+        super::getsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_DOMAIN)
+    }
+
+    /// Returns the address family the socket was created with (`AF_INET`,
+    /// `AF_INET6`, `AF_UNIX`, ...). Useful to check inherited descriptors
+    /// before wrapping them.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    fn domain(&self) -> io::Result<i32> {
+        super::socket_family(self.as_raw_fd())
+    }
+
+    /// Returns the type of the socket (`SOCK_STREAM`, `SOCK_DGRAM`, ...).
+    fn socket_type(&self) -> io::Result<i32> {
+        super::getsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_TYPE)
+    }
+
+    /// Returns true if `listen` has been called on the socket
+    /// (`SO_ACCEPTCONN`).
+    fn is_listening(&self) -> io::Result<bool> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_ACCEPTCONN)
+            .map(|on| on != 0)
+    }
+
+    /// Returns the protocol of the socket (`IPPROTO_TCP`, `IPPROTO_UDP`,
+    /// ...). Not available on OS X.
+    #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+    fn protocol(&self) -> io::Result<i32> {
+        super::getsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_PROTOCOL)
+    }
+
+    /// Sets `IPV6_UNICAST_HOPS`, the hop limit of outgoing unicast IPv6
+    /// packets.
+    fn set_unicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_UNICAST_HOPS, &(hops as ffi::c_int))
+    }
+
+    /// Returns the value of the `IPV6_UNICAST_HOPS` socket option.
+    fn unicast_hops_v6(&self) -> io::Result<u32> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_UNICAST_HOPS)
+            .map(|hops| hops as u32)
+    }
+
+    /// Sets `SO_RCVLOWAT`, the minimum number of bytes that must be buffered
+    /// before a read returns. Linux also delays readiness notifications from
+    /// epoll until that many bytes are available; on other platforms only
+    /// blocking reads honor it.
+    fn set_recv_lowat(&self, bytes: usize) -> io::Result<()> {
+        if bytes > ::std::i32::MAX as usize {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "low water mark too large"));
+        }
+
+        super::setsockopt(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_RCVLOWAT, &(bytes as ffi::c_int))
+    }
+
+    /// Returns the value of the `SO_RCVLOWAT` socket option.
+    fn recv_lowat(&self) -> io::Result<usize> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_RCVLOWAT)
+            .map(|bytes| bytes as usize)
+    }
+
+    /// Sends the data gathered from `bufs` together with the ancillary data
+    /// in `control` (`sendmsg(2)`). `addr` is only used by unconnected
+    /// datagram sockets.
+    ///
+    /// Returns `Ok(None)` if the socket is not ready for writing.
+    fn send_msg(&self, bufs: &[&[u8]], addr: Option<&SocketAddr>, control: &ControlMessages) -> io::Result<Option<usize>> {
+        msg::sendmsg(self.as_raw_fd(), bufs, addr, control.as_bytes(), 0)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+
+    /// Receives data into `bufs` and ancillary data into `control`
+    /// (`recvmsg(2)`). Use `ControlMessages::parse` on the first
+    /// `control_len` bytes of `control` to read the received messages. The
+    /// source address is only reported for IP sockets.
+    ///
+    /// Returns `Ok(None)` if the socket is not ready for reading.
+    fn recv_msg(&self, bufs: &mut [&mut [u8]], control: &mut [u8]) -> io::Result<Option<RecvMsg>> {
+        msg::recvmsg(self.as_raw_fd(), bufs, control, 0)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_device(fd: ::std::os::unix::io::RawFd, device: Option<&str>) -> io::Result<()> {
+    let name = device.unwrap_or("");
+
+    if name.len() >= ffi::IFNAMSIZ {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "interface name too long"));
+    }
+
+    super::setsockopt_bytes(fd, ffi::SOL_SOCKET, ffi::SO_BINDTODEVICE, name.as_bytes())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn device(fd: ::std::os::unix::io::RawFd) -> io::Result<Option<String>> {
+    let mut buf = [0u8; ffi::IFNAMSIZ];
+    let len = try!(super::getsockopt_bytes(fd, ffi::SOL_SOCKET, ffi::SO_BINDTODEVICE, &mut buf));
+
+    // The kernel includes the trailing nul in the reported length
+    let len = buf[..len].iter().position(|b| *b == 0).unwrap_or(len);
+
+    if len == 0 {
+        Ok(None)
+    } else {
+        Ok(Some(String::from_utf8_lossy(&buf[..len]).into_owned()))
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn set_device(fd: ::std::os::unix::io::RawFd, device: Option<&str>) -> io::Result<()> {
+    use std::ffi::CString;
+
+    let index = match device {
+        Some(name) => {
+            let name = try!(CString::new(name)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid interface name")));
+
+            match unsafe { ffi::if_nametoindex(name.as_ptr()) } {
+                0 => return Err(io::Error::last_os_error()),
+                index => index,
+            }
+        }
+        None => 0,
+    };
+
+    let (level, name) = try!(bound_if_opt(fd));
+    super::setsockopt(fd, level, name, &(index as ffi::c_int))
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn device(fd: ::std::os::unix::io::RawFd) -> io::Result<Option<String>> {
+    use std::ffi::CStr;
+
+    let (level, name) = try!(bound_if_opt(fd));
+    let index = try!(super::getsockopt::<ffi::c_int>(fd, level, name));
+
+    if index == 0 {
+        return Ok(None);
+    }
+
+    let mut buf = [0 as ::libc::c_char; ffi::IFNAMSIZ];
+
+    unsafe {
+        if ffi::if_indextoname(index as ffi::c_uint, buf.as_mut_ptr()).is_null() {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Some(CStr::from_ptr(buf.as_ptr()).to_string_lossy().into_owned()))
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+fn bound_if_opt(fd: ::std::os::unix::io::RawFd) -> io::Result<(ffi::c_int, ffi::c_int)> {
+    if try!(super::socket_family(fd)) == ::libc::AF_INET6 {
+        Ok((ffi::IPPROTO_IPV6, ffi::IPV6_BOUND_IF))
+    } else {
+        Ok((ffi::IPPROTO_IP, ffi::IP_BOUND_IF))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn unscale_buffer_size(size: usize) -> usize {
+    size / 2
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn unscale_buffer_size(size: usize) -> usize {
+    size
 }
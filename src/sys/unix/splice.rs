@@ -0,0 +1,28 @@
+use {io};
+use sys::unix::ffi;
+use std::ptr;
+use std::os::unix::io::RawFd;
+
+/// Moves up to `len` bytes from `src` to `dst`, one of which must be a pipe
+pub fn splice(src: RawFd, dst: RawFd, len: usize) -> io::Result<usize> {
+    let flags = ffi::SPLICE_F_MOVE | ffi::SPLICE_F_NONBLOCK;
+    let res = unsafe { ffi::splice(src, ptr::null_mut(), dst, ptr::null_mut(), len, flags) };
+
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res as usize)
+    }
+}
+
+/// Duplicates up to `len` bytes from pipe `src` into pipe `dst` without
+/// consuming them
+pub fn tee(src: RawFd, dst: RawFd, len: usize) -> io::Result<usize> {
+    let res = unsafe { ffi::tee(src, dst, len, ffi::SPLICE_F_NONBLOCK) };
+
+    if res < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(res as usize)
+    }
+}
@@ -1,8 +1,8 @@
-use {io, Evented, EventSet, Io, PollOpt, Selector, Token, TryAccept};
+use {io, Evented, EventSet, Io, PollOpt, ReadVectored, Selector, Token, TryAccept, WriteVectored};
 use sys::unix::{net, nix, Socket};
 use std::io::{Read, Write};
 use std::net::SocketAddr;
-use std::os::unix::io::{RawFd, FromRawFd, AsRawFd};
+use std::os::unix::io::{RawFd, FromRawFd, IntoRawFd, AsRawFd};
 
 #[derive(Debug)]
 pub struct TcpSocket {
@@ -62,6 +62,27 @@ impl TcpSocket {
         net::shutdown(&self.io, how)
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn send_more(&self, buf: &[u8]) -> io::Result<Option<usize>> {
+        use sys::unix::{ffi, msg};
+
+        msg::sendmsg(self.as_raw_fd(), &[buf], None, &[], ffi::MSG_MORE)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+    pub fn send_file(&self, file: RawFd, offset: u64, len: usize) -> io::Result<Option<usize>> {
+        // The BSD sendfile calls read a zero length as "to end of file"
+        if len == 0 {
+            return Ok(Some(0));
+        }
+
+        net::sendfile(&self.io, file, offset, len)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+
     /*
      *
      * ===== Socket Options =====
@@ -91,6 +112,41 @@ impl TcpSocket {
             }
         }
     }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_freebind(&self, on: bool) -> io::Result<()> {
+        net::set_freebind(&self.io, on)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn freebind(&self) -> io::Result<bool> {
+        net::freebind(&self.io)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_transparent(&self, on: bool) -> io::Result<()> {
+        net::set_transparent(&self.io, on)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cbpf(&self, filter: &[super::bpf::SockFilter]) -> io::Result<()> {
+        net::attach_reuseport_cbpf(&self.io, filter)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog: RawFd) -> io::Result<()> {
+        net::attach_reuseport_ebpf(&self.io, prog)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_incoming_cpu(&self, cpu: u32) -> io::Result<()> {
+        net::set_incoming_cpu(&self.io, cpu)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn incoming_cpu(&self) -> io::Result<u32> {
+        net::incoming_cpu(&self.io)
+    }
 }
 
 impl Read for TcpSocket {
@@ -109,6 +165,18 @@ impl Write for TcpSocket {
     }
 }
 
+impl ReadVectored for TcpSocket {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        self.io.read_bufs(bufs)
+    }
+}
+
+impl WriteVectored for TcpSocket {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        self.io.write_bufs(bufs)
+    }
+}
+
 impl Evented for TcpSocket {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.io.register(selector, token, interest, opts)
@@ -151,3 +219,9 @@ impl AsRawFd for TcpSocket {
         self.io.as_raw_fd()
     }
 }
+
+impl IntoRawFd for TcpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
@@ -0,0 +1,59 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use sys::unix::{ffi, nix};
+use std::{mem, ptr};
+use std::os::unix::io::AsRawFd;
+
+const NS_PER_SEC: u64 = 1_000_000_000;
+
+/// A non-blocking `timerfd` on the monotonic clock. It becomes readable once
+/// the armed deadline passes.
+#[derive(Debug)]
+pub struct TimerFd {
+    io: Io,
+}
+
+impl TimerFd {
+    pub fn new() -> io::Result<TimerFd> {
+        let fd = try!(super::cvt(unsafe {
+            ffi::timerfd_create(ffi::CLOCK_MONOTONIC, ffi::TFD_NONBLOCK | ffi::TFD_CLOEXEC)
+        }));
+
+        Ok(TimerFd { io: Io::from_raw_fd(fd) })
+    }
+
+    /// Arms the timer to fire at `at`, an absolute monotonic time in
+    /// nanoseconds. Deadlines in the past fire immediately.
+    pub fn set_deadline_ns(&self, at: u64) -> io::Result<()> {
+        let mut spec: ffi::itimerspec = unsafe { mem::zeroed() };
+
+        // An all zero value would disarm the timer instead
+        let at = if at == 0 { 1 } else { at };
+
+        spec.it_value.tv_sec = (at / NS_PER_SEC) as ::libc::c_long;
+        spec.it_value.tv_nsec = (at % NS_PER_SEC) as ::libc::c_long;
+
+        super::cvt(unsafe {
+            ffi::timerfd_settime(self.io.as_raw_fd(), ffi::TFD_TIMER_ABSTIME, &spec, ptr::null_mut())
+        }).map(|_| ())
+    }
+
+    /// Consumes the expiration count so the descriptor is no longer readable
+    pub fn reset(&self) {
+        let mut buf = [0; 8];
+        let _ = nix::read(self.io.as_raw_fd(), &mut buf);
+    }
+}
+
+impl Evented for TimerFd {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
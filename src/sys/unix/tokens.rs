@@ -0,0 +1,60 @@
+use {io, Token};
+use std::collections::HashMap;
+use std::os::unix::io::RawFd;
+use std::sync::{Arc, Mutex};
+
+/// The token each descriptor is registered under, kept by a selector once
+/// `enable` is called. Shared with the selectors returned by `share`.
+///
+/// Entries are keyed by descriptor: a descriptor has a single registration
+/// per selector, and registering a reused descriptor number replaces the
+/// stale entry left by one that was closed while registered.
+#[derive(Clone, Debug, Default)]
+pub struct Tokens {
+    map: Option<Arc<Mutex<HashMap<RawFd, Token>>>>,
+}
+
+impl Tokens {
+    pub fn enable(&mut self) {
+        if self.map.is_none() {
+            self.map = Some(Arc::new(Mutex::new(HashMap::new())));
+        }
+    }
+
+    pub fn insert(&self, fd: RawFd, token: Token) {
+        if let Some(ref map) = self.map {
+            map.lock().unwrap().insert(fd, token);
+        }
+    }
+
+    pub fn remove(&self, fd: RawFd) {
+        if let Some(ref map) = self.map {
+            map.lock().unwrap().remove(&fd);
+        }
+    }
+
+    /// Descriptors registered under `token`
+    pub fn fds(&self, token: Token) -> io::Result<Vec<RawFd>> {
+        match self.map {
+            Some(ref map) => {
+                Ok(map.lock().unwrap().iter()
+                    .filter(|&(_, t)| *t == token)
+                    .map(|(fd, _)| *fd)
+                    .collect())
+            }
+            None => Err(untracked()),
+        }
+    }
+
+    /// Whether any descriptor is registered under `token`
+    pub fn contains(&self, token: Token) -> io::Result<bool> {
+        match self.map {
+            Some(ref map) => Ok(map.lock().unwrap().values().any(|t| *t == token)),
+            None => Err(untracked()),
+        }
+    }
+}
+
+fn untracked() -> io::Error {
+    io::Error::new(io::ErrorKind::Other, "tokens are not tracked by this selector")
+}
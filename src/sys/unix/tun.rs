@@ -0,0 +1,129 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use std::io::{Read, Write};
+use std::os::unix::io::{RawFd, AsRawFd};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TunKind {
+    Tun,
+    Tap,
+}
+
+#[derive(Debug)]
+pub struct TunDevice {
+    io: Io,
+    name: String,
+}
+
+impl TunDevice {
+    /// Opens a new, non-blocking TUN or TAP device. On Linux, `name` may be
+    /// empty or contain a `%d` pattern, in which case the kernel picks the
+    /// interface name.
+    #[cfg(target_os = "linux")]
+    pub fn open(kind: TunKind, name: &str, packet_info: bool) -> io::Result<TunDevice> {
+        use sys::unix::ffi;
+
+        if name.len() >= ffi::IFNAMSIZ {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "interface name too long"));
+        }
+
+        let io = try!(open_dev("/dev/net/tun"));
+
+        let mut req = ffi::ifreq_flags {
+            ifr_name: [0; ffi::IFNAMSIZ],
+            ifr_flags: 0,
+            _pad: [0; 22],
+        };
+
+        for (dst, src) in req.ifr_name.iter_mut().zip(name.as_bytes()) {
+            *dst = *src;
+        }
+
+        req.ifr_flags = match kind {
+            TunKind::Tun => ffi::IFF_TUN,
+            TunKind::Tap => ffi::IFF_TAP,
+        };
+
+        if !packet_info {
+            req.ifr_flags |= ffi::IFF_NO_PI;
+        }
+
+        try!(super::cvt(unsafe { ffi::ioctl(io.as_raw_fd(), ffi::TUNSETIFF, &mut req as *mut ffi::ifreq_flags) }));
+
+        // The kernel writes back the name that was actually assigned
+        let len = req.ifr_name.iter().position(|b| *b == 0).unwrap_or(ffi::IFNAMSIZ);
+        let name = String::from_utf8_lossy(&req.ifr_name[..len]).into_owned();
+
+        Ok(TunDevice { io: io, name: name })
+    }
+
+    /// Opens a new, non-blocking TUN or TAP device. On the BSDs, each device
+    /// has its own node, so `name` must name an existing one (`tun0`, `tap1`,
+    /// ...). Packet info is not supported.
+    #[cfg(any(target_os = "freebsd",
+              target_os = "dragonfly",
+              target_os = "netbsd",
+              target_os = "openbsd"))]
+    pub fn open(kind: TunKind, name: &str, packet_info: bool) -> io::Result<TunDevice> {
+        let prefix = match kind {
+            TunKind::Tun => "tun",
+            TunKind::Tap => "tap",
+        };
+
+        if packet_info || !name.starts_with(prefix) || name.contains('/') {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "invalid device name"));
+        }
+
+        let io = try!(open_dev(&format!("/dev/{}", name)));
+
+        Ok(TunDevice { io: io, name: name.to_string() })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+fn open_dev(path: &str) -> io::Result<Io> {
+    use nix::fcntl::{open, O_RDWR, O_NONBLOCK, O_CLOEXEC};
+    use nix::sys::stat::Mode;
+
+    open(path, O_RDWR | O_NONBLOCK | O_CLOEXEC, Mode::empty())
+        .map(Io::from_raw_fd)
+        .map_err(super::from_nix_error)
+}
+
+impl Read for TunDevice {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl Write for TunDevice {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl Evented for TunDevice {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
+
+impl AsRawFd for TunDevice {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
@@ -1,8 +1,8 @@
 use {io, Evented, EventSet, Io, IpAddr, PollOpt, Selector, Token};
 use bytes::{Buf, MutBuf};
-use sys::unix::{net, nix, Socket};
+use sys::unix::{ffi, msg, net, nix, Socket};
 use std::net::SocketAddr;
-use std::os::unix::io::{RawFd, AsRawFd, FromRawFd};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
 
 #[derive(Debug)]
 pub struct UdpSocket {
@@ -45,6 +45,16 @@ impl UdpSocket {
             .or_else(io::to_non_block)
     }
 
+    #[cfg(target_os = "linux")]
+    pub fn send_to_more<B: Buf>(&self, buf: &mut B, target: &SocketAddr) -> io::Result<Option<()>> {
+        msg::sendmsg(self.as_raw_fd(), &[buf.bytes()], Some(target), &[], ffi::MSG_MORE)
+            .map(|cnt| {
+                buf.advance(cnt);
+                Some(())
+            })
+            .or_else(io::to_non_block)
+    }
+
     pub fn recv_from<B: MutBuf>(&self, buf: &mut B) -> io::Result<Option<SocketAddr>> {
         net::recvfrom(&self.io, unsafe { buf.mut_bytes() })
             .map(|(cnt, addr)| {
@@ -54,6 +64,41 @@ impl UdpSocket {
             .or_else(io::to_non_block)
     }
 
+    pub fn set_recv_ttl(&self, on: bool) -> io::Result<()> {
+        let on = on as ffi::c_int;
+
+        match try!(self.local_addr()) {
+            SocketAddr::V4(..) => super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IP, ffi::IP_RECVTTL, &on),
+            SocketAddr::V6(..) => super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_RECVHOPLIMIT, &on),
+        }
+    }
+
+    pub fn recv_from_ttl<B: MutBuf>(&self, buf: &mut B) -> io::Result<Option<(SocketAddr, Option<u8>)>> {
+        let mut control = [0u8; 64];
+
+        let res = match msg::recvmsg(self.as_raw_fd(), &mut [unsafe { buf.mut_bytes() }], &mut control, 0) {
+            Ok(res) => res,
+            Err(e) => return io::to_non_block(e),
+        };
+
+        buf.advance(res.len);
+
+        let mut ttl = None;
+
+        for cmsg in msg::ControlMessages::parse(&control[..res.control_len]) {
+            match cmsg {
+                msg::ControlMessage::Ttl(val) |
+                msg::ControlMessage::HopLimit(val) => ttl = Some(val),
+                _ => {}
+            }
+        }
+
+        match res.addr {
+            Some(addr) => Ok(Some((addr, ttl))),
+            None => Err(io::Error::new(io::ErrorKind::Other, "datagram without a source address")),
+        }
+    }
+
     pub fn set_broadcast(&self, on: bool) -> io::Result<()> {
         nix::setsockopt(self.as_raw_fd(), nix::sockopt::Broadcast, &on)
             .map_err(super::from_nix_error)
@@ -118,6 +163,40 @@ impl UdpSocket {
         nix::setsockopt(self.as_raw_fd(), nix::sockopt::IpMulticastTtl, &v)
             .map_err(super::from_nix_error)
     }
+
+    pub fn set_multicast_hops_v6(&self, hops: u32) -> io::Result<()> {
+        super::setsockopt(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_MULTICAST_HOPS, &(hops as ffi::c_int))
+    }
+
+    pub fn multicast_hops_v6(&self) -> io::Result<u32> {
+        super::getsockopt::<ffi::c_int>(self.as_raw_fd(), ffi::IPPROTO_IPV6, ffi::IPV6_MULTICAST_HOPS)
+            .map(|hops| hops as u32)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_freebind(&self, on: bool) -> io::Result<()> {
+        net::set_freebind(&self.io, on)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn freebind(&self) -> io::Result<bool> {
+        net::freebind(&self.io)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn set_transparent(&self, on: bool) -> io::Result<()> {
+        net::set_transparent(&self.io, on)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_cbpf(&self, filter: &[super::bpf::SockFilter]) -> io::Result<()> {
+        net::attach_reuseport_cbpf(&self.io, filter)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn attach_reuseport_ebpf(&self, prog: RawFd) -> io::Result<()> {
+        net::attach_reuseport_ebpf(&self.io, prog)
+    }
 }
 
 impl Evented for UdpSocket {
@@ -154,3 +233,9 @@ impl AsRawFd for UdpSocket {
         self.io.as_raw_fd()
     }
 }
+
+impl IntoRawFd for UdpSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
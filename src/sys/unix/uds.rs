@@ -1,8 +1,8 @@
-use {io, Evented, EventSet, Io, PollOpt, Selector, Token, TryAccept};
+use {io, Evented, EventSet, Io, PollOpt, ReadVectored, Selector, Token, TryAccept, WriteVectored};
 use sys::unix::{net, nix, Socket};
 use std::io::{Read, Write};
 use std::path::Path;
-use std::os::unix::io::{RawFd, AsRawFd, FromRawFd};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
 
 #[derive(Debug)]
 pub struct UnixSocket {
@@ -15,6 +15,27 @@ impl UnixSocket {
         UnixSocket::new(nix::SockType::Stream)
     }
 
+    /// Returns a pair of connected, non-blocking stream sockets
+    pub fn pair() -> io::Result<(UnixSocket, UnixSocket)> {
+        use sys::unix::ffi;
+
+        let mut fds = [0; 2];
+
+        try!(super::cvt(unsafe {
+            ffi::socketpair(ffi::AF_UNIX, ffi::SOCK_STREAM, 0, fds.as_mut_ptr())
+        }));
+
+        let pair = (From::from(Io::from_raw_fd(fds[0])), From::from(Io::from_raw_fd(fds[1])));
+
+        // Set after the fact, as the flags to `socketpair` are Linux only
+        for &fd in &fds {
+            try!(super::set_nonblocking(fd, true));
+            try!(super::set_cloexec(fd, true));
+        }
+
+        Ok(pair)
+    }
+
     fn new(ty: nix::SockType) -> io::Result<UnixSocket> {
         let fd = try!(net::socket(nix::AddressFamily::Unix, ty, true));
         Ok(From::from(Io::from_raw_fd(fd)))
@@ -45,6 +66,36 @@ impl UnixSocket {
         net::dup(&self.io)
             .map(From::from)
     }
+
+    #[cfg(target_os = "linux")]
+    pub fn send_more(&self, buf: &[u8]) -> io::Result<Option<usize>> {
+        use sys::unix::{ffi, msg};
+
+        msg::sendmsg(self.as_raw_fd(), &[buf], None, &[], ffi::MSG_MORE)
+            .map(Some)
+            .or_else(io::to_non_block)
+    }
+
+    #[cfg(target_os = "linux")]
+    pub fn peer_security_context(&self) -> io::Result<Vec<u8>> {
+        use sys::unix::ffi;
+
+        let mut buf = vec![0; 256];
+
+        loop {
+            match super::getsockopt_bytes(self.as_raw_fd(), ffi::SOL_SOCKET, ffi::SO_PEERSEC, &mut buf) {
+                Ok(len) => {
+                    buf.truncate(len);
+                    return Ok(buf);
+                }
+                Err(ref e) if e.raw_os_error() == Some(::libc::ERANGE) && buf.len() < 64 * 1024 => {
+                    let len = buf.len() * 2;
+                    buf.resize(len, 0);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 }
 
 impl Read for UnixSocket {
@@ -63,6 +114,18 @@ impl Write for UnixSocket {
     }
 }
 
+impl ReadVectored for UnixSocket {
+    fn read_bufs(&mut self, bufs: &mut [&mut [u8]]) -> io::Result<usize> {
+        self.io.read_bufs(bufs)
+    }
+}
+
+impl WriteVectored for UnixSocket {
+    fn write_bufs(&mut self, bufs: &[&[u8]]) -> io::Result<usize> {
+        self.io.write_bufs(bufs)
+    }
+}
+
 impl Evented for UnixSocket {
     fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
         self.io.register(selector, token, interest, opts)
@@ -106,6 +169,12 @@ impl AsRawFd for UnixSocket {
     }
 }
 
+impl IntoRawFd for UnixSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
+
 fn to_nix_addr<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<nix::SockAddr> {
     nix::SockAddr::new_unix(path.as_ref())
         .map_err(super::from_nix_error)
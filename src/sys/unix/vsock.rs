@@ -0,0 +1,175 @@
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use sys::unix::{ffi, net, nix, Socket};
+use std::mem;
+use std::io::{Read, Write};
+use std::os::unix::io::{RawFd, AsRawFd, FromRawFd, IntoRawFd};
+
+/// Address of a vsock endpoint, identified by a context id and a port
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VsockAddr {
+    pub cid: u32,
+    pub port: u32,
+}
+
+impl VsockAddr {
+    pub fn new(cid: u32, port: u32) -> VsockAddr {
+        VsockAddr { cid: cid, port: port }
+    }
+}
+
+#[derive(Debug)]
+pub struct VsockSocket {
+    io: Io,
+}
+
+impl VsockSocket {
+    /// Returns a new, unbound, non-blocking vsock stream socket
+    pub fn stream() -> io::Result<VsockSocket> {
+        let ty = ffi::SOCK_STREAM | ffi::SOCK_NONBLOCK | ffi::SOCK_CLOEXEC;
+        let fd = try!(super::cvt(unsafe { ::libc::socket(ffi::AF_VSOCK, ty, 0) }));
+
+        Ok(From::from(Io::from_raw_fd(fd)))
+    }
+
+    pub fn connect(&self, addr: &VsockAddr) -> io::Result<bool> {
+        let addr = to_sockaddr(addr);
+
+        let res = unsafe {
+            ::libc::connect(self.as_raw_fd(),
+                            &addr as *const _ as *const ::libc::sockaddr,
+                            mem::size_of::<ffi::sockaddr_vm>() as ffi::socklen_t)
+        };
+
+        match super::cvt(res) {
+            Ok(_) => Ok(true),
+            Err(ref e) if e.raw_os_error() == Some(nix::EINPROGRESS as i32) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub fn bind(&self, addr: &VsockAddr) -> io::Result<()> {
+        let addr = to_sockaddr(addr);
+
+        super::cvt(unsafe {
+            ::libc::bind(self.as_raw_fd(),
+                         &addr as *const _ as *const ::libc::sockaddr,
+                         mem::size_of::<ffi::sockaddr_vm>() as ffi::socklen_t)
+        }).map(|_| ())
+    }
+
+    pub fn listen(&self, backlog: usize) -> io::Result<()> {
+        net::listen(&self.io, backlog)
+    }
+
+    pub fn accept(&self) -> io::Result<Option<VsockSocket>> {
+        net::accept(&self.io, true)
+            .map(|fd| Some(From::from(Io::from_raw_fd(fd))))
+            .or_else(io::to_non_block)
+    }
+
+    pub fn local_addr(&self) -> io::Result<VsockAddr> {
+        let mut addr: ffi::sockaddr_vm = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<ffi::sockaddr_vm>() as ffi::socklen_t;
+
+        try!(super::cvt(unsafe {
+            ::libc::getsockname(self.as_raw_fd(),
+                                &mut addr as *mut _ as *mut ::libc::sockaddr,
+                                &mut len)
+        }));
+
+        Ok(VsockAddr::new(addr.svm_cid, addr.svm_port))
+    }
+
+    pub fn peer_addr(&self) -> io::Result<VsockAddr> {
+        let mut addr: ffi::sockaddr_vm = unsafe { mem::zeroed() };
+        let mut len = mem::size_of::<ffi::sockaddr_vm>() as ffi::socklen_t;
+
+        try!(super::cvt(unsafe {
+            ::libc::getpeername(self.as_raw_fd(),
+                                &mut addr as *mut _ as *mut ::libc::sockaddr,
+                                &mut len)
+        }));
+
+        Ok(VsockAddr::new(addr.svm_cid, addr.svm_port))
+    }
+
+    pub fn try_clone(&self) -> io::Result<VsockSocket> {
+        net::dup(&self.io)
+            .map(From::from)
+    }
+
+    pub fn shutdown(&self, how: net::Shutdown) -> io::Result<()> {
+        net::shutdown(&self.io, how)
+    }
+
+    pub fn take_socket_error(&self) -> io::Result<()> {
+        net::take_socket_error(&self.io)
+    }
+}
+
+impl Read for VsockSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl Write for VsockSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl Evented for VsockSocket {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
+
+impl Socket for VsockSocket {
+}
+
+impl From<Io> for VsockSocket {
+    fn from(io: Io) -> VsockSocket {
+        VsockSocket { io: io }
+    }
+}
+
+impl FromRawFd for VsockSocket {
+    unsafe fn from_raw_fd(fd: RawFd) -> VsockSocket {
+        VsockSocket { io: Io::from_raw_fd(fd) }
+    }
+}
+
+impl AsRawFd for VsockSocket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.io.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for VsockSocket {
+    fn into_raw_fd(self) -> RawFd {
+        self.io.into_raw_fd()
+    }
+}
+
+fn to_sockaddr(addr: &VsockAddr) -> ffi::sockaddr_vm {
+    ffi::sockaddr_vm {
+        svm_family: ffi::AF_VSOCK as u16,
+        svm_reserved1: 0,
+        svm_port: addr.port,
+        svm_cid: addr.cid,
+        svm_zero: [0; 4],
+    }
+}
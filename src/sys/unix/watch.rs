@@ -0,0 +1,177 @@
+//! kqueue based watchers for filters that do not map onto readiness.
+//!
+//! Each watcher owns a private kqueue holding the filter. The private kqueue
+//! descriptor becomes readable when it has pending events, so it can be
+//! registered with the event loop's selector like any other descriptor.
+//! Pending events are then drained with a zero timeout `kevent` call.
+use {io, Evented, EventSet, Io, PollOpt, Selector, Token};
+use nix::sys::event::{EventFilter, EventFlag, FilterFlag, KEvent, kqueue, kevent};
+use nix::sys::event::{EV_ADD, EV_CLEAR};
+use std::path::Path;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+struct Watcher {
+    kq: Io,
+}
+
+impl Watcher {
+    fn new(ident: usize, filter: EventFilter, fflags: FilterFlag) -> io::Result<Watcher> {
+        let kq = Io::from_raw_fd(try!(kqueue().map_err(super::from_nix_error)));
+        let flags: EventFlag = EV_ADD | EV_CLEAR;
+
+        let change = KEvent {
+            ident: ident as ::libc::uintptr_t,
+            filter: filter,
+            flags: flags,
+            fflags: fflags,
+            data: 0,
+            udata: 0,
+        };
+
+        try!(kevent(kq.as_raw_fd(), &[change], &mut [], 0)
+                .map_err(super::from_nix_error));
+
+        Ok(Watcher { kq: kq })
+    }
+
+    // Drains pending events, merging their filter flags
+    fn poll(&self) -> io::Result<FilterFlag> {
+        let mut evts = [KEvent {
+            ident: 0,
+            filter: EventFilter::EVFILT_READ,
+            flags: EventFlag::empty(),
+            fflags: FilterFlag::empty(),
+            data: 0,
+            udata: 0,
+        }; 16];
+
+        let mut fflags = FilterFlag::empty();
+
+        loop {
+            let cnt = try!(kevent(self.kq.as_raw_fd(), &[], &mut evts, 0)
+                               .map_err(super::from_nix_error));
+
+            for e in evts[..cnt].iter() {
+                fflags = fflags | e.fflags;
+            }
+
+            if cnt < evts.len() {
+                return Ok(fflags);
+            }
+        }
+    }
+}
+
+/*
+ *
+ * ===== FileWatch =====
+ *
+ */
+
+pub struct FileWatch {
+    watcher: Watcher,
+    // Keeps the watched vnode open for the lifetime of the watch
+    file: Io,
+}
+
+impl FileWatch {
+    pub fn new<P: AsRef<Path> + ?Sized>(path: &P) -> io::Result<FileWatch> {
+        use nix::fcntl::{open, O_RDONLY, O_CLOEXEC};
+        use nix::sys::event::{NOTE_DELETE, NOTE_WRITE, NOTE_EXTEND, NOTE_ATTRIB,
+                              NOTE_LINK, NOTE_RENAME, NOTE_REVOKE};
+        use nix::sys::stat::Mode;
+
+        let fd = try!(open(path.as_ref(), O_RDONLY | O_CLOEXEC, Mode::empty())
+                          .map_err(super::from_nix_error));
+        let file = Io::from_raw_fd(fd);
+
+        let fflags = NOTE_DELETE | NOTE_WRITE | NOTE_EXTEND | NOTE_ATTRIB |
+                     NOTE_LINK | NOTE_RENAME | NOTE_REVOKE;
+
+        let watcher = try!(Watcher::new(fd as usize, EventFilter::EVFILT_VNODE, fflags));
+
+        Ok(FileWatch { watcher: watcher, file: file })
+    }
+
+    /// Returns the raw `NOTE_*` flags of all events since the last call
+    pub fn poll(&self) -> io::Result<u32> {
+        self.watcher.poll()
+            .map(|fflags| fflags.bits() as u32)
+    }
+
+    pub fn file_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+impl Evented for FileWatch {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.watcher.kq.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.watcher.kq.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.watcher.kq.deregister(selector)
+    }
+}
+
+impl AsRawFd for FileWatch {
+    fn as_raw_fd(&self) -> RawFd {
+        self.watcher.kq.as_raw_fd()
+    }
+}
+
+/*
+ *
+ * ===== ProcWatch =====
+ *
+ */
+
+pub struct ProcWatch {
+    watcher: Watcher,
+    pid: i32,
+}
+
+impl ProcWatch {
+    pub fn new(pid: i32) -> io::Result<ProcWatch> {
+        use nix::sys::event::{NOTE_EXIT, NOTE_FORK, NOTE_EXEC};
+
+        let watcher = try!(Watcher::new(pid as usize, EventFilter::EVFILT_PROC,
+                                        NOTE_EXIT | NOTE_FORK | NOTE_EXEC));
+
+        Ok(ProcWatch { watcher: watcher, pid: pid })
+    }
+
+    /// Returns the raw `NOTE_*` flags of all events since the last call
+    pub fn poll(&self) -> io::Result<u32> {
+        self.watcher.poll()
+            .map(|fflags| fflags.bits() as u32)
+    }
+
+    pub fn pid(&self) -> i32 {
+        self.pid
+    }
+}
+
+impl Evented for ProcWatch {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.watcher.kq.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.watcher.kq.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.watcher.kq.deregister(selector)
+    }
+}
+
+impl AsRawFd for ProcWatch {
+    fn as_raw_fd(&self) -> RawFd {
+        self.watcher.kq.as_raw_fd()
+    }
+}
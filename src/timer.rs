@@ -1,31 +1,131 @@
 use token::Token;
 use util::Slab;
 use clock_ticks::precise_time_ns;
-use std::{usize, iter};
-use std::cmp::max;
+use std::{cmp, fmt, mem, u64, usize, iter};
+use std::cmp::{max, min};
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use self::TimerErrorKind::TimerOverflow;
 
 const EMPTY: Token = Token(usize::MAX);
 const NS_PER_MS: u64 = 1_000_000;
+const NS_PER_SEC: u64 = 1_000_000_000;
 
 // Implements coarse-grained timeouts using an algorithm based on hashed timing
 // wheels by Varghese & Lauck.
 //
+// Timeouts more than one revolution of the wheel away wait in an overflow
+// heap ordered by tick, and move into the wheel once they come within range.
+// This keeps each wheel slot to a single tick's worth of entries, so long
+// timeouts (hours, days) are not rescanned on every revolution.
+//
 // TODO:
-// * Handle the case when the timer falls more than an entire wheel behind. There
-//   is no point to loop multiple times around the wheel in one go.
+// * Handle the case when the timer falls more than an entire wheel behind
+//   while the wheel is not empty. There is no point to loop multiple times
+//   around the wheel in one go.
 // * New type for tick, now() -> Tick
+
+/// A source of monotonic time for a `Timer`
+pub trait Clock: fmt::Debug + Send {
+    /// Returns the current time in nanoseconds, measured from an arbitrary
+    /// but fixed origin. Must never go backwards.
+    fn now_ns(&self) -> u64;
+}
+
+/// The default clock, backed by the OS monotonic clock
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock;
+
+impl Clock for MonotonicClock {
+    fn now_ns(&self) -> u64 {
+        precise_time_ns()
+    }
+}
+
+/// A clock that only moves when told to, for testing timeout logic
+/// deterministically. Clones share the same time.
+///
+/// ```
+/// use mio::{ManualClock, Timer};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut timer = Timer::with_clock(Duration::from_millis(10), 64, 128, Box::new(clock.clone()));
+///
+/// timer.setup();
+/// timer.timeout(1, Duration::from_secs(30)).unwrap();
+///
+/// clock.advance(Duration::from_secs(29));
+/// assert_eq!(timer.poll(), None);
+///
+/// clock.advance(Duration::from_secs(1));
+/// assert_eq!(timer.poll(), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<u64>>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock { now: Arc::new(Mutex::new(0)) }
+    }
+
+    /// Moves the clock forward by `dur`
+    pub fn advance(&self, dur: Duration) {
+        *self.now.lock().unwrap() += duration_to_ns(dur);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_ns(&self) -> u64 {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// A hashed timing wheel.
+///
+/// Every `EventLoop` drives one of these internally. It can also be used on
+/// its own alongside `Poll`: cap the poll timeout with `next_tick_in_ms` and
+/// call `poll` after each wakeup to collect the timeouts that have expired.
+///
+/// ```
+/// use mio::{Poll, Timer};
+/// use std::time::Duration;
+///
+/// let mut poll = Poll::new().unwrap();
+/// let mut timer = Timer::new(Duration::from_millis(10), 64, 128);
+///
+/// timer.setup();
+/// timer.timeout_ms("retransmit", 20).unwrap();
+///
+/// loop {
+///     poll.poll(timer.next_tick_in_ms() as usize).unwrap();
+///
+///     if let Some(token) = timer.poll() {
+///         assert_eq!(token, "retransmit");
+///         break;
+///     }
+/// }
+/// ```
 #[derive(Debug)]
 pub struct Timer<T> {
-    // Size of each tick in milliseconds
-    tick_ms: u64,
+    // Size of each tick in nanoseconds
+    tick_ns: u64,
     // Slab of timeout entries
     entries: Slab<Entry<T>>,
     // Timeout wheel. Each tick, the timer will look at the next slot for
     // timeouts that match the current tick.
     wheel: Vec<Token>,
-    // Tick 0's time in milliseconds
+    // Number of entries linked into the wheel
+    wheel_len: usize,
+    // Timeouts beyond the wheel's range, soonest first
+    overflow: BinaryHeap<Overflow>,
+    // Number of overflow heap entries whose timeout has been cleared
+    stale: usize,
+    // Tick 0's time in nanoseconds
     start: u64,
     // The current tick
     tick: u64,
@@ -33,29 +133,59 @@ pub struct Timer<T> {
     next: Token,
     // Masks the target tick to get the slot
     mask: u64,
+    // Sequence number given to the next timeout, so that stale handles never
+    // match an entry that reused their slab slot
+    seq: u64,
+    // Source of the current time
+    clock: Box<Clock>,
 }
 
-#[derive(Copy, Clone)]
+/// A handle to a pending timeout, used to cancel it
+#[derive(Copy, Clone, Debug)]
 pub struct Timeout {
     // Reference into the timer entry slab
     token: Token,
-    // Tick that it should matchup with
-    tick: u64,
+    // Sequence number that the entry should match up with
+    seq: u64,
 }
 
 impl<T> Timer<T> {
-    pub fn new(tick_ms: u64, mut slots: usize, mut capacity: usize) -> Timer<T> {
+    /// Returns a new timer ticking every `tick`. `slots` is the size of the
+    /// wheel and `capacity` the maximum number of pending timeouts; both are
+    /// rounded up to a power of two.
+    ///
+    /// Timeouts are rounded up to the next tick, so a smaller tick gives
+    /// more precise expirations at the cost of more frequent wakeups. Memory
+    /// for `capacity` entries is allocated up front.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tick` is zero.
+    pub fn new(tick: Duration, slots: usize, capacity: usize) -> Timer<T> {
+        Timer::with_clock(tick, slots, capacity, Box::new(MonotonicClock))
+    }
+
+    /// Returns a new timer that reads the current time from `clock`
+    pub fn with_clock(tick: Duration, mut slots: usize, mut capacity: usize, clock: Box<Clock>) -> Timer<T> {
+        let tick_ns = duration_to_ns(tick);
+        assert!(tick_ns > 0, "timer tick must be non-zero");
+
         slots = slots.next_power_of_two();
         capacity = capacity.next_power_of_two();
 
         Timer {
-            tick_ms: tick_ms,
+            tick_ns: tick_ns,
             entries: Slab::new(capacity),
             wheel: iter::repeat(EMPTY).take(slots).collect(),
+            wheel_len: 0,
+            overflow: BinaryHeap::new(),
+            stale: 0,
             start: 0,
             tick: 0,
             next: EMPTY,
-            mask: (slots as u64) - 1
+            mask: (slots as u64) - 1,
+            seq: 0,
+            clock: clock,
         }
     }
 
@@ -64,16 +194,28 @@ impl<T> Timer<T> {
         self.entries.count()
     }
 
-    // Number of ms remaining until the next tick
+    /// Absolute time of the next tick, in nanoseconds of the timer's clock
+    pub fn next_tick_at_ns(&self) -> u64 {
+        self.start + (self.tick + 1) * self.tick_ns
+    }
+
+    /// How far behind tick `now` the wheel is, in nanoseconds. Zero when
+    /// every tick is processed on time.
+    #[cfg(feature = "metrics")]
+    pub fn lag_ns(&self, now: u64) -> u64 {
+        now.saturating_sub(self.tick) * self.tick_ns
+    }
+
+    /// Number of ms remaining until the next tick, rounded up
     pub fn next_tick_in_ms(&self) -> u64 {
-        let now = self.now_ms();
-        let nxt = self.start + (self.tick + 1) * self.tick_ms;
+        let now = self.now_ns();
+        let nxt = self.next_tick_at_ns();
 
         if nxt <= now {
             return 0;
         }
 
-        nxt - now
+        (nxt - now + NS_PER_MS - 1) / NS_PER_MS
     }
 
     /*
@@ -82,13 +224,14 @@ impl<T> Timer<T> {
      *
      */
 
-    // Sets the starting time of the timer using the current system time
+    /// Sets the starting time of the timer using the current system time.
+    /// Must be called before any timeout is set.
     pub fn setup(&mut self) {
-        let now = self.now_ms();
-        self.set_start_ms(now);
+        let now = self.now_ns();
+        self.set_start_ns(now);
     }
 
-    fn set_start_ms(&mut self, start: u64) {
+    fn set_start_ns(&mut self, start: u64) {
         assert!(!self.is_initialized(), "the timer has already started");
         self.start = start;
     }
@@ -99,16 +242,42 @@ impl<T> Timer<T> {
      *
      */
 
+    /// Schedules `token` to expire after `delay`
+    pub fn timeout(&mut self, token: T, delay: Duration) -> TimerResult<Timeout> {
+        let at = self.now_ns().saturating_add(duration_to_ns(delay));
+        self.timeout_at_ns(token, at)
+    }
+
+    /// Schedules `token` to expire at `deadline`. Deadlines that have
+    /// already passed expire on the next tick.
+    pub fn timeout_at(&mut self, token: T, deadline: Instant) -> TimerResult<Timeout> {
+        let now = Instant::now();
+        let mut at = self.now_ns();
+
+        if deadline > now {
+            at = at.saturating_add(duration_to_ns(deadline - now));
+        }
+
+        self.timeout_at_ns(token, at)
+    }
+
+    /// Schedules `token` to expire after `delay` milliseconds
     pub fn timeout_ms(&mut self, token: T, delay: u64) -> TimerResult<Timeout> {
-        let at = self.now_ms() + max(0, delay);
-        self.timeout_at_ms(token, at)
+        let at = self.now_ns() + max(0, delay) * NS_PER_MS;
+        self.timeout_at_ns(token, at)
     }
 
-    pub fn timeout_at_ms(&mut self, token: T, mut at: u64) -> TimerResult<Timeout> {
+    /// Schedules `token` to expire at the given absolute time, in
+    /// milliseconds of the timer's clock
+    pub fn timeout_at_ms(&mut self, token: T, at: u64) -> TimerResult<Timeout> {
+        self.timeout_at_ns(token, at * NS_PER_MS)
+    }
+
+    fn timeout_at_ns(&mut self, token: T, mut at: u64) -> TimerResult<Timeout> {
         // Make relative to start
         at -= self.start;
-        // Calculate tick
-        let mut tick = (at + self.tick_ms - 1) / self.tick_ms;
+        // Calculate tick, rounding up without overflowing for far deadlines
+        let mut tick = at / self.tick_ns + if at % self.tick_ns != 0 { 1 } else { 0 };
 
         // Always target at least 1 tick in the future
         if tick <= self.tick {
@@ -118,32 +287,73 @@ impl<T> Timer<T> {
         self.insert(token, tick)
     }
 
+    /// Cancels the timeout, returning false if it has already expired or
+    /// been cleared. This is O(1) and safe to call with any handle.
     pub fn clear(&mut self, timeout: Timeout) -> bool {
-        let links = match self.entries.get(timeout.token) {
-            Some(e) => e.links,
-            None => return false
+        let (links, linked) = match self.entries.get(timeout.token) {
+            Some(e) if e.seq == timeout.seq => (e.links, e.linked),
+            _ => return false
         };
 
-        // Sanity check
-        if links.tick != timeout.tick {
-            return false;
+        if linked {
+            self.unlink(&links, timeout.token);
         }
 
-        self.unlink(&links, timeout.token);
         self.entries.remove(timeout.token);
+
+        if !linked {
+            // The heap entry is skipped once popped; drop the dead ones
+            // early if they make up most of the heap
+            self.stale += 1;
+
+            if self.stale > self.overflow.len() / 2 {
+                self.compact();
+            }
+        }
+
         true
     }
 
     fn insert(&mut self, token: T, tick: u64) -> TimerResult<Timeout> {
-        // Get the slot for the requested tick
-        let slot = (tick & self.mask) as usize;
-        let curr = self.wheel[slot];
+        let seq = self.seq;
 
         // Insert the new entry
         let token = try!(
-            self.entries.insert(Entry::new(token, tick, curr))
+            self.entries.insert(Entry::new(token, tick, seq))
             .map_err(|_| TimerError::overflow()));
 
+        self.seq += 1;
+
+        if tick > self.tick + self.mask {
+            trace!("deferring timeout; tick={}; token={:?}", tick, token);
+
+            self.overflow.push(Overflow {
+                tick: tick,
+                token: token,
+                seq: seq,
+            });
+        } else {
+            self.link(token, tick);
+        }
+
+        // Return the new timeout
+        Ok(Timeout {
+            token: token,
+            seq: seq,
+        })
+    }
+
+    fn link(&mut self, token: Token, tick: u64) {
+        // Get the slot for the requested tick
+        let slot = self.slot_for(tick);
+        let curr = self.wheel[slot];
+
+        {
+            let entry = &mut self.entries[token];
+            entry.linked = true;
+            entry.links.next = curr;
+        }
+
         if curr != EMPTY {
             // If there was a previous entry, set its prev pointer to the new
             // entry
@@ -152,20 +362,59 @@ impl<T> Timer<T> {
 
         // Update the head slot
         self.wheel[slot] = token;
+        self.wheel_len += 1;
 
         trace!("inserted timout; slot={}; token={:?}", slot, token);
+    }
 
-        // Return the new timeout
-        Ok(Timeout {
-            token: token,
-            tick: tick
-        })
+    // Moves overflow entries that are now within range into the wheel
+    fn migrate(&mut self) {
+        let limit = self.tick + self.mask;
+
+        loop {
+            let next = match self.overflow.peek() {
+                Some(e) if e.tick <= limit => *e,
+                _ => return,
+            };
+
+            self.overflow.pop();
+
+            // Entries cleared while waiting are left in the heap
+            let live = match self.entries.get(next.token) {
+                Some(e) => e.seq == next.seq,
+                None => false,
+            };
+
+            if live {
+                self.link(next.token, next.tick);
+            } else {
+                self.stale -= 1;
+            }
+        }
+    }
+
+    // Rebuilds the overflow heap without the entries of cleared timeouts, so
+    // that it never holds more than twice as many entries as are pending
+    fn compact(&mut self) {
+        let overflow = mem::replace(&mut self.overflow, BinaryHeap::new());
+        let entries = &self.entries;
+
+        let live: Vec<Overflow> = overflow.into_vec().into_iter()
+            .filter(|o| entries.get(o.token).map(|e| e.seq == o.seq).unwrap_or(false))
+            .collect();
+
+        trace!("compacted overflow; live={}; stale={}", live.len(), self.stale);
+
+        self.overflow = BinaryHeap::from(live);
+        self.stale = 0;
     }
 
     fn unlink(&mut self, links: &EntryLinks, token: Token) {
        trace!("unlinking timeout; slot={}; token={:?}",
                self.slot_for(links.tick), token);
 
+        self.wheel_len -= 1;
+
         if links.prev == EMPTY {
             let slot = self.slot_for(links.tick);
             self.wheel[slot] = links.next;
@@ -190,10 +439,20 @@ impl<T> Timer<T> {
      *
      */
 
+    /// Returns the current tick
     pub fn now(&self) -> u64 {
-        self.ms_to_tick(self.now_ms())
+        self.ns_to_tick(self.now_ns())
     }
 
+    /// Returns the next timeout that expired at or before the current time,
+    /// or `None` once there are no more
+    pub fn poll(&mut self) -> Option<T> {
+        let now = self.now();
+        self.tick_to(now)
+    }
+
+    /// Advances the timer up to tick `now`, returning the next timeout that
+    /// expired along the way
     pub fn tick_to(&mut self, now: u64) -> Option<T> {
         trace!("tick_to; now={}; tick={}", now, self.tick);
 
@@ -204,6 +463,15 @@ impl<T> Timer<T> {
 
             if curr == EMPTY {
                 self.tick += 1;
+
+                if self.wheel_len == 0 {
+                    // Nothing can expire before the soonest overflow entry,
+                    // so skip the empty ticks in one go
+                    let first = self.overflow.peek().map(|e| e.tick).unwrap_or(now);
+                    self.tick = max(self.tick, min(now, first));
+                }
+
+                self.migrate();
                 self.next = self.wheel[self.slot_for(self.tick)];
             } else {
                 let links = self.entries[curr].links;
@@ -243,38 +511,80 @@ impl<T> Timer<T> {
         (self.mask & tick) as usize
     }
 
-    // Convert a ms duration into a number of ticks, rounds up
+    // Convert a point in time into the tick it falls in
     #[inline]
+    fn ns_to_tick(&self, ns: u64) -> u64 {
+        (ns - self.start) / self.tick_ns
+    }
+
+    #[cfg(test)]
     fn ms_to_tick(&self, ms: u64) -> u64 {
-        (ms - self.start) / self.tick_ms
+        self.ns_to_tick(ms * NS_PER_MS)
     }
 
     #[inline]
-    fn now_ms(&self) -> u64 {
-        precise_time_ns() / NS_PER_MS
+    fn now_ns(&self) -> u64 {
+        self.clock.now_ns()
     }
 }
 
+// Saturates at `u64::MAX`, some 584 years
+fn duration_to_ns(dur: Duration) -> u64 {
+    dur.as_secs()
+        .checked_mul(NS_PER_SEC)
+        .and_then(|ns| ns.checked_add(dur.subsec_nanos() as u64))
+        .unwrap_or(u64::MAX)
+}
+
 // Doubly linked list of timer entries. Allows for efficient insertion /
 // removal of timeouts.
 struct Entry<T> {
     token: T,
+    seq: u64,
+    // False while the entry waits in the overflow heap
+    linked: bool,
     links: EntryLinks,
 }
 
 impl<T> Entry<T> {
-    fn new(token: T, tick: u64, next: Token) -> Entry<T> {
+    fn new(token: T, tick: u64, seq: u64) -> Entry<T> {
         Entry {
             token: token,
+            seq: seq,
+            linked: false,
             links: EntryLinks {
                 tick: tick,
                 prev: EMPTY,
-                next: next,
+                next: EMPTY,
             },
         }
     }
 }
 
+// A timeout waiting in the overflow heap. Ordered so that the heap pops the
+// soonest tick first.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Overflow {
+    tick: u64,
+    token: Token,
+    seq: u64,
+}
+
+impl Ord for Overflow {
+    fn cmp(&self, other: &Overflow) -> cmp::Ordering {
+        match other.tick.cmp(&self.tick) {
+            cmp::Ordering::Equal => other.seq.cmp(&self.seq),
+            ord => ord,
+        }
+    }
+}
+
+impl PartialOrd for Overflow {
+    fn partial_cmp(&self, other: &Overflow) -> Option<cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 #[derive(Copy, Clone)]
 struct EntryLinks {
     tick: u64,
@@ -306,7 +616,8 @@ pub enum TimerErrorKind {
 
 #[cfg(test)]
 mod test {
-    use super::Timer;
+    use super::{ManualClock, Timer};
+    use std::time::{Duration, Instant};
 
     #[test]
     pub fn test_timeout_next_tick() {
@@ -348,6 +659,24 @@ mod test {
         assert_eq!(t.count(), 0);
     }
 
+    #[test]
+    pub fn test_clearing_stale_timeout() {
+        let mut t = timer();
+
+        let a = t.timeout_at_ms("a", 100).unwrap();
+        assert!(t.clear(a));
+
+        // "b" reuses the slab slot of "a" and targets the same tick
+        t.timeout_at_ms("b", 100).unwrap();
+        assert!(!t.clear(a));
+        assert_eq!(t.count(), 1);
+
+        let tick = t.ms_to_tick(100);
+        assert_eq!(Some("b"), t.tick_to(tick));
+        assert!(!t.clear(a));
+        assert_eq!(t.count(), 0);
+    }
+
     #[test]
     pub fn test_multiple_timeouts_same_tick() {
         let mut t = timer();
@@ -444,6 +773,97 @@ mod test {
         assert_eq!(0, t.count());
     }
 
+    #[test]
+    pub fn test_duration_and_instant_timeouts() {
+        let mut t = timer();
+
+        t.timeout("b", Duration::from_secs(1)).unwrap();
+        t.timeout_at("a", Instant::now()).unwrap();
+
+        let tick = t.now() + 1;
+        assert_eq!(Some("a"), t.tick_to(tick));
+        assert_eq!(None, t.tick_to(tick));
+
+        let tick = t.now() + 1_000 / TICK + 1;
+        assert_eq!(Some("b"), t.tick_to(tick));
+    }
+
+    #[test]
+    pub fn test_manual_clock() {
+        let clock = ManualClock::new();
+        let mut t = Timer::with_clock(Duration::from_millis(TICK), SLOTS, 32, Box::new(clock.clone()));
+
+        t.setup();
+        t.timeout("a", Duration::from_millis(250)).unwrap();
+        assert_eq!(t.next_tick_in_ms(), TICK);
+
+        clock.advance(Duration::from_millis(200));
+        assert_eq!(None, t.poll());
+
+        clock.advance(Duration::from_millis(100));
+        assert_eq!(Some("a"), t.poll());
+        assert_eq!(None, t.poll());
+    }
+
+    #[test]
+    pub fn test_sub_millisecond_tick() {
+        let mut t = Timer::new(Duration::new(0, 100_000), SLOTS, 32);
+
+        t.timeout_at_ms("a", 1).unwrap();
+
+        // 0.9ms
+        assert_eq!(None, t.tick_to(9));
+        assert_eq!(Some("a"), t.tick_to(10));
+    }
+
+    #[test]
+    pub fn test_long_timeouts() {
+        let mut t = timer();
+        let mut tick;
+
+        // Many revolutions of the wheel away
+        let far = 100 + TICK * SLOTS as u64 * 10;
+
+        t.timeout_at_ms("a", far).unwrap();
+        let b = t.timeout_at_ms("b", far * 2).unwrap();
+        t.timeout_at_ms("c", 200).unwrap();
+
+        tick = t.ms_to_tick(200);
+        assert_eq!(Some("c"), t.tick_to(tick));
+
+        tick = t.ms_to_tick(far - TICK);
+        assert_eq!(None, t.tick_to(tick));
+
+        tick = t.ms_to_tick(far);
+        assert_eq!(Some("a"), t.tick_to(tick));
+        assert_eq!(None, t.tick_to(tick));
+
+        // Clearing works while the timeout is still deferred
+        assert!(t.clear(b));
+        assert_eq!(0, t.count());
+
+        tick = t.ms_to_tick(far * 2);
+        assert_eq!(None, t.tick_to(tick));
+    }
+
+    #[test]
+    pub fn test_clearing_long_timeouts_bounds_overflow() {
+        let mut t = Timer::new(Duration::from_millis(TICK), SLOTS, 8);
+        let far = TICK * SLOTS as u64 * 10;
+
+        let keep = t.timeout_at_ms("keep", far).unwrap();
+
+        for _ in 0..1_000 {
+            let to = t.timeout_at_ms("far", far).unwrap();
+            assert!(t.clear(to));
+            assert!(t.overflow.len() <= 2 * 8);
+        }
+
+        assert!(t.clear(keep));
+        assert!(t.overflow.len() <= 1);
+        assert_eq!(0, t.count());
+    }
+
     #[test]
     pub fn test_clearing_timeout_between_triggers() {
         let mut t = timer();
@@ -470,7 +890,18 @@ mod test {
     const TICK: u64 = 100;
     const SLOTS: usize = 16;
 
+    #[test]
+    pub fn test_huge_timeout_saturates() {
+        let mut t = timer();
+
+        t.timeout("a", Duration::from_secs(::std::u64::MAX)).unwrap();
+
+        let tick = t.ms_to_tick(1_000_000);
+        assert_eq!(None, t.tick_to(tick));
+        assert_eq!(t.count(), 1);
+    }
+
     fn timer() -> Timer<&'static str> {
-        Timer::new(TICK, SLOTS, 32)
+        Timer::new(Duration::from_millis(TICK), SLOTS, 32)
     }
 }
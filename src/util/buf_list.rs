@@ -0,0 +1,89 @@
+use {WriteVectored};
+use bytes::Buf;
+use std::collections::VecDeque;
+use std::io;
+
+/// A sequence of buffers read as one.
+///
+/// Lets a message be assembled from several owned pieces, such as a header
+/// and a body, without copying them into a contiguous buffer. `write_to`
+/// hands every segment to a single `writev`.
+#[derive(Debug)]
+pub struct BufList<B: Buf> {
+    bufs: VecDeque<B>,
+}
+
+impl<B: Buf> BufList<B> {
+    pub fn new() -> BufList<B> {
+        BufList { bufs: VecDeque::new() }
+    }
+
+    /// Appends `buf` after the existing segments
+    pub fn push(&mut self, buf: B) {
+        if buf.remaining() > 0 {
+            self.bufs.push_back(buf);
+        }
+    }
+
+    /// Writes as many segments as `dst` accepts with one call, advancing
+    /// past what was written. Fails with `ErrorKind::WouldBlock` if `dst`
+    /// cannot take any data.
+    pub fn write_to<W: WriteVectored>(&mut self, dst: &mut W) -> io::Result<usize> {
+        let cnt = {
+            let src: Vec<&[u8]> = self.bufs.iter()
+                .map(|buf| buf.bytes())
+                .collect();
+
+            try!(dst.write_bufs(&src))
+        };
+
+        self.advance(cnt);
+        Ok(cnt)
+    }
+
+    /// The number of segments with data remaining
+    pub fn len(&self) -> usize {
+        self.bufs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bufs.is_empty()
+    }
+}
+
+impl<B: Buf> Buf for BufList<B> {
+    fn remaining(&self) -> usize {
+        self.bufs.iter().fold(0, |sum, buf| sum + buf.remaining())
+    }
+
+    fn bytes(&self) -> &[u8] {
+        match self.bufs.front() {
+            Some(buf) => buf.bytes(),
+            None => &[],
+        }
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let done = match self.bufs.front_mut() {
+                Some(buf) => {
+                    let rem = buf.remaining();
+
+                    if cnt < rem {
+                        buf.advance(cnt);
+                        return;
+                    }
+
+                    buf.advance(rem);
+                    cnt -= rem;
+                    true
+                }
+                None => return,
+            };
+
+            if done {
+                self.bufs.pop_front();
+            }
+        }
+    }
+}
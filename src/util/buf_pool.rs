@@ -0,0 +1,197 @@
+use bytes::{Buf, MutBuf};
+use std::{cmp, fmt, mem, ptr};
+use std::sync::{Arc, Mutex};
+
+/// Recycles fixed-size byte buffers, so that per-connection buffers do not
+/// have to be allocated and freed as connections come and go.
+///
+/// `checkout` hands out a `PooledBuf`, which returns its memory to the pool
+/// when dropped. At most `max_idle` buffers are kept around. The pool is a
+/// cheap handle that may be cloned and shared between threads.
+#[derive(Clone)]
+pub struct BufPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    buf_size: usize,
+    max_idle: usize,
+    idle: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl BufPool {
+    pub fn new(buf_size: usize, max_idle: usize) -> BufPool {
+        BufPool {
+            inner: Arc::new(Inner {
+                buf_size: buf_size,
+                max_idle: max_idle,
+                idle: Mutex::new(Vec::with_capacity(max_idle)),
+            }),
+        }
+    }
+
+    /// Allocates buffers up front until `cnt` are idle, bounded by `max_idle`
+    pub fn reserve(&self, cnt: usize) {
+        let mut idle = self.inner.idle.lock().unwrap();
+        let cnt = cmp::min(cnt, self.inner.max_idle);
+
+        while idle.len() < cnt {
+            idle.push(alloc(self.inner.buf_size));
+        }
+    }
+
+    /// Returns an empty buffer, reusing an idle one if available
+    pub fn checkout(&self) -> PooledBuf {
+        let mem = self.inner.idle.lock().unwrap().pop()
+            .unwrap_or_else(|| alloc(self.inner.buf_size));
+
+        PooledBuf {
+            mem: mem,
+            pos: 0,
+            lim: 0,
+            mark: None,
+            pool: self.clone(),
+        }
+    }
+
+    /// The capacity of each buffer
+    pub fn buf_size(&self) -> usize {
+        self.inner.buf_size
+    }
+
+    /// The number of buffers waiting to be checked out
+    pub fn idle(&self) -> usize {
+        self.inner.idle.lock().unwrap().len()
+    }
+
+    fn checkin(&self, mem: Box<[u8]>) {
+        let mut idle = self.inner.idle.lock().unwrap();
+
+        if idle.len() < self.inner.max_idle {
+            idle.push(mem);
+        }
+    }
+}
+
+impl fmt::Debug for BufPool {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "BufPool {{ buf_size: {}, idle: {} }}", self.buf_size(), self.idle())
+    }
+}
+
+/// A buffer checked out of a `BufPool`.
+///
+/// Bytes written through `MutBuf` are read back, in order, through `Buf`.
+/// Once everything written has been read, and no mark is set, the buffer
+/// starts over from the beginning.
+///
+/// Incremental parsers can `mark` the start of a frame and `reset` to it
+/// when the frame turns out to be incomplete. `compact` reclaims the space
+/// taken by data that has already been read.
+pub struct PooledBuf {
+    mem: Box<[u8]>,
+    // Read cursor
+    pos: usize,
+    // Write cursor
+    lim: usize,
+    mark: Option<usize>,
+    pool: BufPool,
+}
+
+impl PooledBuf {
+    /// Discards any unread data and the mark
+    pub fn clear(&mut self) {
+        self.pos = 0;
+        self.lim = 0;
+        self.mark = None;
+    }
+
+    /// Remembers the current read position, replacing any previous mark
+    pub fn mark(&mut self) {
+        self.mark = Some(self.pos);
+    }
+
+    /// Rewinds the read position to the mark, which is then cleared.
+    /// Returns false if no mark was set.
+    pub fn reset(&mut self) -> bool {
+        match self.mark.take() {
+            Some(pos) => {
+                self.pos = pos;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the unread data, starting at the mark if one is set, to the
+    /// front of the buffer
+    pub fn compact(&mut self) {
+        let start = self.mark.unwrap_or(self.pos);
+
+        if start == 0 {
+            return;
+        }
+
+        unsafe {
+            let base = self.mem.as_mut_ptr();
+            ptr::copy(base.offset(start as isize), base, self.lim - start);
+        }
+
+        self.pos -= start;
+        self.lim -= start;
+        self.mark = self.mark.map(|mark| mark - start);
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.mem.len()
+    }
+}
+
+impl Buf for PooledBuf {
+    fn remaining(&self) -> usize {
+        self.lim - self.pos
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.mem[self.pos..self.lim]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.pos += cmp::min(cnt, Buf::remaining(self));
+
+        if self.pos == self.lim && self.mark.is_none() {
+            self.clear();
+        }
+    }
+}
+
+impl MutBuf for PooledBuf {
+    fn remaining(&self) -> usize {
+        self.mem.len() - self.lim
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.lim += cmp::min(cnt, MutBuf::remaining(self));
+    }
+
+    unsafe fn mut_bytes(&mut self) -> &mut [u8] {
+        &mut self.mem[self.lim..]
+    }
+}
+
+impl Drop for PooledBuf {
+    fn drop(&mut self) {
+        let mem = mem::replace(&mut self.mem, Vec::new().into_boxed_slice());
+        self.pool.checkin(mem);
+    }
+}
+
+impl fmt::Debug for PooledBuf {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "PooledBuf {{ pos: {}, lim: {}, mark: {:?}, cap: {} }}", self.pos, self.lim, self.mark, self.capacity())
+    }
+}
+
+fn alloc(size: usize) -> Box<[u8]> {
+    vec![0; size].into_boxed_slice()
+}
@@ -0,0 +1,140 @@
+use {io, sys, Evented, EventSet, NotifyError, PollOpt, Selector, Token};
+use std::fmt;
+use std::sync::{mpsc, Arc};
+
+/// Creates a `std::sync::mpsc` channel whose receiving end can be registered
+/// with an `EventLoop`
+pub fn channel<T>() -> io::Result<(Sender<T>, Receiver<T>)> {
+    let (tx, rx) = mpsc::channel();
+    let rx = try!(Receiver::new(rx));
+    let tx = Sender::new(tx, rx.waker());
+
+    Ok((tx, rx))
+}
+
+/// A `std::sync::mpsc::Receiver` paired with a wakeup handle, so that the
+/// arrival of messages is reported as a readable event.
+///
+/// The receiver stays readable until `try_recv` finds the channel empty.
+/// Every send must be followed by a wakeup, either by sending through
+/// `Sender` or by calling `Waker::wake` next to existing `mpsc` senders.
+pub struct Receiver<T> {
+    rx: mpsc::Receiver<T>,
+    waker: Waker,
+}
+
+impl<T> Receiver<T> {
+    pub fn new(rx: mpsc::Receiver<T>) -> io::Result<Receiver<T>> {
+        Ok(Receiver {
+            rx: rx,
+            waker: Waker {
+                awaken: Arc::new(try!(sys::Awakener::new())),
+            },
+        })
+    }
+
+    /// Returns a handle for signaling that messages were sent
+    pub fn waker(&self) -> Waker {
+        self.waker.clone()
+    }
+
+    /// Receives a message without blocking. Pending wakeups are consumed
+    /// once the channel is found empty.
+    pub fn try_recv(&self) -> Result<T, mpsc::TryRecvError> {
+        match self.rx.try_recv() {
+            Err(mpsc::TryRecvError::Empty) => {
+                self.waker.awaken.cleanup();
+
+                // A message sent between the check and the cleanup had its
+                // wakeup consumed, so look again
+                self.rx.try_recv()
+            }
+            res => res,
+        }
+    }
+
+    pub fn get_ref(&self) -> &mpsc::Receiver<T> {
+        &self.rx
+    }
+}
+
+impl<T> Evented for Receiver<T> {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.waker.awaken.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.waker.awaken.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.waker.awaken.deregister(selector)
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Receiver<?>")
+    }
+}
+
+/// A `std::sync::mpsc::Sender` that wakes up its `Receiver` on every send
+pub struct Sender<T> {
+    tx: mpsc::Sender<T>,
+    waker: Waker,
+}
+
+impl<T> Sender<T> {
+    pub fn new(tx: mpsc::Sender<T>, waker: Waker) -> Sender<T> {
+        Sender {
+            tx: tx,
+            waker: waker,
+        }
+    }
+
+    pub fn send(&self, msg: T) -> Result<(), NotifyError<T>> {
+        if let Err(mpsc::SendError(msg)) = self.tx.send(msg) {
+            return Err(NotifyError::Closed(Some(msg)));
+        }
+
+        self.waker.wake().map_err(NotifyError::Io)
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        Sender {
+            tx: self.tx.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Sender<?>")
+    }
+}
+
+/// Makes a `Receiver` readable. May be shared between threads.
+#[derive(Clone)]
+pub struct Waker {
+    awaken: Arc<sys::Awakener>,
+}
+
+impl Waker {
+    pub fn wake(&self) -> io::Result<()> {
+        self.awaken.wakeup()
+    }
+}
+
+impl fmt::Debug for Waker {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Waker")
+    }
+}
+
+// The awakener only writes to and reads from file descriptors, which is
+// thread safe
+unsafe impl Sync for Waker { }
+unsafe impl Send for Waker { }
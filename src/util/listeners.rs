@@ -0,0 +1,221 @@
+use tcp::TcpListener;
+use unix::{Socket, UnixListener};
+use std::{env, io};
+use std::os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd};
+
+/// The environment variable `Listeners::from_env` reads
+pub const LISTENERS_ENV: &'static str = "MIO_LISTENERS";
+
+/// A named set of listening sockets handed from one process to another,
+/// e.g. to upgrade a server binary without refusing connections.
+///
+/// The old process adds its listeners and passes `encode()` to the new
+/// process, typically in `LISTENERS_ENV`. The listeners keep their
+/// close-on-exec flag until `inherit` is called between fork and exec, so
+/// other processes spawned meanwhile do not hold on to them. The new process
+/// rebuilds the set with `from_env` or `decode` and takes the listeners back
+/// by name.
+///
+/// When handing the sockets over a Unix socket instead of across exec,
+/// send `fds()` with `SCM_RIGHTS` alongside the encoded set; the receiver
+/// has to rewrite the fd numbers to the ones it received.
+#[derive(Debug, Clone)]
+pub struct Listeners {
+    entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Tcp,
+    Unix,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    name: String,
+    kind: Kind,
+    fd: RawFd,
+}
+
+impl Listeners {
+    pub fn new() -> Listeners {
+        Listeners { entries: vec![] }
+    }
+
+    /// Adds `listener` under `name`. Names and descriptors must be unique.
+    pub fn add_tcp(&mut self, name: &str, listener: &TcpListener) -> io::Result<()> {
+        self.add(name, Kind::Tcp, listener)
+    }
+
+    /// Adds `listener` under `name`. Names and descriptors must be unique.
+    pub fn add_unix(&mut self, name: &str, listener: &UnixListener) -> io::Result<()> {
+        self.add(name, Kind::Unix, listener)
+    }
+
+    /// Takes the TCP listener named `name` out of the set, restoring its
+    /// non-blocking and close-on-exec flags. Fails, leaving the descriptor
+    /// open, if it is not a listening TCP socket.
+    pub fn take_tcp(&mut self, name: &str) -> io::Result<Option<TcpListener>> {
+        match self.take(name, Kind::Tcp) {
+            Some(fd) => adopt(unsafe { TcpListener::from_raw_fd(fd) }, Kind::Tcp).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Takes the Unix listener named `name` out of the set, restoring its
+    /// non-blocking and close-on-exec flags. Fails, leaving the descriptor
+    /// open, if it is not a listening Unix stream socket.
+    pub fn take_unix(&mut self, name: &str) -> io::Result<Option<UnixListener>> {
+        match self.take(name, Kind::Unix) {
+            Some(fd) => adopt(unsafe { UnixListener::from_raw_fd(fd) }, Kind::Unix).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Clears the close-on-exec flag of every descriptor in the set so that
+    /// they survive exec.
+    ///
+    /// Meant to run in the child between fork and exec, e.g. from
+    /// `CommandExt::before_exec`; it only calls `fcntl` and does not
+    /// allocate. Called in the parent instead, every process it spawns from
+    /// then on inherits the listeners.
+    pub fn inherit(&self) -> io::Result<()> {
+        for e in &self.entries {
+            let flags = unsafe { ::libc::fcntl(e.fd, ::libc::F_GETFD) };
+
+            if flags == -1 || unsafe { ::libc::fcntl(e.fd, ::libc::F_SETFD, flags & !::libc::FD_CLOEXEC) } == -1 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The descriptors in the set, in the order they were added
+    pub fn fds(&self) -> Vec<RawFd> {
+        self.entries.iter().map(|e| e.fd).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Encodes the set as `name=kind:fd` pairs separated by `;`
+    pub fn encode(&self) -> String {
+        let entries: Vec<String> = self.entries.iter()
+            .map(|e| {
+                let kind = match e.kind {
+                    Kind::Tcp => "tcp",
+                    Kind::Unix => "unix",
+                };
+
+                format!("{}={}:{}", e.name, kind, e.fd)
+            })
+            .collect();
+
+        entries.join(";")
+    }
+
+    pub fn decode(s: &str) -> io::Result<Listeners> {
+        let mut listeners = Listeners::new();
+
+        for entry in s.split(';').filter(|e| !e.is_empty()) {
+            let mut parts = entry.splitn(2, '=');
+            let name = parts.next().unwrap();
+            let mut val = try!(parts.next().ok_or_else(invalid)).splitn(2, ':');
+
+            let kind = match val.next() {
+                Some("tcp") => Kind::Tcp,
+                Some("unix") => Kind::Unix,
+                _ => return Err(invalid()),
+            };
+
+            let fd = try!(val.next().and_then(|fd| fd.parse().ok()).ok_or_else(invalid));
+
+            if listeners.contains(name, fd) {
+                return Err(invalid());
+            }
+
+            listeners.entries.push(Entry {
+                name: name.to_string(),
+                kind: kind,
+                fd: fd,
+            });
+        }
+
+        Ok(listeners)
+    }
+
+    /// Decodes the set passed in `LISTENERS_ENV`, which is empty if the
+    /// variable is not set
+    pub fn from_env() -> io::Result<Listeners> {
+        match env::var(LISTENERS_ENV) {
+            Ok(val) => Listeners::decode(&val),
+            Err(_) => Ok(Listeners::new()),
+        }
+    }
+
+    fn add<S: Socket>(&mut self, name: &str, kind: Kind, sock: &S) -> io::Result<()> {
+        if name.is_empty() || name.contains(|c: char| c == '=' || c == ';') {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "listener names must not be empty or contain '=' or ';'"));
+        }
+
+        if self.contains(name, sock.as_raw_fd()) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "listener name or descriptor already in the set"));
+        }
+
+        self.entries.push(Entry {
+            name: name.to_string(),
+            kind: kind,
+            fd: sock.as_raw_fd(),
+        });
+
+        Ok(())
+    }
+
+    fn contains(&self, name: &str, fd: RawFd) -> bool {
+        self.entries.iter().any(|e| e.name == name || e.fd == fd)
+    }
+
+    fn take(&mut self, name: &str, kind: Kind) -> Option<RawFd> {
+        let pos = match self.entries.iter().position(|e| e.name == name && e.kind == kind) {
+            Some(pos) => pos,
+            None => return None,
+        };
+
+        Some(self.entries.remove(pos).fd)
+    }
+}
+
+fn adopt<S: Socket + IntoRawFd>(sock: S, kind: Kind) -> io::Result<S> {
+    if let Err(err) = check(&sock, kind) {
+        // Not a listener of ours, so the descriptor is not ours to close
+        let _ = sock.into_raw_fd();
+        return Err(err);
+    }
+
+    try!(sock.set_nonblocking(true));
+    try!(sock.set_cloexec(true));
+    Ok(sock)
+}
+
+// Checks that an inherited descriptor is a listening stream socket of the
+// expected family before it is used as one
+fn check<S: Socket>(sock: &S, kind: Kind) -> io::Result<()> {
+    let domain = try!(sock.domain());
+
+    let family = match kind {
+        Kind::Tcp => domain == ::libc::AF_INET || domain == ::libc::AF_INET6,
+        Kind::Unix => domain == ::libc::AF_UNIX,
+    };
+
+    if !family || try!(sock.socket_type()) != ::libc::SOCK_STREAM || !try!(sock.is_listening()) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "inherited descriptor is not a listener of the expected kind"));
+    }
+
+    Ok(())
+}
+
+fn invalid() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "malformed listener set")
+}
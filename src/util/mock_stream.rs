@@ -0,0 +1,155 @@
+use {Evented, EventSet, PollOpt, Selector, Token};
+use std::cell::Cell;
+use std::cmp;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+
+/// A scripted stand-in for a non-blocking stream, for unit testing handler
+/// logic without sockets or timing.
+///
+/// Reads and writes play back outcomes queued with the `push_*` functions,
+/// one per call; once a script runs dry the call fails with `WouldBlock`.
+/// Registering the stream only records the registration, so readiness is
+/// delivered by queuing events with `Poll::inject`.
+#[derive(Debug)]
+pub struct MockStream {
+    reads: VecDeque<ReadOp>,
+    writes: VecDeque<WriteOp>,
+    written: Vec<u8>,
+    registration: Cell<Option<(Token, EventSet, PollOpt)>>,
+}
+
+#[derive(Debug)]
+enum ReadOp {
+    Data(Vec<u8>),
+    Eof,
+    Err(io::ErrorKind),
+}
+
+#[derive(Debug)]
+enum WriteOp {
+    Accept(usize),
+    Err(io::ErrorKind),
+}
+
+impl MockStream {
+    pub fn new() -> MockStream {
+        MockStream {
+            reads: VecDeque::new(),
+            writes: VecDeque::new(),
+            written: Vec::new(),
+            registration: Cell::new(None),
+        }
+    }
+
+    /// Makes a read return `data`. A read into a smaller buffer leaves the
+    /// rest for the next one.
+    pub fn push_read(&mut self, data: &[u8]) {
+        self.reads.push_back(ReadOp::Data(data.to_vec()));
+    }
+
+    /// Makes reads return end of stream from this point on
+    pub fn push_read_eof(&mut self) {
+        self.reads.push_back(ReadOp::Eof);
+    }
+
+    /// Makes a read fail with `kind`
+    pub fn push_read_err(&mut self, kind: io::ErrorKind) {
+        self.reads.push_back(ReadOp::Err(kind));
+    }
+
+    /// Makes a write accept up to `limit` bytes
+    pub fn push_write(&mut self, limit: usize) {
+        self.writes.push_back(WriteOp::Accept(limit));
+    }
+
+    /// Makes a write fail with `kind`
+    pub fn push_write_err(&mut self, kind: io::ErrorKind) {
+        self.writes.push_back(WriteOp::Err(kind));
+    }
+
+    /// Everything written so far
+    pub fn written(&self) -> &[u8] {
+        &self.written
+    }
+
+    /// Token, interest and options of the current registration, if any
+    pub fn registration(&self) -> Option<(Token, EventSet, PollOpt)> {
+        self.registration.get()
+    }
+}
+
+impl Read for MockStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.reads.pop_front() {
+            Some(ReadOp::Data(mut data)) => {
+                let n = cmp::min(data.len(), buf.len());
+
+                for (dst, src) in buf.iter_mut().zip(data.drain(..n)) {
+                    *dst = src;
+                }
+
+                if !data.is_empty() {
+                    self.reads.push_front(ReadOp::Data(data));
+                }
+
+                Ok(n)
+            }
+            Some(ReadOp::Eof) => {
+                self.reads.push_front(ReadOp::Eof);
+                Ok(0)
+            }
+            Some(ReadOp::Err(kind)) => Err(io::Error::new(kind, "scripted read error")),
+            None => Err(io::Error::new(io::ErrorKind::WouldBlock, "no scripted read")),
+        }
+    }
+}
+
+impl Write for MockStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.writes.pop_front() {
+            Some(WriteOp::Accept(limit)) => {
+                let n = cmp::min(limit, buf.len());
+                self.written.extend(buf[..n].iter().cloned());
+                Ok(n)
+            }
+            Some(WriteOp::Err(kind)) => Err(io::Error::new(kind, "scripted write error")),
+            None => Err(io::Error::new(io::ErrorKind::WouldBlock, "no scripted write")),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Mirrors the selectors' errors for registering twice or modifying an
+// unknown registration
+impl Evented for MockStream {
+    fn register(&self, _: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        if self.registration.get().is_some() {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "already registered"));
+        }
+
+        self.registration.set(Some((token, interest, opts)));
+        Ok(())
+    }
+
+    fn reregister(&self, _: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        if self.registration.get().is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "not registered"));
+        }
+
+        self.registration.set(Some((token, interest, opts)));
+        Ok(())
+    }
+
+    fn deregister(&self, _: &mut Selector) -> io::Result<()> {
+        if self.registration.get().is_none() {
+            return Err(io::Error::new(io::ErrorKind::NotFound, "not registered"));
+        }
+
+        self.registration.set(None);
+        Ok(())
+    }
+}
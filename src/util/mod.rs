@@ -1,7 +1,33 @@
 //! Utilities for non-blocking IO programs
 
+pub use self::buf_list::BufList;
+pub use self::buf_pool::{BufPool, PooledBuf};
+pub use self::channel::{channel, Receiver, Sender, Waker};
+pub use self::listeners::{Listeners, LISTENERS_ENV};
+#[cfg(feature = "testing")]
+pub use self::mock_stream::MockStream;
 pub use self::mpmc_bounded_queue::Queue as BoundedQueue;
+pub use self::pump::{Pump, PumpStatus};
+pub use self::resolver::{Resolve, Resolver, SystemResolve};
+pub use self::take::Take;
+pub use self::timed_io::TimedIo;
+pub use self::token_allocator::TokenAllocator;
+pub use self::window::Window;
+pub use self::write_queue::WriteQueue;
 
+mod buf_list;
+mod buf_pool;
+mod channel;
+mod listeners;
+#[cfg(feature = "testing")]
+mod mock_stream;
 mod mpmc_bounded_queue;
+mod pump;
+mod resolver;
+mod take;
+mod timed_io;
+mod token_allocator;
+mod window;
+mod write_queue;
 
 pub type Slab<T> = ::slab::Slab<T, ::Token>;
@@ -0,0 +1,160 @@
+use {EventSet, MapNonBlock, TryRead, TryWrite};
+use std::io::{self, Read, Write};
+
+/// Shuttles bytes in both directions between two non-blocking streams, as a
+/// proxy does.
+///
+/// Call `pump` whenever either stream receives an event; it moves as much
+/// data as possible until every path would block. `interest_a` and
+/// `interest_b` then return the events to (re)register each stream for.
+///
+/// When one side reaches end of stream and everything it sent has been
+/// delivered, `pump` reports it once through `PumpStatus` so the write half
+/// of the other stream can be shut down.
+#[derive(Debug)]
+pub struct Pump {
+    // a -> b
+    up: Channel,
+    // b -> a
+    down: Channel,
+}
+
+/// Shutdown transitions observed by a call to `Pump::pump`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PumpStatus {
+    /// `a` reached end of stream and all of its data was written to `b`
+    pub a_closed: bool,
+    /// `b` reached end of stream and all of its data was written to `a`
+    pub b_closed: bool,
+}
+
+impl Pump {
+    /// Returns a new `Pump` buffering up to `buf_size` bytes per direction
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buf_size` is zero, as reads into an empty buffer would be
+    /// taken for end of stream.
+    pub fn new(buf_size: usize) -> Pump {
+        assert!(buf_size > 0, "pump buffer size must be non-zero");
+
+        Pump {
+            up: Channel::new(buf_size),
+            down: Channel::new(buf_size),
+        }
+    }
+
+    /// Moves data between `a` and `b` until both directions would block
+    pub fn pump<A, B>(&mut self, a: &mut A, b: &mut B) -> io::Result<PumpStatus>
+        where A: Read + Write, B: Read + Write
+    {
+        Ok(PumpStatus {
+            a_closed: try!(self.up.transfer(a, b)),
+            b_closed: try!(self.down.transfer(b, a)),
+        })
+    }
+
+    /// Events that `a` should currently be registered for
+    pub fn interest_a(&self) -> EventSet {
+        interest(&self.up, &self.down)
+    }
+
+    /// Events that `b` should currently be registered for
+    pub fn interest_b(&self) -> EventSet {
+        interest(&self.down, &self.up)
+    }
+
+    /// Bytes read from `a` and not yet written to `b`
+    pub fn pending_a(&self) -> usize {
+        self.up.pending()
+    }
+
+    /// Bytes read from `b` and not yet written to `a`
+    pub fn pending_b(&self) -> usize {
+        self.down.pending()
+    }
+
+    /// Returns true once both directions have been closed and flushed
+    pub fn is_done(&self) -> bool {
+        self.up.closed && self.down.closed
+    }
+}
+
+// `src` is the channel the stream reads into, `dst` the one it drains
+fn interest(src: &Channel, dst: &Channel) -> EventSet {
+    let mut interest = EventSet::none();
+
+    if src.wants_read() {
+        interest.insert(EventSet::readable());
+    }
+
+    if dst.pending() > 0 {
+        interest.insert(EventSet::writable());
+    }
+
+    interest
+}
+
+#[derive(Debug)]
+struct Channel {
+    buf: Vec<u8>,
+    pos: usize,
+    len: usize,
+    eof: bool,
+    closed: bool,
+}
+
+impl Channel {
+    fn new(size: usize) -> Channel {
+        Channel {
+            buf: vec![0; size],
+            pos: 0,
+            len: 0,
+            eof: false,
+            closed: false,
+        }
+    }
+
+    fn pending(&self) -> usize {
+        self.len - self.pos
+    }
+
+    fn wants_read(&self) -> bool {
+        !self.eof && self.pending() == 0
+    }
+
+    // Returns true the first time the channel is found closed and flushed
+    fn transfer<R: Read, W: Write>(&mut self, src: &mut R, dst: &mut W) -> io::Result<bool> {
+        loop {
+            while self.pos < self.len {
+                match try!(dst.try_write(&self.buf[self.pos..self.len]).map_non_block()) {
+                    Some(0) => {
+                        return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write buffered data"));
+                    }
+                    Some(cnt) => self.pos += cnt,
+                    None => return Ok(false),
+                }
+            }
+
+            self.pos = 0;
+            self.len = 0;
+
+            if self.eof {
+                break;
+            }
+
+            match try!(src.try_read(&mut self.buf).map_non_block()) {
+                Some(0) => self.eof = true,
+                Some(cnt) => self.len = cnt,
+                None => return Ok(false),
+            }
+        }
+
+        if self.closed {
+            return Ok(false);
+        }
+
+        self.closed = true;
+        Ok(true)
+    }
+}
@@ -0,0 +1,114 @@
+use {NotifyError, Sender};
+use std::{fmt, io, mem, thread};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::{mpsc, Arc, Mutex};
+
+/// A source of host name resolutions. Implementations may block; they are
+/// only called from the `Resolver`'s worker threads.
+pub trait Resolve: Send + Sync {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>>;
+}
+
+/// Resolves host names with the system resolver (`getaddrinfo`)
+#[derive(Debug, Clone, Copy)]
+pub struct SystemResolve;
+
+impl Resolve for SystemResolve {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        (host, port).to_socket_addrs().map(|addrs| addrs.collect())
+    }
+}
+
+/// Resolves host names on a set of worker threads, delivering each result
+/// to an event loop as a message so that the loop itself never blocks.
+///
+/// The resolution itself is delegated to a `Resolve` implementation, the
+/// system resolver by default; an asynchronous DNS client can be plugged
+/// in the same way. Dropping the resolver waits for the resolutions
+/// already queued.
+pub struct Resolver {
+    jobs: Option<mpsc::Sender<Job>>,
+    threads: Vec<thread::JoinHandle<()>>,
+}
+
+struct Job {
+    host: String,
+    port: u16,
+    deliver: Box<FnMut(io::Result<Vec<SocketAddr>>) + Send>,
+}
+
+impl Resolver {
+    /// Returns a resolver using the system resolver on `threads` threads
+    pub fn new(threads: usize) -> Resolver {
+        Resolver::with_backend(threads, SystemResolve)
+    }
+
+    pub fn with_backend<R: Resolve + 'static>(threads: usize, backend: R) -> Resolver {
+        let backend = Arc::new(backend);
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+
+        let threads = (0..threads).map(|_| {
+            let backend = backend.clone();
+            let rx = rx.clone();
+
+            thread::spawn(move || {
+                loop {
+                    // Only hold the lock while waiting for a job
+                    let job = rx.lock().unwrap().recv();
+
+                    let mut job = match job {
+                        Ok(job) => job,
+                        Err(_) => return,
+                    };
+
+                    let res = backend.resolve(&job.host, job.port);
+                    (job.deliver)(res);
+                }
+            })
+        }).collect();
+
+        Resolver {
+            jobs: Some(tx),
+            threads: threads,
+        }
+    }
+
+    /// Resolves `host` in the background. Once done, the result is turned
+    /// into a message by `msg` and sent through `sender`, which is usually
+    /// obtained from `EventLoop::channel`.
+    pub fn resolve<M, F>(&self, host: &str, port: u16, sender: Sender<M>, msg: F) -> io::Result<()>
+        where M: Send + 'static,
+              F: Fn(io::Result<Vec<SocketAddr>>) -> M + Send + 'static,
+    {
+        let job = Job {
+            host: host.to_string(),
+            port: port,
+            deliver: Box::new(move |res| {
+                if let Err(NotifyError::Io(e)) = sender.send_blocking(msg(res)) {
+                    debug!("failed to deliver resolution; err={:?}", e);
+                }
+            }),
+        };
+
+        self.jobs.as_ref().unwrap().send(job)
+            .map_err(|_| io::Error::new(io::ErrorKind::Other, "resolver threads have exited"))
+    }
+}
+
+impl fmt::Debug for Resolver {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Resolver {{ threads: {} }}", self.threads.len())
+    }
+}
+
+impl Drop for Resolver {
+    fn drop(&mut self) {
+        // Closing the queue stops the workers once pending jobs are done
+        drop(self.jobs.take());
+
+        for thread in mem::replace(&mut self.threads, vec![]) {
+            let _ = thread.join();
+        }
+    }
+}
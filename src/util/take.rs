@@ -0,0 +1,82 @@
+use bytes::{Buf, MutBuf};
+use std::cmp;
+
+/// Limits a buffer to the next `limit` bytes without copying, e.g. to
+/// hand the body of a length prefixed frame to a parser.
+///
+/// Reads through `Buf` and writes through `MutBuf` both count against the
+/// same limit.
+#[derive(Debug)]
+pub struct Take<B> {
+    inner: B,
+    limit: usize,
+}
+
+impl<B> Take<B> {
+    pub fn new(inner: B, limit: usize) -> Take<B> {
+        Take {
+            inner: inner,
+            limit: limit,
+        }
+    }
+
+    /// The number of bytes that may still be transferred
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn get_ref(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B: Buf> Buf for Take<B> {
+    fn remaining(&self) -> usize {
+        cmp::min(self.inner.remaining(), self.limit)
+    }
+
+    fn bytes(&self) -> &[u8] {
+        let bytes = self.inner.bytes();
+        &bytes[..cmp::min(bytes.len(), self.limit)]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        let cnt = cmp::min(cnt, self.limit);
+
+        self.inner.advance(cnt);
+        self.limit -= cnt;
+    }
+}
+
+impl<B: MutBuf> MutBuf for Take<B> {
+    fn remaining(&self) -> usize {
+        cmp::min(self.inner.remaining(), self.limit)
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        let cnt = cmp::min(cnt, self.limit);
+
+        self.inner.advance(cnt);
+        self.limit -= cnt;
+    }
+
+    unsafe fn mut_bytes(&mut self) -> &mut [u8] {
+        let limit = self.limit;
+        let bytes = self.inner.mut_bytes();
+        let len = cmp::min(bytes.len(), limit);
+
+        &mut bytes[..len]
+    }
+}
@@ -0,0 +1,143 @@
+use {io, Clock, Evented, EventLoop, EventSet, Handler, MonotonicClock, PollOpt, Selector, Timeout, TimerResult, Token};
+use std::io::{Read, Write};
+use std::time::Duration;
+
+/// Wraps an IO handle with an idle deadline: "close the connection if
+/// nothing happened for N seconds".
+///
+/// Reads and writes that transfer data through the wrapper count as
+/// activity. Rather than resetting a timer on every operation, a single
+/// timeout is kept armed; when it fires, pass it to `expired`, which either
+/// reports the handle as idle or re-arms the timeout for the time remaining.
+#[derive(Debug)]
+pub struct TimedIo<T> {
+    io: T,
+    idle: Duration,
+    // Time of the last activity, in nanoseconds of `clock`
+    last_active: u64,
+    timeout: Option<Timeout>,
+    clock: Box<Clock>,
+}
+
+impl<T> TimedIo<T> {
+    pub fn new(io: T, idle: Duration) -> TimedIo<T> {
+        TimedIo::with_clock(io, idle, Box::new(MonotonicClock))
+    }
+
+    /// Returns a wrapper that reads the current time from `clock`, which
+    /// should be the one the event loop was created with, see
+    /// `EventLoop::configured_with_clock`
+    pub fn with_clock(io: T, idle: Duration, clock: Box<Clock>) -> TimedIo<T> {
+        TimedIo {
+            io: io,
+            idle: idle,
+            last_active: clock.now_ns(),
+            timeout: None,
+            clock: clock,
+        }
+    }
+
+    /// Starts the idle countdown. `token` is delivered to
+    /// `Handler::timeout` once the deadline passes.
+    pub fn arm<H: Handler>(&mut self, event_loop: &mut EventLoop<H>, token: H::Timeout) -> TimerResult<()> {
+        self.disarm(event_loop);
+
+        let remaining = if self.idle_for() < self.idle {
+            self.idle - self.idle_for()
+        } else {
+            Duration::from_millis(0)
+        };
+
+        let timeout = try!(event_loop.timeout(token, remaining));
+        self.timeout = Some(timeout);
+        Ok(())
+    }
+
+    /// Cancels the idle countdown
+    pub fn disarm<H: Handler>(&mut self, event_loop: &mut EventLoop<H>) {
+        if let Some(timeout) = self.timeout.take() {
+            event_loop.clear_timeout(timeout);
+        }
+    }
+
+    /// Handles the firing of the idle timeout. Returns true if the handle has
+    /// been idle for the full period; otherwise the timeout is re-armed with
+    /// `token` for the remaining time and false is returned.
+    pub fn expired<H: Handler>(&mut self, event_loop: &mut EventLoop<H>, token: H::Timeout) -> TimerResult<bool> {
+        self.timeout = None;
+
+        if self.idle_for() >= self.idle {
+            return Ok(true);
+        }
+
+        try!(self.arm(event_loop, token));
+        Ok(false)
+    }
+
+    /// Records activity that did not go through the wrapper
+    pub fn touch(&mut self) {
+        self.last_active = self.clock.now_ns();
+    }
+
+    /// Time since the last activity
+    pub fn idle_for(&self) -> Duration {
+        let ns = self.clock.now_ns().saturating_sub(self.last_active);
+        Duration::new(ns / 1_000_000_000, (ns % 1_000_000_000) as u32)
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns a mutable reference to the handle. Activity through it is not
+    /// tracked.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
+
+impl<T: Read> Read for TimedIo<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let cnt = try!(self.io.read(buf));
+
+        if cnt > 0 {
+            self.touch();
+        }
+
+        Ok(cnt)
+    }
+}
+
+impl<T: Write> Write for TimedIo<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let cnt = try!(self.io.write(buf));
+
+        if cnt > 0 {
+            self.touch();
+        }
+
+        Ok(cnt)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl<T: Evented> Evented for TimedIo<T> {
+    fn register(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.register(selector, token, interest, opts)
+    }
+
+    fn reregister(&self, selector: &mut Selector, token: Token, interest: EventSet, opts: PollOpt) -> io::Result<()> {
+        self.io.reregister(selector, token, interest, opts)
+    }
+
+    fn deregister(&self, selector: &mut Selector) -> io::Result<()> {
+        self.io.deregister(selector)
+    }
+}
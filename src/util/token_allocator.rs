@@ -0,0 +1,119 @@
+use Token;
+use std::usize;
+
+// Tokens are `generation << INDEX_BITS | index`
+#[cfg(target_pointer_width = "64")]
+const INDEX_BITS: usize = 32;
+
+#[cfg(target_pointer_width = "32")]
+const INDEX_BITS: usize = 20;
+
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+
+// The generation never has all of its bits set, which keeps allocated
+// tokens clear of the values at the top of the range reserved by the event
+// loop
+const MAX_GENERATION: usize = (usize::MAX >> INDEX_BITS) - 1;
+
+/// Hands out tokens tagged with a generation, so that events for a
+/// connection that has since been closed can be told apart from events for
+/// a new connection reusing its slot.
+///
+/// The low bits of a token hold a slot index, suitable for indexing
+/// per-connection storage, and the high bits hold the slot's generation,
+/// which is bumped every time the token is released. Events carrying a
+/// token for which `is_live` returns false are stale and may be dropped.
+#[derive(Debug)]
+pub struct TokenAllocator {
+    generations: Vec<usize>,
+    live: Vec<bool>,
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl TokenAllocator {
+    pub fn new() -> TokenAllocator {
+        TokenAllocator::with_capacity(0)
+    }
+
+    /// Returns an allocator with room for `capacity` slots before it needs
+    /// to grow
+    pub fn with_capacity(capacity: usize) -> TokenAllocator {
+        TokenAllocator {
+            generations: Vec::with_capacity(capacity),
+            live: Vec::with_capacity(capacity),
+            free: Vec::with_capacity(capacity),
+            len: 0,
+        }
+    }
+
+    /// Returns a new live token, reusing a released slot if there is one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every slot that fits in the index bits is in use.
+    pub fn allocate(&mut self) -> Token {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                let index = self.generations.len();
+                assert!(index <= INDEX_MASK, "token index space exhausted");
+
+                self.generations.push(0);
+                self.live.push(false);
+                index
+            }
+        };
+
+        self.live[index] = true;
+        self.len += 1;
+
+        Token(self.generations[index] << INDEX_BITS | index)
+    }
+
+    /// Releases `token`, making it and any events still carrying it stale.
+    /// Returns false if the token was not live.
+    pub fn release(&mut self, token: Token) -> bool {
+        if !self.is_live(token) {
+            return false;
+        }
+
+        let index = TokenAllocator::index(token);
+        let generation = self.generations[index];
+
+        self.generations[index] = if generation == MAX_GENERATION { 0 } else { generation + 1 };
+        self.live[index] = false;
+        self.free.push(index);
+        self.len -= 1;
+
+        true
+    }
+
+    /// Returns true if `token` was allocated and has not been released since
+    pub fn is_live(&self, token: Token) -> bool {
+        let index = TokenAllocator::index(token);
+
+        index < self.live.len() &&
+            self.live[index] &&
+            self.generations[index] == TokenAllocator::generation(token)
+    }
+
+    /// The slot index of `token`
+    pub fn index(token: Token) -> usize {
+        token.as_usize() & INDEX_MASK
+    }
+
+    /// The generation of `token`
+    pub fn generation(token: Token) -> usize {
+        token.as_usize() >> INDEX_BITS
+    }
+
+    /// The number of live tokens
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
@@ -0,0 +1,61 @@
+use bytes::Buf;
+use std::cmp;
+
+/// A `Buf` reading a range of bytes owned by `T`, such as one frame out of
+/// a larger shared allocation, without copying it.
+#[derive(Debug)]
+pub struct Window<T> {
+    inner: T,
+    pos: usize,
+    end: usize,
+}
+
+impl<T: AsRef<[u8]>> Window<T> {
+    /// Returns a window over `inner[start..end]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds.
+    pub fn new(inner: T, start: usize, end: usize) -> Window<T> {
+        assert!(start <= end && end <= inner.as_ref().len(), "window out of bounds");
+
+        Window {
+            inner: inner,
+            pos: start,
+            end: end,
+        }
+    }
+
+    /// Returns a window over all of `inner`
+    pub fn full(inner: T) -> Window<T> {
+        let end = inner.as_ref().len();
+        Window::new(inner, 0, end)
+    }
+
+    /// The offset in `inner` of the next byte to be read
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: AsRef<[u8]>> Buf for Window<T> {
+    fn remaining(&self) -> usize {
+        self.end - self.pos
+    }
+
+    fn bytes(&self) -> &[u8] {
+        &self.inner.as_ref()[self.pos..self.end]
+    }
+
+    fn advance(&mut self, cnt: usize) {
+        self.pos += cmp::min(cnt, self.remaining());
+    }
+}
@@ -0,0 +1,75 @@
+use {EventSet, MapNonBlock, TryWrite};
+use bytes::Buf;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// A queue of outgoing buffers for a non-blocking stream.
+///
+/// Writes that would block leave the unsent tail queued; call `flush` again
+/// on the next writable event. `flush` returns true once everything has been
+/// written, at which point writable interest can be dropped.
+#[derive(Debug)]
+pub struct WriteQueue<B: Buf> {
+    bufs: VecDeque<B>,
+}
+
+impl<B: Buf> WriteQueue<B> {
+    pub fn new() -> WriteQueue<B> {
+        WriteQueue { bufs: VecDeque::new() }
+    }
+
+    /// Queues `buf` behind any data already waiting to be written
+    pub fn push(&mut self, buf: B) {
+        if buf.remaining() > 0 {
+            self.bufs.push_back(buf);
+        }
+    }
+
+    /// Writes queued data to `dst` until the queue drains or `dst` would
+    /// block. Returns true if the queue is now empty.
+    pub fn flush<W: Write>(&mut self, dst: &mut W) -> io::Result<bool> {
+        loop {
+            let done = match self.bufs.front_mut() {
+                Some(buf) => {
+                    match try!(dst.try_write_buf(buf).map_non_block()) {
+                        Some(0) => {
+                            return Err(io::Error::new(io::ErrorKind::WriteZero, "failed to write queued data"));
+                        }
+                        Some(_) => buf.remaining() == 0,
+                        None => return Ok(false),
+                    }
+                }
+                None => return Ok(true),
+            };
+
+            if done {
+                self.bufs.pop_front();
+            }
+        }
+    }
+
+    /// Events the stream should be registered for on account of the queue:
+    /// writable while data is pending, none otherwise
+    pub fn interest(&self) -> EventSet {
+        if self.is_empty() {
+            EventSet::none()
+        } else {
+            EventSet::writable()
+        }
+    }
+
+    /// Returns true if no data is waiting to be written
+    pub fn is_empty(&self) -> bool {
+        self.bufs.is_empty()
+    }
+
+    /// Number of buffers waiting to be written
+    pub fn len(&self) -> usize {
+        self.bufs.len()
+    }
+
+    /// Number of bytes waiting to be written
+    pub fn remaining(&self) -> usize {
+        self.bufs.iter().map(|buf| buf.remaining()).fold(0, |acc, n| acc + n)
+    }
+}
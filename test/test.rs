@@ -1,5 +1,6 @@
 extern crate mio;
 extern crate bytes;
+extern crate libc;
 
 #[macro_use]
 extern crate log;
@@ -8,15 +9,63 @@ extern crate tempdir;
 
 pub use ports::localhost;
 
+use mio::tcp::{TcpListener, TcpStream};
+
 mod test_battery;
+mod test_buf_adapters;
+mod test_buf_pool;
+mod test_channel;
 mod test_close_on_drop;
 mod test_echo_server;
+mod test_event_loop_pool;
+#[cfg(feature = "testing")]
+mod test_faults;
+mod test_listeners;
+#[cfg(feature = "testing")]
+mod test_mock_stream;
+#[cfg(target_os = "linux")]
+mod test_mq;
 mod test_multicast;
+#[cfg(target_os = "linux")]
+mod test_netlink;
 mod test_notify;
+#[cfg(feature = "testing")]
+mod test_pump;
+mod test_record_replay;
 mod test_register_deregister;
+mod test_resolver;
+#[cfg(target_os = "linux")]
+mod test_sctp;
+mod test_send_recv_msg;
+mod test_sharded_poll;
+mod test_socket_opts;
+#[cfg(target_os = "linux")]
+mod test_splice;
+mod test_std_io;
+mod test_tcp;
+mod test_timed_io;
 mod test_timer;
+mod test_token_allocator;
+#[cfg(any(target_os = "linux",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+mod test_tun;
 mod test_udp_socket;
 mod test_unix_echo_server;
+mod test_vectored;
+#[cfg(target_os = "linux")]
+mod test_vsock;
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly",
+          target_os = "netbsd",
+          target_os = "openbsd"))]
+mod test_watch;
+#[cfg(feature = "testing")]
+mod test_write_queue;
 
 mod ports {
     use std::net::SocketAddr;
@@ -49,3 +98,18 @@ pub fn sleep_ms(ms: usize) {
     use std::thread;
     thread::sleep_ms(ms as u32);
 }
+
+// Returns a connected (client, server) pair
+pub fn tcp_pair() -> (TcpStream, TcpStream) {
+    let addr = localhost();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let client = TcpStream::connect(&addr).unwrap();
+
+    loop {
+        if let Some(server) = listener.accept().unwrap() {
+            return (client, server);
+        }
+
+        sleep_ms(10);
+    }
+}
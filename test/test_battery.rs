@@ -5,6 +5,7 @@ use mio::util::Slab;
 use super::localhost;
 use std::collections::LinkedList;
 use std::{io, thread};
+use std::time::Duration;
 
 const SERVER: Token = Token(0);
 const CLIENT: Token = Token(1);
@@ -35,7 +36,7 @@ impl EchoConn {
 
     fn readable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         loop {
-            match self.sock.try_read(&mut self.buf[..]) {
+            match self.sock.try_read(&mut self.buf[..]).map_non_block() {
                 Ok(None) => {
                     break;
                 }
@@ -124,7 +125,7 @@ impl EchoClient {
         debug!("client socket writable");
 
         while self.backlog.len() > 0 {
-            match self.sock.try_write(self.backlog.front().unwrap().as_bytes()) {
+            match self.sock.try_write(self.backlog.front().unwrap().as_bytes()).map_non_block() {
                 Ok(None) => {
                     break;
                 }
@@ -186,7 +187,7 @@ impl Handler for Echo {
     }
 
     fn notify(&mut self, event_loop: &mut EventLoop<Echo>, msg: String) {
-        match self.client.sock.try_write(msg.as_bytes()) {
+        match self.client.sock.try_write(msg.as_bytes()).map_non_block() {
             Ok(Some(n)) => {
                 self.client.count += 1;
                 if self.client.count % 10000 == 0 {
@@ -212,11 +213,17 @@ pub fn test_echo_server() {
     let config =
         EventLoopConfig {
             io_poll_timeout_ms: 1_000,
+            io_events_capacity: 1_024,
+            io_poll_spin: None,
+            io_dispatch_budget: None,
+            io_dispatch_per_token: None,
             notify_capacity: 1_048_576,
             messages_per_tick: 64,
-            timer_tick_ms: 100,
+            timer_tick: Duration::from_millis(100),
             timer_wheel_size: 1_024,
             timer_capacity: 65_536,
+            timer_precise: false,
+            catch_panics: false,
         };
     let mut event_loop = EventLoop::configured(config).unwrap();
 
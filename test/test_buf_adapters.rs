@@ -0,0 +1,49 @@
+use mio::TryRead;
+use mio::util::{Take, Window};
+use bytes::{Buf, ByteBuf, SliceBuf};
+
+#[test]
+pub fn test_take_limits_reads() {
+    let mut buf = Take::new(SliceBuf::wrap(b"framebody"), 5);
+
+    assert_eq!(buf.remaining(), 5);
+    assert_eq!(buf.bytes(), b"frame");
+
+    buf.advance(3);
+    assert_eq!(buf.bytes(), b"me");
+
+    buf.advance(10);
+    assert_eq!(buf.limit(), 0);
+    assert!(!buf.has_remaining());
+
+    let rest = buf.into_inner();
+    assert_eq!(rest.bytes(), b"body");
+}
+
+#[test]
+pub fn test_take_limits_writes() {
+    let mut src: &[u8] = b"hello world";
+    let mut dst = Take::new(ByteBuf::mut_with_capacity(64), 5);
+
+    assert_eq!(src.try_read_buf(&mut dst).unwrap(), 5);
+    assert_eq!(dst.limit(), 0);
+    assert_eq!(dst.into_inner().flip().bytes(), b"hello");
+}
+
+#[test]
+pub fn test_window() {
+    let frames = vec![1u8, 2, 3, 4, 5, 6];
+
+    let mut buf = Window::new(frames, 2, 5);
+    assert_eq!(buf.bytes(), &[3, 4, 5]);
+
+    buf.advance(2);
+    assert_eq!(buf.position(), 4);
+    assert_eq!(buf.bytes(), &[5]);
+
+    buf.advance(2);
+    assert!(!buf.has_remaining());
+
+    let buf = Window::full(buf.into_inner());
+    assert_eq!(buf.remaining(), 6);
+}
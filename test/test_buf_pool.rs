@@ -0,0 +1,79 @@
+use mio::{TryRead, TryWrite};
+use mio::util::BufPool;
+use bytes::{Buf, MutBuf};
+
+#[test]
+pub fn test_buf_pool_recycles() {
+    let pool = BufPool::new(16, 2);
+    pool.reserve(4);
+    assert_eq!(pool.idle(), 2);
+
+    let a = pool.checkout();
+    let b = pool.checkout();
+    let c = pool.checkout();
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(c.capacity(), 16);
+
+    drop(a);
+    drop(b);
+    drop(c);
+
+    // Only `max_idle` buffers are kept
+    assert_eq!(pool.idle(), 2);
+}
+
+#[test]
+pub fn test_pooled_buf_read_write() {
+    let pool = BufPool::new(8, 1);
+    let mut buf = pool.checkout();
+
+    let mut src: &[u8] = b"hello world";
+    assert_eq!(src.try_read_buf(&mut buf).unwrap(), 8);
+    assert_eq!(MutBuf::remaining(&buf), 0);
+    assert_eq!(buf.bytes(), b"hello wo");
+
+    let mut dst = vec![];
+    assert_eq!(dst.try_write_buf(&mut buf).unwrap(), 8);
+    assert_eq!(&dst[..], b"hello wo");
+    assert_eq!(Buf::remaining(&buf), 0);
+
+    // Fully consumed buffers start over
+    assert_eq!(MutBuf::remaining(&buf), 8);
+
+    drop(buf);
+
+    let buf = pool.checkout();
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(Buf::remaining(&buf), 0);
+}
+
+#[test]
+pub fn test_pooled_buf_mark_reset_compact() {
+    let pool = BufPool::new(8, 1);
+    let mut buf = pool.checkout();
+
+    let mut src: &[u8] = b"abcdef";
+    assert_eq!(src.try_read_buf(&mut buf).unwrap(), 6);
+
+    Buf::advance(&mut buf, 2);
+    buf.mark();
+    Buf::advance(&mut buf, 4);
+    assert_eq!(Buf::remaining(&buf), 0);
+
+    // The mark keeps the consumed frame around
+    assert!(buf.reset());
+    assert_eq!(buf.bytes(), b"cdef");
+    assert!(!buf.reset());
+
+    buf.mark();
+    Buf::advance(&mut buf, 1);
+    buf.compact();
+    assert_eq!(MutBuf::remaining(&buf), 4);
+    assert_eq!(buf.bytes(), b"def");
+
+    assert!(buf.reset());
+    assert_eq!(buf.bytes(), b"cdef");
+
+    buf.compact();
+    assert_eq!(MutBuf::remaining(&buf), 4);
+}
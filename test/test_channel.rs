@@ -0,0 +1,72 @@
+use mio::*;
+use mio::util::{self, Receiver};
+use std::sync::mpsc;
+use std::thread;
+
+const RX: Token = Token(0);
+
+struct ChannelHandler {
+    rx: Receiver<usize>,
+    received: Vec<usize>,
+}
+
+impl Handler for ChannelHandler {
+    type Timeout = ();
+    type Message = ();
+
+    fn ready(&mut self, event_loop: &mut EventLoop<ChannelHandler>, token: Token, events: EventSet) {
+        assert_eq!(token, RX);
+        assert!(events.is_readable());
+
+        while let Ok(n) = self.rx.try_recv() {
+            self.received.push(n);
+        }
+
+        if self.received.len() == 10 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+#[test]
+pub fn test_channel() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let (tx, rx) = util::channel().unwrap();
+
+    event_loop.register_opt(&rx, RX, EventSet::readable(), PollOpt::level()).unwrap();
+
+    let th = thread::spawn(move || {
+        for i in 0..10 {
+            tx.send(i).unwrap();
+        }
+    });
+
+    let mut handler = ChannelHandler { rx: rx, received: vec![] };
+    event_loop.run(&mut handler).unwrap();
+    th.join().unwrap();
+
+    assert_eq!(handler.received, (0..10).collect::<Vec<usize>>());
+}
+
+#[test]
+pub fn test_existing_std_channel() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let (tx, rx) = mpsc::channel();
+    let rx = Receiver::new(rx).unwrap();
+    let waker = rx.waker();
+
+    event_loop.register_opt(&rx, RX, EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let th = thread::spawn(move || {
+        for i in 0..10 {
+            tx.send(i).unwrap();
+            waker.wake().unwrap();
+        }
+    });
+
+    let mut handler = ChannelHandler { rx: rx, received: vec![] };
+    event_loop.run(&mut handler).unwrap();
+    th.join().unwrap();
+
+    assert_eq!(handler.received, (0..10).collect::<Vec<usize>>());
+}
@@ -62,7 +62,7 @@ impl TestHandler {
 
                 let mut buf = ByteBuf::mut_with_capacity(1024);
 
-                match self.cli.try_read_buf(&mut buf) {
+                match self.cli.try_read_buf(&mut buf).map_non_block() {
                     Ok(Some(0)) => event_loop.shutdown(),
                     _ => panic!("the client socket should not be readable")
                 }
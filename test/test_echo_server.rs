@@ -30,7 +30,7 @@ impl EchoConn {
     fn writable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         let mut buf = self.buf.take().unwrap();
 
-        match self.sock.try_write_buf(&mut buf) {
+        match self.sock.try_write_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 debug!("client flushing buf; WOULDBLOCK");
 
@@ -54,7 +54,7 @@ impl EchoConn {
     fn readable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         let mut buf = self.mut_buf.take().unwrap();
 
-        match self.sock.try_read_buf(&mut buf) {
+        match self.sock.try_read_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 panic!("We just got readable, but were unable to read from the socket?");
             }
@@ -145,7 +145,7 @@ impl EchoClient {
 
         let mut buf = self.mut_buf.take().unwrap();
 
-        match self.sock.try_read_buf(&mut buf) {
+        match self.sock.try_read_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 panic!("We just got readable, but were unable to read from the socket?");
             }
@@ -181,7 +181,7 @@ impl EchoClient {
     fn writable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         debug!("client socket writable");
 
-        match self.sock.try_write_buf(&mut self.tx) {
+        match self.sock.try_write_buf(&mut self.tx).map_non_block() {
             Ok(None) => {
                 debug!("client flushing buf; WOULDBLOCK");
                 self.interest.insert(EventSet::writable());
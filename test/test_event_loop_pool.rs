@@ -0,0 +1,67 @@
+use mio::*;
+use std::sync::mpsc;
+use std::sync::Mutex;
+
+struct Worker {
+    id: usize,
+    tx: mpsc::Sender<(usize, u32)>,
+}
+
+impl Handler for Worker {
+    type Timeout = ();
+    type Message = u32;
+
+    fn notify(&mut self, _: &mut EventLoop<Worker>, msg: u32) {
+        self.tx.send((self.id, msg)).unwrap();
+    }
+}
+
+#[test]
+pub fn test_event_loop_pool_round_robin() {
+    let (tx, rx) = mpsc::channel();
+    // `mpsc::Sender` is not `Sync`, which the handler factory must be
+    let tx = Mutex::new(tx);
+
+    let pool = EventLoopPool::new(2, EventLoopConfig::default(), move |id, _| {
+        Ok(Worker { id: id, tx: tx.lock().unwrap().clone() })
+    }).unwrap();
+
+    assert_eq!(pool.len(), 2);
+
+    for i in 0..4 {
+        assert_eq!(pool.send(i).unwrap(), i as usize % 2);
+    }
+
+    pool.send_to(1, 10).unwrap();
+
+    let mut received: Vec<(usize, u32)> = (0..5).map(|_| rx.recv().unwrap()).collect();
+    received.sort();
+
+    assert_eq!(received, vec![(0, 0), (0, 2), (1, 1), (1, 3), (1, 10)]);
+
+    pool.join().unwrap();
+}
+
+#[test]
+pub fn test_event_loop_pool_empty() {
+    let res = EventLoopPool::<u32>::new(0, EventLoopConfig::default(), |_, _| {
+        Ok(Worker { id: 0, tx: mpsc::channel().0 })
+    });
+
+    assert!(res.is_err());
+}
+
+#[test]
+pub fn test_event_loop_pool_drop_joins() {
+    let (tx, rx) = mpsc::channel();
+    let tx = Mutex::new(tx);
+
+    let pool = EventLoopPool::new(2, EventLoopConfig::default(), move |id, _| {
+        Ok(Worker { id: id, tx: tx.lock().unwrap().clone() })
+    }).unwrap();
+
+    drop(pool);
+
+    // The handlers, and their senders, are gone once the threads are joined
+    assert_eq!(rx.try_recv(), Err(mpsc::TryRecvError::Disconnected));
+}
@@ -0,0 +1,55 @@
+use mio::*;
+use mio::unix::pipe;
+use std::io::ErrorKind;
+
+#[test]
+pub fn test_injected_interrupt() {
+    let mut poll = Poll::new().unwrap();
+
+    poll.set_faults(Some(Faults { seed: 7, interrupt: 1, .. Faults::default() }));
+    assert_eq!(poll.poll(1_000).unwrap_err().kind(), ErrorKind::Interrupted);
+
+    poll.set_faults(None);
+    assert_eq!(poll.poll(0).unwrap(), 0);
+}
+
+#[test]
+pub fn test_injected_spurious_event() {
+    let mut poll = Poll::new().unwrap();
+    let (mut rd, mut wr) = pipe().unwrap();
+
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    poll.set_faults(Some(Faults { seed: 7, spurious: 1, .. Faults::default() }));
+
+    wr.try_write(b"a").unwrap();
+
+    // The real event, followed by a replay of it
+    assert_eq!(poll.poll(1_000).unwrap(), 2);
+    assert!(poll.events().all(|evt| evt.token == Token(1)));
+
+    let mut buf = [0; 8];
+    assert_eq!(rd.try_read(&mut buf).unwrap(), 1);
+
+    // Drained, yet reported ready again
+    assert_eq!(poll.poll(0).unwrap(), 1);
+    assert_eq!(poll.event(0).token, Token(1));
+    assert_eq!(rd.try_read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+}
+
+#[test]
+pub fn test_faults_are_deterministic() {
+    let faults = Faults { seed: 42, interrupt: 3, .. Faults::default() };
+
+    let outcomes = || {
+        let mut poll = Poll::new().unwrap();
+        poll.set_faults(Some(faults));
+
+        (0..32).map(|_| poll.poll(0).is_err()).collect::<Vec<bool>>()
+    };
+
+    let first = outcomes();
+
+    assert!(first.iter().any(|&failed| failed));
+    assert!(first.iter().any(|&failed| !failed));
+    assert_eq!(first, outcomes());
+}
@@ -0,0 +1,73 @@
+use mio::tcp::{TcpListener, TcpStream};
+use mio::util::Listeners;
+use libc;
+use std::os::unix::io::{AsRawFd, IntoRawFd};
+use super::localhost;
+
+#[test]
+pub fn test_listeners_round_trip() {
+    let addr = localhost();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let fd = listener.as_raw_fd();
+
+    let mut set = Listeners::new();
+    set.add_tcp("http", &listener).unwrap();
+    assert!(set.add_tcp("bad;name", &listener).is_err());
+    assert!(set.add_tcp("other", &listener).is_err());
+
+    let encoded = set.encode();
+    assert_eq!(encoded, format!("http=tcp:{}", fd));
+
+    // Stand in for the new process, which owns the inherited fd
+    let _ = listener.into_raw_fd();
+
+    let mut set = Listeners::decode(&encoded).unwrap();
+    assert_eq!(set.fds(), vec![fd]);
+    assert!(set.take_unix("http").unwrap().is_none());
+
+    let listener = set.take_tcp("http").unwrap().unwrap();
+    assert_eq!(listener.local_addr().unwrap(), addr);
+    assert!(set.is_empty());
+}
+
+#[test]
+pub fn test_listeners_malformed() {
+    assert!(Listeners::decode("").unwrap().is_empty());
+    assert!(Listeners::decode("http").is_err());
+    assert!(Listeners::decode("http=udp:3").is_err());
+    assert!(Listeners::decode("http=tcp:x").is_err());
+    assert!(Listeners::decode("http=tcp:3;other=tcp:3").is_err());
+    assert!(Listeners::decode("http=tcp:3;http=tcp:4").is_err());
+}
+
+#[test]
+pub fn test_listeners_inherit() {
+    let listener = TcpListener::bind(&localhost()).unwrap();
+    let fd = listener.as_raw_fd();
+    let cloexec = || unsafe { libc::fcntl(fd, libc::F_GETFD) } & libc::FD_CLOEXEC != 0;
+
+    let mut set = Listeners::new();
+    set.add_tcp("http", &listener).unwrap();
+
+    // Only cleared once asked to
+    assert!(cloexec());
+    set.inherit().unwrap();
+    assert!(!cloexec());
+}
+
+#[test]
+pub fn test_listeners_take_checks_kind() {
+    let addr = localhost();
+    let listener = TcpListener::bind(&addr).unwrap();
+    let stream = TcpStream::connect(&addr).unwrap();
+
+    let encoded = format!("http=unix:{};peer=tcp:{}", listener.as_raw_fd(), stream.as_raw_fd());
+    let mut set = Listeners::decode(&encoded).unwrap();
+
+    assert!(set.take_unix("http").is_err());
+    assert!(set.take_tcp("peer").is_err());
+
+    // The descriptors are left open
+    assert_eq!(listener.local_addr().unwrap(), addr);
+    assert!(stream.local_addr().is_ok());
+}
@@ -0,0 +1,57 @@
+use mio::*;
+use mio::util::MockStream;
+use std::io::ErrorKind;
+
+#[test]
+pub fn test_mock_stream_scripted_io() {
+    let mut stream = MockStream::new();
+    let mut buf = [0; 3];
+
+    stream.push_read(b"hello");
+    stream.push_read_err(ErrorKind::ConnectionReset);
+    stream.push_read_eof();
+
+    assert_eq!(stream.try_read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"hel");
+    assert_eq!(stream.try_read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf[..2], b"lo");
+    assert_eq!(stream.try_read(&mut buf).unwrap_err().kind(), ErrorKind::ConnectionReset);
+    assert_eq!(stream.try_read(&mut buf).unwrap(), 0);
+    assert_eq!(stream.try_read(&mut buf).unwrap(), 0);
+
+    stream.push_write(2);
+    assert_eq!(stream.try_write(b"abc").unwrap(), 2);
+    assert_eq!(stream.try_write(b"c").unwrap_err().kind(), ErrorKind::WouldBlock);
+    assert_eq!(stream.written(), b"ab");
+}
+
+#[test]
+pub fn test_mock_stream_with_injected_events() {
+    let mut poll = Poll::new().unwrap();
+    let mut stream = MockStream::new();
+
+    poll.register(&stream, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    assert_eq!(stream.registration(), Some((Token(1), EventSet::readable(), PollOpt::edge())));
+
+    let mut buf = [0; 8];
+    assert_eq!(stream.try_read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    // Readiness is delivered without waiting on the OS
+    stream.push_read(b"ping");
+    poll.inject(Token(1), EventSet::readable());
+
+    assert_eq!(poll.poll(1_000).unwrap(), 1);
+    assert_eq!(poll.event(0).token, Token(1));
+    assert!(poll.event(0).kind.is_readable());
+    assert_eq!(stream.try_read(&mut buf).unwrap(), 4);
+
+    // Injected events are only reported once
+    assert_eq!(poll.poll(0).unwrap(), 0);
+
+    poll.reregister(&stream, Token(1), EventSet::writable(), PollOpt::level()).unwrap();
+    assert_eq!(stream.registration(), Some((Token(1), EventSet::writable(), PollOpt::level())));
+
+    poll.deregister(&stream).unwrap();
+    assert_eq!(stream.registration(), None);
+    assert_eq!(poll.deregister(&stream).unwrap_err().kind(), ErrorKind::NotFound);
+}
@@ -0,0 +1,34 @@
+use mio::unix::MessageQueue;
+use libc;
+
+#[test]
+pub fn test_message_queue() {
+    let name = format!("/mio-test-{}", unsafe { libc::getpid() });
+
+    let mq = match MessageQueue::create_new(&name, 4, 64) {
+        Ok(mq) => mq,
+        // Kernels without POSIX message queues
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOSYS) => return,
+        Err(e) => panic!("failed to create a message queue; err={:?}", e),
+    };
+
+    // The name stays taken until unlinked
+    assert!(MessageQueue::create_new(&name, 4, 64).is_err());
+    MessageQueue::unlink(&name).unwrap();
+
+    let mut buf = [0; 64];
+    assert_eq!(mq.receive(&mut buf).unwrap(), None);
+
+    assert_eq!(mq.send(b"low", 1).unwrap(), Some(()));
+    assert_eq!(mq.send(b"high", 7).unwrap(), Some(()));
+    assert_eq!(mq.attributes().unwrap(), (4, 64, 2));
+
+    // Higher priorities are received first
+    assert_eq!(mq.receive(&mut buf).unwrap(), Some((4, 7)));
+    assert_eq!(&buf[..4], b"high");
+
+    assert_eq!(mq.receive(&mut buf).unwrap(), Some((3, 1)));
+    assert_eq!(&buf[..3], b"low");
+
+    assert_eq!(mq.receive(&mut buf).unwrap(), None);
+}
@@ -0,0 +1,90 @@
+use mio::*;
+use mio::unix::NetlinkSocket;
+
+const NETLINK_ROUTE: i32 = 0;
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+const NLM_F_REQUEST: u16 = 0x01;
+const NLM_F_DUMP: u16 = 0x300;
+const SEQ: u32 = 42;
+
+const NETLINK: Token = Token(0);
+
+struct NetlinkHandler {
+    sock: NetlinkSocket,
+    reply: Vec<u8>,
+}
+
+impl Handler for NetlinkHandler {
+    type Timeout = ();
+    type Message = ();
+
+    fn ready(&mut self, event_loop: &mut EventLoop<NetlinkHandler>, token: Token, events: EventSet) {
+        assert_eq!(token, NETLINK);
+        assert!(events.is_readable());
+
+        let mut buf = [0; 8192];
+
+        if let Some(cnt) = self.sock.try_read(&mut buf).map_non_block().unwrap() {
+            self.reply.extend(buf[..cnt].iter().cloned());
+            event_loop.shutdown();
+        }
+    }
+}
+
+// A `nlmsghdr` followed by an empty `ifinfomsg`, asking for every link
+fn getlink_request() -> Vec<u8> {
+    let mut req = Vec::new();
+
+    put_u32(&mut req, 32);
+    put_u16(&mut req, RTM_GETLINK);
+    put_u16(&mut req, NLM_F_REQUEST | NLM_F_DUMP);
+    put_u32(&mut req, SEQ);
+    put_u32(&mut req, 0);
+    req.extend([0u8; 16].iter().cloned());
+
+    req
+}
+
+fn put_u16(buf: &mut Vec<u8>, val: u16) {
+    let bytes: [u8; 2] = unsafe { ::std::mem::transmute(val) };
+    buf.extend(bytes.iter().cloned());
+}
+
+fn put_u32(buf: &mut Vec<u8>, val: u32) {
+    let bytes: [u8; 4] = unsafe { ::std::mem::transmute(val) };
+    buf.extend(bytes.iter().cloned());
+}
+
+// Netlink headers are in host byte order
+fn get_u16(buf: &[u8]) -> u16 {
+    let mut bytes = [0; 2];
+    bytes.copy_from_slice(&buf[..2]);
+    unsafe { ::std::mem::transmute(bytes) }
+}
+
+fn get_u32(buf: &[u8]) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(&buf[..4]);
+    unsafe { ::std::mem::transmute(bytes) }
+}
+
+#[test]
+pub fn test_netlink_getlink() {
+    let mut event_loop = EventLoop::new().unwrap();
+
+    let mut sock = NetlinkSocket::bound(NETLINK_ROUTE, 0, 0).unwrap();
+    assert_eq!(sock.try_write(&getlink_request()).unwrap(), 32);
+
+    event_loop.register_opt(&sock, NETLINK, EventSet::readable(), PollOpt::edge()).unwrap();
+
+    let mut handler = NetlinkHandler { sock: sock, reply: Vec::new() };
+    event_loop.run(&mut handler).unwrap();
+
+    // Every host has at least a loopback link, so the dump is not empty
+    let reply = &handler.reply[..];
+    assert!(reply.len() >= 16);
+    assert!(get_u32(reply) as usize <= reply.len());
+    assert_eq!(get_u16(&reply[4..]), RTM_NEWLINK);
+    assert_eq!(get_u32(&reply[8..]), SEQ);
+}
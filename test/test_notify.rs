@@ -197,3 +197,204 @@ pub fn test_notify_drop() {
 
     handle.join().unwrap();
 }
+
+#[test]
+pub fn test_notify_send_blocking() {
+    use std::thread;
+
+    struct Counter(i32);
+
+    impl Handler for Counter {
+        type Message = i32;
+        type Timeout = ();
+
+        fn notify(&mut self, event_loop: &mut EventLoop<Counter>, msg: i32) {
+            self.0 += msg;
+
+            if self.0 == 100 {
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    let config = EventLoopConfig {
+        notify_capacity: 2,
+        .. EventLoopConfig::default()
+    };
+
+    let mut event_loop = EventLoop::configured(config).unwrap();
+    let notify = event_loop.channel();
+
+    // Far more messages than the queue can hold at once
+    let handle = thread::spawn(move || {
+        for _ in 0..100 {
+            notify.send_blocking(1).unwrap();
+        }
+    });
+
+    let mut handler = Counter(0);
+    event_loop.run(&mut handler).unwrap();
+
+    assert_eq!(handler.0, 100);
+    handle.join().unwrap();
+}
+
+#[test]
+pub fn test_shutdown_from_other_thread() {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    struct Idle;
+
+    impl Handler for Idle {
+        type Message = ();
+        type Timeout = ();
+    }
+
+    let mut event_loop = EventLoop::new().unwrap();
+    let sender = event_loop.channel();
+
+    let handle = thread::spawn(move || {
+        sleep_ms(100);
+        sender.shutdown().unwrap();
+    });
+
+    event_loop.run(&mut Idle).unwrap();
+    assert!(!event_loop.is_running());
+
+    handle.join().unwrap();
+
+    // The request is consumed, so the loop can be run again
+    let sender = event_loop.channel();
+    let start = Instant::now();
+
+    let handle = thread::spawn(move || {
+        sleep_ms(100);
+        sender.shutdown().unwrap();
+    });
+
+    event_loop.run(&mut Idle).unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    handle.join().unwrap();
+}
+
+#[test]
+pub fn test_tick_after_dispatch() {
+    struct Ticks {
+        received: bool,
+        ticks_after: usize,
+    }
+
+    impl Handler for Ticks {
+        type Message = ();
+        type Timeout = ();
+
+        fn notify(&mut self, _: &mut EventLoop<Ticks>, _: ()) {
+            assert!(!self.received);
+            self.received = true;
+        }
+
+        fn tick(&mut self, event_loop: &mut EventLoop<Ticks>) {
+            if self.received {
+                self.ticks_after += 1;
+                event_loop.shutdown();
+            }
+        }
+    }
+
+    let mut event_loop = EventLoop::new().unwrap();
+    event_loop.channel().send(()).unwrap();
+
+    let mut handler = Ticks { received: false, ticks_after: 0 };
+    event_loop.run(&mut handler).unwrap();
+
+    // The message and the tick are handled in the same iteration
+    assert_eq!(handler.ticks_after, 1);
+}
+
+#[test]
+pub fn test_notify_send_blocking_closed() {
+    use std::thread;
+    use std::time::Duration;
+
+    let config = EventLoopConfig {
+        notify_capacity: 2,
+        .. EventLoopConfig::default()
+    };
+
+    let event_loop: EventLoop<TestHandler> = EventLoop::configured(config).unwrap();
+    let notify = event_loop.channel();
+
+    while notify.send("fill".to_string()).is_ok() {}
+
+    // Waits for room until the loop goes away
+    let handle = thread::spawn(move || notify.send_blocking("blocked".to_string()));
+
+    thread::sleep(Duration::from_millis(50));
+    drop(event_loop);
+
+    match handle.join().unwrap() {
+        Err(NotifyError::Closed(..)) => {}
+        res => panic!("unexpected result: {:?}", res),
+    }
+}
+
+struct CountingHandler {
+    received: usize,
+}
+
+impl Handler for CountingHandler {
+    type Timeout = ();
+    type Message = usize;
+
+    fn notify(&mut self, _: &mut EventLoop<CountingHandler>, _: usize) {
+        self.received += 1;
+    }
+}
+
+#[test]
+pub fn test_notify_wakeup_coalesces() {
+    use std::time::{Duration, Instant};
+
+    // Long enough that only the awakener can end the poll early
+    let config = EventLoopConfig {
+        io_poll_timeout_ms: 10_000,
+        timer_tick: Duration::from_secs(10),
+        .. EventLoopConfig::default()
+    };
+
+    let mut event_loop = EventLoop::configured(config).unwrap();
+    let mut handler = CountingHandler { received: 0 };
+    let sender = event_loop.channel();
+
+    let handle = thread::spawn(move || {
+        sleep_ms(50);
+
+        for i in 0..3 {
+            sender.send(i).unwrap();
+        }
+    });
+
+    let start = Instant::now();
+    event_loop.run_once(&mut handler, None).unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Pick up whatever arrived after the loop woke
+    handle.join().unwrap();
+    event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+
+    assert_eq!(handler.received, 3);
+    assert_single_wakeup(&event_loop);
+}
+
+// Only a send that finds the loop asleep writes to the awakener, so the
+// three sends above surface as at most one event
+#[cfg(feature = "metrics")]
+fn assert_single_wakeup(event_loop: &EventLoop<CountingHandler>) {
+    assert!(event_loop.poll_stats().events <= 1, "events={}", event_loop.poll_stats().events);
+}
+
+#[cfg(not(feature = "metrics"))]
+fn assert_single_wakeup(_: &EventLoop<CountingHandler>) {
+}
@@ -0,0 +1,67 @@
+use mio::EventSet;
+use mio::util::{MockStream, Pump};
+
+#[test]
+pub fn test_pump_both_directions() {
+    let mut a = MockStream::new();
+    let mut b = MockStream::new();
+    let mut pump = Pump::new(2);
+
+    a.push_read(b"ping");
+    b.push_read(b"pong");
+
+    // The buffer holds two bytes, so each side takes two writes
+    for _ in 0..2 {
+        a.push_write(1024);
+        b.push_write(1024);
+    }
+
+    let status = pump.pump(&mut a, &mut b).unwrap();
+    assert!(!status.a_closed && !status.b_closed);
+
+    assert_eq!(b.written(), b"ping");
+    assert_eq!(a.written(), b"pong");
+    assert_eq!(pump.interest_a(), EventSet::readable());
+    assert_eq!(pump.interest_b(), EventSet::readable());
+}
+
+#[test]
+pub fn test_pump_partial_write_and_eof() {
+    let mut a = MockStream::new();
+    let mut b = MockStream::new();
+    let mut pump = Pump::new(64);
+
+    a.push_read(b"hello world");
+    a.push_read_eof();
+
+    // `b` only accepts part of the data
+    b.push_write(4);
+
+    let status = pump.pump(&mut a, &mut b).unwrap();
+    assert!(!status.a_closed);
+    assert_eq!(b.written(), b"hell");
+    assert_eq!(pump.pending_a(), 7);
+    assert_eq!(pump.interest_a(), EventSet::none());
+    assert_eq!(pump.interest_b(), EventSet::readable() | EventSet::writable());
+
+    // Once the rest is flushed, the end of stream is reported exactly once
+    b.push_write(1024);
+    let status = pump.pump(&mut a, &mut b).unwrap();
+    assert!(status.a_closed);
+    assert_eq!(b.written(), b"hello world");
+
+    let status = pump.pump(&mut a, &mut b).unwrap();
+    assert!(!status.a_closed);
+    assert!(!pump.is_done());
+
+    b.push_read_eof();
+    let status = pump.pump(&mut a, &mut b).unwrap();
+    assert!(status.b_closed);
+    assert!(pump.is_done());
+}
+
+#[test]
+#[should_panic(expected = "buffer size must be non-zero")]
+pub fn test_pump_zero_buffer() {
+    Pump::new(0);
+}
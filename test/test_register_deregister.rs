@@ -96,3 +96,275 @@ pub fn test_register_deregister() {
 
     assert!(handler.state == 2, "unexpected final state {}", handler.state);
 }
+
+struct Noop;
+
+impl Handler for Noop {
+    type Timeout = ();
+    type Message = ();
+}
+
+#[test]
+pub fn test_register_owned_deregisters_on_drop() {
+    use mio::udp::UdpSocket;
+    use std::time::Duration;
+
+    let mut event_loop: EventLoop<Noop> = EventLoop::new().unwrap();
+
+    let sock = UdpSocket::bound(&localhost()).unwrap();
+    let addr = sock.local_addr().unwrap();
+
+    // The clone keeps the underlying socket open after the guard is dropped,
+    // so any events would still be reported had it stayed registered
+    let clone = sock.try_clone().unwrap();
+
+    let guard = event_loop.register_owned(sock, CLIENT, EventSet::readable(), PollOpt::level()).unwrap();
+    assert_eq!(guard.local_addr().unwrap(), addr);
+    drop(guard);
+
+    let sender = UdpSocket::v4().unwrap();
+    sender.send_to(&mut SliceBuf::wrap(b"hello"), &addr).unwrap();
+
+    let stats = event_loop.run_once(&mut Noop, Some(Duration::from_millis(100))).unwrap();
+    assert_eq!(stats.events, 0);
+
+    drop(clone);
+}
+
+#[test]
+pub fn test_registered_into_inner() {
+    use mio::udp::UdpSocket;
+
+    let mut event_loop: EventLoop<Noop> = EventLoop::new().unwrap();
+
+    let sock = UdpSocket::bound(&localhost()).unwrap();
+    let guard = event_loop.register_owned(sock, CLIENT, EventSet::readable(), PollOpt::level()).unwrap();
+    let sock = guard.into_inner().unwrap();
+
+    // Registering again only succeeds if the guard deregistered the socket
+    event_loop.register_opt(&sock, CLIENT, EventSet::readable(), PollOpt::level()).unwrap();
+}
+
+#[test]
+pub fn test_registered_into_inner_failure() {
+    use mio::udp::UdpSocket;
+
+    let mut event_loop: EventLoop<Noop> = EventLoop::new().unwrap();
+
+    let sock = UdpSocket::bound(&localhost()).unwrap();
+    let addr = sock.local_addr().unwrap();
+    let guard = event_loop.register_owned(sock, CLIENT, EventSet::readable(), PollOpt::level()).unwrap();
+
+    // Deregistering behind the guard's back makes its own attempt fail
+    event_loop.deregister(&*guard).unwrap();
+
+    let (_, guard) = guard.into_inner().err().unwrap();
+
+    // The handle is handed back rather than closed
+    assert_eq!(guard.local_addr().unwrap(), addr);
+}
+
+#[test]
+pub fn test_register_smart_pointers() {
+    use mio::udp::UdpSocket;
+    use std::rc::Rc;
+    use std::sync::Arc;
+
+    let mut event_loop: EventLoop<Noop> = EventLoop::new().unwrap();
+
+    let boxed: Box<Evented> = Box::new(UdpSocket::bound(&localhost()).unwrap());
+    let rc = Rc::new(UdpSocket::bound(&localhost()).unwrap());
+    let arc = Arc::new(UdpSocket::bound(&localhost()).unwrap());
+
+    event_loop.register_opt(&boxed, Token(0), EventSet::readable(), PollOpt::level()).unwrap();
+    event_loop.register_opt(&rc, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+    event_loop.register_opt(&arc, Token(2), EventSet::readable(), PollOpt::level()).unwrap();
+
+    event_loop.deregister(&boxed).unwrap();
+    event_loop.deregister(&rc).unwrap();
+    event_loop.deregister(&arc).unwrap();
+
+    // A guard may own a shared handle
+    let guard = event_loop.register_owned(arc.clone(), Token(3), EventSet::readable(), PollOpt::level()).unwrap();
+    guard.into_inner().unwrap();
+}
+
+#[test]
+pub fn test_poll_events_iter() {
+    use mio::unix::pipe;
+
+    let mut poll = Poll::new().unwrap();
+    let (rd1, mut wr1) = pipe().unwrap();
+    let (rd2, mut wr2) = pipe().unwrap();
+
+    poll.register(&rd1, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+    poll.register(&rd2, Token(2), EventSet::readable(), PollOpt::level()).unwrap();
+
+    wr1.try_write(b"a").unwrap();
+    wr2.try_write(b"b").unwrap();
+
+    assert_eq!(poll.poll(1_000).unwrap(), 2);
+    assert_eq!(poll.events().size_hint(), (2, Some(2)));
+
+    let mut tokens: Vec<Token> = poll.events()
+        .inspect(|evt| assert!(evt.kind.is_readable()))
+        .map(|evt| evt.token)
+        .collect();
+
+    tokens.sort_by(|a, b| a.as_usize().cmp(&b.as_usize()));
+    assert_eq!(tokens, vec![Token(1), Token(2)]);
+}
+
+#[test]
+pub fn test_deregister_all() {
+    use mio::udp::UdpSocket;
+
+    let mut event_loop: EventLoop<Noop> = EventLoop::new().unwrap();
+
+    let socks: Vec<UdpSocket> = (0..3)
+        .map(|_| UdpSocket::bound(&localhost()).unwrap())
+        .collect();
+
+    for (i, sock) in socks.iter().enumerate() {
+        event_loop.register_opt(sock, Token(i), EventSet::readable(), PollOpt::level()).unwrap();
+    }
+
+    let refs: Vec<&UdpSocket> = socks.iter().collect();
+    event_loop.deregister_all(&refs).unwrap();
+
+    // Registering again only succeeds once deregistered
+    for (i, sock) in socks.iter().enumerate() {
+        event_loop.register_opt(sock, Token(i), EventSet::readable(), PollOpt::level()).unwrap();
+    }
+
+    event_loop.deregister_all(&refs).unwrap();
+}
+
+#[test]
+pub fn test_poll_spin() {
+    use mio::unix::pipe;
+    use std::time::Duration;
+
+    let mut poll = Poll::new().unwrap();
+    poll.set_spin(Some(Duration::from_millis(5)));
+
+    let (rd, mut wr) = pipe().unwrap();
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+
+    // Nothing ready: spins, then blocks for what is left of the timeout
+    assert_eq!(poll.poll(20).unwrap(), 0);
+
+    wr.try_write(b"a").unwrap();
+    assert_eq!(poll.poll(1_000).unwrap(), 1);
+    assert_eq!(poll.events().next().unwrap().token, Token(1));
+}
+
+#[test]
+pub fn test_poll_spin_bounded_by_timeout() {
+    use mio::unix::pipe;
+    use std::time::{Duration, Instant};
+
+    let mut poll = Poll::new().unwrap();
+    poll.set_spin(Some(Duration::from_secs(10)));
+
+    let (rd, _wr) = pipe().unwrap();
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::level()).unwrap();
+
+    // Spinning counts against the timeout rather than extending it
+    let start = Instant::now();
+    assert_eq!(poll.poll(20).unwrap(), 0);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_registrations() {
+    use mio::unix::pipe;
+    use std::os::unix::io::AsRawFd;
+
+    let mut poll = Poll::new().unwrap();
+    let (rd, wr) = pipe().unwrap();
+
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    poll.register(&wr, Token(2), EventSet::writable(), PollOpt::level() | PollOpt::oneshot()).unwrap();
+
+    let mut regs = poll.registrations().unwrap();
+    regs.sort_by(|a, b| a.token.as_usize().cmp(&b.token.as_usize()));
+
+    assert_eq!(regs.len(), 2);
+
+    assert_eq!(regs[0].fd, rd.as_raw_fd());
+    assert_eq!(regs[0].token, Token(1));
+    assert_eq!(regs[0].interest, EventSet::readable());
+    assert_eq!(regs[0].opts, PollOpt::edge());
+
+    assert_eq!(regs[1].fd, wr.as_raw_fd());
+    assert_eq!(regs[1].interest, EventSet::writable());
+    assert_eq!(regs[1].opts, PollOpt::level() | PollOpt::oneshot());
+
+    poll.deregister(&rd).unwrap();
+    assert_eq!(poll.registrations().unwrap().len(), 1);
+}
+
+// Turns leak panics back off for the thread, even when the test panics
+#[cfg(debug_assertions)]
+struct PanicOnLeak;
+
+#[cfg(debug_assertions)]
+impl PanicOnLeak {
+    fn enable() -> PanicOnLeak {
+        Io::set_panic_on_leak(true);
+        PanicOnLeak
+    }
+}
+
+#[cfg(debug_assertions)]
+impl Drop for PanicOnLeak {
+    fn drop(&mut self) {
+        Io::set_panic_on_leak(false);
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "dropped while registered")]
+pub fn test_leaked_registration_panics() {
+    use mio::unix::pipe;
+
+    let _panic = PanicOnLeak::enable();
+
+    let mut poll = Poll::new().unwrap();
+    let (rd, _wr) = pipe().unwrap();
+
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    drop(rd);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn test_unregistered_drops_are_not_leaks() {
+    use mio::unix::pipe;
+    use std::os::unix::io::IntoRawFd;
+
+    let _panic = PanicOnLeak::enable();
+
+    let mut poll = Poll::new().unwrap();
+    let (rd, _wr) = pipe().unwrap();
+
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    poll.deregister(&rd).unwrap();
+    drop(rd);
+
+    // Closing the selector drops its registrations as well
+    let (rd, _wr) = pipe().unwrap();
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    drop(poll);
+    drop(rd);
+
+    // Handing the descriptor off forgets about its registrations
+    let mut poll = Poll::new().unwrap();
+    let (rd, _wr) = pipe().unwrap();
+    poll.register(&rd, Token(1), EventSet::readable(), PollOpt::edge()).unwrap();
+    let fd = rd.into_raw_fd();
+    drop(Io::from_raw_fd(fd));
+}
@@ -0,0 +1,70 @@
+use mio::*;
+use mio::util::{Resolve, Resolver};
+use std::io;
+use std::net::SocketAddr;
+
+struct Static;
+
+impl Resolve for Static {
+    fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        match host {
+            "example" => Ok(vec![format!("10.0.0.1:{}", port).parse().unwrap()]),
+            _ => Err(io::Error::new(io::ErrorKind::NotFound, "unknown host")),
+        }
+    }
+}
+
+struct ResolveHandler {
+    results: Vec<(&'static str, io::Result<Vec<SocketAddr>>)>,
+}
+
+impl Handler for ResolveHandler {
+    type Timeout = ();
+    type Message = (&'static str, io::Result<Vec<SocketAddr>>);
+
+    fn notify(&mut self, event_loop: &mut EventLoop<ResolveHandler>, msg: Self::Message) {
+        self.results.push(msg);
+
+        if self.results.len() == 2 {
+            event_loop.shutdown();
+        }
+    }
+}
+
+#[test]
+pub fn test_resolver_backend() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let resolver = Resolver::with_backend(1, Static);
+
+    resolver.resolve("example", 80, event_loop.channel(), |res| ("example", res)).unwrap();
+    resolver.resolve("missing", 80, event_loop.channel(), |res| ("missing", res)).unwrap();
+
+    let mut handler = ResolveHandler { results: vec![] };
+    event_loop.run(&mut handler).unwrap();
+
+    let (host, ref res) = handler.results[0];
+    assert_eq!(host, "example");
+    assert_eq!(res.as_ref().unwrap(), &vec!["10.0.0.1:80".parse().unwrap()]);
+
+    let (host, ref res) = handler.results[1];
+    assert_eq!(host, "missing");
+    assert_eq!(res.as_ref().unwrap_err().kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+pub fn test_system_resolver() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let resolver = Resolver::new(2);
+
+    resolver.resolve("127.0.0.1", 8080, event_loop.channel(), |res| ("a", res)).unwrap();
+    resolver.resolve("::1", 8080, event_loop.channel(), |res| ("b", res)).unwrap();
+
+    let mut handler = ResolveHandler { results: vec![] };
+    event_loop.run(&mut handler).unwrap();
+
+    for &(_, ref res) in handler.results.iter() {
+        let addrs = res.as_ref().unwrap();
+        assert_eq!(addrs.len(), 1);
+        assert_eq!(addrs[0].port(), 8080);
+    }
+}
@@ -0,0 +1,53 @@
+use mio::sctp::{SctpListener, SctpStream};
+use libc;
+use super::{localhost, sleep_ms};
+
+#[test]
+pub fn test_sctp_loopback() {
+    let addr = localhost();
+
+    let listener = match SctpListener::bind(&addr) {
+        Ok(listener) => listener,
+        // Hosts without the SCTP module
+        Err(ref e) if [libc::EPROTONOSUPPORT, libc::ESOCKTNOSUPPORT, libc::EAFNOSUPPORT]
+            .contains(&e.raw_os_error().unwrap_or(0)) => return,
+        Err(e) => panic!("failed to bind an SCTP listener; err={:?}", e),
+    };
+
+    let client = SctpStream::connect(&addr).unwrap();
+
+    let server;
+
+    loop {
+        if let Some(sock) = listener.accept().unwrap() {
+            server = sock;
+            break;
+        }
+
+        sleep_ms(10);
+    }
+
+    // The accepted side is established, the client may still be finishing
+    // the handshake
+    assert_eq!(server.send(b"hello", 3, 42).unwrap(), Some(5));
+
+    let mut buf = [0; 256];
+
+    let info;
+
+    loop {
+        match client.recv(&mut buf).unwrap() {
+            // Association events arrive alongside the data
+            Some(i) if i.notification => {}
+            Some(i) => {
+                info = i;
+                break;
+            }
+            None => sleep_ms(10),
+        }
+    }
+
+    assert_eq!(&buf[..info.len], b"hello");
+    assert_eq!(info.stream, 3);
+    assert_eq!(info.ppid, 42);
+}
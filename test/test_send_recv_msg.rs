@@ -0,0 +1,51 @@
+use mio::TryRead;
+use mio::unix::*;
+use std::io::Write;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use tempdir::TempDir;
+
+#[test]
+pub fn test_pass_fd() {
+    let tmp_dir = TempDir::new("mio").unwrap();
+    let addr = tmp_dir.path().join("sock");
+
+    let srv = UnixListener::bind(&addr).unwrap();
+    let client = UnixStream::connect(&addr).unwrap();
+    let conn = srv.accept().unwrap().unwrap();
+
+    let (rd, mut wr) = pipe().unwrap();
+
+    let mut control = ControlMessages::new();
+    control.rights(&[rd.as_raw_fd()]);
+
+    assert_eq!(client.send_msg(&[b"fd"], None, &control).unwrap(), Some(2));
+
+    let mut data = [0; 16];
+    let mut cbuf = [0; 64];
+    let msg = conn.recv_msg(&mut [&mut data[..]], &mut cbuf).unwrap().unwrap();
+
+    assert_eq!(msg.len, 2);
+    assert_eq!(&data[..2], b"fd");
+
+    let fds: Vec<_> = ControlMessages::parse(&cbuf[..msg.control_len])
+        .filter_map(|cmsg| match cmsg {
+            ControlMessage::Rights(fds) => Some(fds),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(fds.len(), 1);
+    assert_eq!(fds[0].len(), 1);
+
+    // The received descriptor is not inherited across exec
+    let flags = unsafe { ::libc::fcntl(fds[0][0], ::libc::F_GETFD) };
+    assert!(flags & ::libc::FD_CLOEXEC != 0);
+
+    // The received descriptor refers to the same pipe
+    let mut received = unsafe { PipeReader::from_raw_fd(fds[0][0]) };
+    wr.write_all(b"hi").unwrap();
+
+    let mut buf = [0; 2];
+    assert_eq!(received.try_read(&mut buf).unwrap(), 2);
+    assert_eq!(&buf, b"hi");
+}
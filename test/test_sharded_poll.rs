@@ -0,0 +1,43 @@
+use mio::*;
+use mio::unix::pipe;
+
+#[test]
+pub fn test_sharded_poll() {
+    let mut poll = ShardedPoll::with_capacity(4, 16).unwrap();
+    assert_eq!(poll.shards(), 4);
+
+    let mut pipes: Vec<_> = (0..8).map(|_| pipe().unwrap()).collect();
+
+    for (i, &(ref rd, _)) in pipes.iter().enumerate() {
+        poll.register(rd, Token(i), EventSet::readable(), PollOpt::level()).unwrap();
+    }
+
+    // Nothing is ready yet
+    assert_eq!(poll.poll(0).unwrap(), 0);
+
+    // Wake handles living in three different shards
+    for &i in &[1, 2, 7] {
+        pipes[i].1.try_write(b"a").unwrap();
+    }
+
+    assert_eq!(poll.poll(1_000).unwrap(), 3);
+
+    let mut tokens: Vec<usize> = poll.events()
+        .inspect(|evt| assert!(evt.kind.is_readable()))
+        .map(|evt| evt.token.as_usize())
+        .collect();
+
+    tokens.sort();
+    assert_eq!(tokens, vec![1, 2, 7]);
+
+    poll.deregister(&pipes[7].0, Token(7)).unwrap();
+    assert_eq!(poll.poll(1_000).unwrap(), 2);
+}
+
+#[test]
+pub fn test_sharded_poll_empty() {
+    use std::io::ErrorKind;
+
+    assert_eq!(ShardedPoll::new(0).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(ShardedPoll::with_capacity(0, 16).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
@@ -0,0 +1,309 @@
+use libc;
+use mio::tcp::*;
+use mio::udp::*;
+use mio::unix::Socket;
+use std::str::FromStr;
+use std::time::Duration;
+
+#[test]
+pub fn test_send_recv_buffer_size() {
+    let sock = TcpSocket::v4().unwrap();
+
+    sock.set_send_buffer_size(16 * 1024).unwrap();
+    sock.set_recv_buffer_size(32 * 1024).unwrap();
+
+    // The kernel may round the values up, but never below what was asked
+    assert!(sock.send_buffer_size().unwrap() >= 16 * 1024);
+    assert!(sock.recv_buffer_size().unwrap() >= 32 * 1024);
+
+    let any = FromStr::from_str("0.0.0.0:0").unwrap();
+    let udp = UdpSocket::bound(&any).unwrap();
+
+    udp.set_recv_buffer_size(64 * 1024).unwrap();
+    assert!(udp.recv_buffer_size().unwrap() >= 64 * 1024);
+}
+
+#[test]
+pub fn test_buffer_size_too_large() {
+    use std::io::ErrorKind;
+
+    let sock = TcpSocket::v4().unwrap();
+    let size = ::std::i32::MAX as usize + 1;
+
+    assert_eq!(sock.set_send_buffer_size(size).unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(sock.set_recv_buffer_size(size).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+pub fn test_linger() {
+    let sock = TcpSocket::v4().unwrap();
+
+    assert_eq!(sock.linger().unwrap(), None);
+
+    sock.set_linger(Some(Duration::from_secs(5))).unwrap();
+    assert_eq!(sock.linger().unwrap(), Some(Duration::from_secs(5)));
+
+    // Zero linger is distinct from lingering being disabled
+    sock.set_linger(Some(Duration::from_secs(0))).unwrap();
+    assert_eq!(sock.linger().unwrap(), Some(Duration::from_secs(0)));
+
+    sock.set_linger(None).unwrap();
+    assert_eq!(sock.linger().unwrap(), None);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_attach_filter() {
+    use mio::unix::bpf::FilterBuilder;
+
+    let any = FromStr::from_str("127.0.0.1:0").unwrap();
+    let udp = UdpSocket::bound(&any).unwrap();
+
+    udp.attach_filter(&FilterBuilder::drop_all()).unwrap();
+    udp.detach_filter().unwrap();
+
+    // Detaching when no filter is attached is an error
+    assert!(udp.detach_filter().is_err());
+}
+
+#[test]
+pub fn test_socket_introspection() {
+    let tcp = TcpSocket::v6().unwrap();
+    assert_eq!(tcp.domain().unwrap(), libc::AF_INET6);
+    assert_eq!(tcp.socket_type().unwrap(), libc::SOCK_STREAM);
+
+    let udp = UdpSocket::v4().unwrap();
+    assert_eq!(udp.domain().unwrap(), libc::AF_INET);
+    assert_eq!(udp.socket_type().unwrap(), libc::SOCK_DGRAM);
+}
+
+#[test]
+pub fn test_hops_v6() {
+    let udp = UdpSocket::v6().unwrap();
+
+    udp.set_unicast_hops_v6(12).unwrap();
+    assert_eq!(udp.unicast_hops_v6().unwrap(), 12);
+
+    udp.set_multicast_hops_v6(3).unwrap();
+    assert_eq!(udp.multicast_hops_v6().unwrap(), 3);
+}
+
+#[test]
+pub fn test_adopt_blocking_fd() {
+    use mio::{Io, TryRead};
+    use std::io::ErrorKind;
+    use std::net;
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+    let sock = net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let mut io = Io::adopt(sock.into_raw_fd()).unwrap();
+
+    let mut buf = [0; 16];
+    assert_eq!(io.try_read(&mut buf).unwrap_err().kind(), ErrorKind::WouldBlock);
+
+    io.set_cloexec(false).unwrap();
+
+    // Socket types expose the same helpers
+    let sock = unsafe { UdpSocket::from_raw_fd(io.into_raw_fd()) };
+    sock.set_nonblocking(false).unwrap();
+    sock.set_nonblocking(true).unwrap();
+    sock.set_cloexec(true).unwrap();
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_device() {
+    use std::io::ErrorKind;
+
+    let udp = UdpSocket::v4().unwrap();
+
+    match udp.set_device(Some("lo")) {
+        Ok(()) => {}
+        // Kernels before 5.7 require CAP_NET_RAW
+        Err(ref e) if e.kind() == ErrorKind::PermissionDenied => return,
+        Err(e) => panic!("set_device failed: {}", e),
+    }
+    assert_eq!(udp.device().unwrap(), Some("lo".to_string()));
+
+    udp.set_device(None).unwrap();
+    assert_eq!(udp.device().unwrap(), None);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_freebind() {
+    let tcp = TcpSocket::v4().unwrap();
+    assert!(!tcp.freebind().unwrap());
+
+    tcp.set_freebind(true).unwrap();
+    assert!(tcp.freebind().unwrap());
+
+    let udp = UdpSocket::v6().unwrap();
+    udp.set_freebind(true).unwrap();
+    assert!(udp.freebind().unwrap());
+
+    udp.set_freebind(false).unwrap();
+    assert!(!udp.freebind().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_mark() {
+    use std::io::ErrorKind;
+
+    let tcp = TcpSocket::v4().unwrap();
+
+    match tcp.set_mark(0x2a) {
+        Ok(()) => {}
+        // Requires CAP_NET_ADMIN
+        Err(ref e) if e.kind() == ErrorKind::PermissionDenied => return,
+        Err(e) => panic!("set_mark failed: {}", e),
+    }
+    assert_eq!(tcp.mark().unwrap(), 0x2a);
+}
+
+#[test]
+pub fn test_tos_tclass() {
+    // Low delay, with the ECN bits left clear
+    let v4 = UdpSocket::v4().unwrap();
+    v4.set_tos(0x10).unwrap();
+    assert_eq!(v4.tos().unwrap(), 0x10);
+
+    let v6 = UdpSocket::v6().unwrap();
+    v6.set_tclass(0x20).unwrap();
+    assert_eq!(v6.tclass().unwrap(), 0x20);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_incoming_cpu() {
+    let addr = FromStr::from_str("127.0.0.1:0").unwrap();
+    let listener = TcpListener::bind(&addr).unwrap();
+
+    listener.set_incoming_cpu(0).unwrap();
+    assert_eq!(listener.incoming_cpu().unwrap(), 0);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_busy_poll() {
+    use std::io::ErrorKind;
+
+    let udp = UdpSocket::v4().unwrap();
+
+    match udp.set_busy_poll(Duration::new(0, 50_000)) {
+        Ok(()) => {}
+        // Raising it above `net.core.busy_read` requires CAP_NET_ADMIN, and
+        // kernels built without busy polling don't know the option
+        Err(ref e) if e.kind() == ErrorKind::PermissionDenied => return,
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => return,
+        Err(e) => panic!("set_busy_poll failed: {}", e),
+    }
+    assert_eq!(udp.busy_poll().unwrap(), Duration::new(0, 50_000));
+}
+
+#[test]
+pub fn test_raw_opt() {
+    use std::mem;
+
+    let tcp = TcpSocket::v4().unwrap();
+    let on: libc::c_int = 1;
+
+    unsafe {
+        let val: [u8; 4] = mem::transmute(on);
+        tcp.set_raw_opt(libc::SOL_SOCKET, libc::SO_KEEPALIVE, &val).unwrap();
+
+        let mut buf = [0u8; 4];
+        assert_eq!(tcp.raw_opt(libc::SOL_SOCKET, libc::SO_KEEPALIVE, &mut buf).unwrap(), 4);
+        assert!(mem::transmute::<[u8; 4], libc::c_int>(buf) != 0);
+    }
+}
+
+#[test]
+pub fn test_oobinline() {
+    let tcp = TcpSocket::v4().unwrap();
+    assert!(!tcp.oobinline().unwrap());
+
+    tcp.set_oobinline(true).unwrap();
+    assert!(tcp.oobinline().unwrap());
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_priority() {
+    let udp = UdpSocket::v4().unwrap();
+
+    udp.set_priority(3).unwrap();
+    assert_eq!(udp.priority().unwrap(), 3);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_attach_reuseport_cbpf() {
+    use mio::unix::bpf::{SockFilter, BPF_K, BPF_RET};
+    use std::net;
+    use sleep_ms;
+
+    // Steer every connection to the first socket of the group
+    let prog = vec![SockFilter::stmt(BPF_RET | BPF_K, 0)];
+    let addr = FromStr::from_str("127.0.0.1:0").unwrap();
+
+    let first = TcpSocket::v4().unwrap();
+    first.set_reuseport(true).unwrap();
+    first.attach_reuseport_cbpf(&prog).unwrap();
+    first.bind(&addr).unwrap();
+    let first = first.listen(16).unwrap();
+    let addr = first.local_addr().unwrap();
+
+    let second = TcpSocket::v4().unwrap();
+    second.set_reuseport(true).unwrap();
+    second.bind(&addr).unwrap();
+    let second = second.listen(16).unwrap();
+
+    let _client = net::TcpStream::connect(&addr).unwrap();
+
+    let mut accepted = None;
+    for _ in 0..100 {
+        accepted = first.accept().unwrap();
+        if accepted.is_some() {
+            break;
+        }
+        sleep_ms(10);
+    }
+
+    assert!(accepted.is_some());
+    assert!(second.accept().unwrap().is_none());
+}
+
+#[test]
+pub fn test_dontroute() {
+    let udp = UdpSocket::v4().unwrap();
+    assert!(!udp.dontroute().unwrap());
+
+    udp.set_dontroute(true).unwrap();
+    assert!(udp.dontroute().unwrap());
+}
+
+#[test]
+pub fn test_recv_lowat() {
+    use std::io::ErrorKind;
+
+    let tcp = TcpSocket::v4().unwrap();
+    assert_eq!(tcp.recv_lowat().unwrap(), 1);
+
+    tcp.set_recv_lowat(128).unwrap();
+    assert_eq!(tcp.recv_lowat().unwrap(), 128);
+
+    let err = tcp.set_recv_lowat(::std::i32::MAX as usize + 1).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(target_os = "freebsd")]
+pub fn test_reuseport_lb() {
+    let tcp = TcpSocket::v4().unwrap();
+    assert!(!tcp.reuseport_lb().unwrap());
+
+    tcp.set_reuseport_lb(true).unwrap();
+    assert!(tcp.reuseport_lb().unwrap());
+}
@@ -0,0 +1,35 @@
+use mio::TryRead;
+use mio::unix::*;
+use std::io::Write;
+
+#[test]
+pub fn test_splice_between_pipes() {
+    let (src_rd, mut src_wr) = pipe().unwrap();
+    let (mut dst_rd, dst_wr) = pipe().unwrap();
+    let (mut mirror_rd, mirror_wr) = pipe().unwrap();
+
+    let mut fwd = Splice::new().unwrap();
+
+    // Nothing to move yet
+    assert_eq!(fwd.fill(&src_rd, 1024).unwrap(), None);
+
+    src_wr.write_all(b"hello").unwrap();
+    assert_eq!(fwd.fill(&src_rd, 1024).unwrap(), Some(5));
+    assert_eq!(fwd.pending(), 5);
+
+    assert_eq!(fwd.drain(&dst_wr).unwrap(), Some(5));
+    assert_eq!(fwd.pending(), 0);
+
+    // Mirror the delivered bytes before consuming them
+    let (tee_rd, tee_wr) = pipe().unwrap();
+    assert_eq!(splice(&dst_rd, &tee_wr, 5).unwrap(), Some(5));
+    assert_eq!(tee(&tee_rd, &mirror_wr, 5).unwrap(), Some(5));
+    assert_eq!(splice(&tee_rd, &dst_wr, 5).unwrap(), Some(5));
+
+    let mut buf = [0; 5];
+    assert_eq!(dst_rd.try_read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+
+    assert_eq!(mirror_rd.try_read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf, b"hello");
+}
@@ -0,0 +1,19 @@
+use mio::unix::pipe;
+use std::io::{self, Read, Write};
+
+#[test]
+pub fn test_pipe_would_block() {
+    let (mut rd, mut wr) = pipe().unwrap();
+
+    let mut buf = [0; 16];
+    let err = rd.read(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    wr.write_all(b"ping").unwrap();
+
+    // `std::io` adapters work on the non-blocking types directly
+    let mut dst = Vec::new();
+    let err = io::copy(&mut rd, &mut dst).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(dst, b"ping");
+}
@@ -0,0 +1,104 @@
+use mio::{MapNonBlock, TryRead};
+use super::{sleep_ms, tcp_pair};
+
+#[test]
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "ios", target_os = "freebsd", target_os = "dragonfly"))]
+pub fn test_send_file() {
+    use std::fs::File;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::os::unix::io::AsRawFd;
+    use tempdir::TempDir;
+
+    let dir = TempDir::new("mio").unwrap();
+    let path = dir.path().join("payload");
+    let data: Vec<u8> = (0..65_536).map(|i| i as u8).collect();
+    File::create(&path).unwrap().write_all(&data).unwrap();
+
+    let mut file = File::open(&path).unwrap();
+    let (client, mut server) = tcp_pair();
+
+    // Zero sends nothing, rather than the whole file as on the BSDs
+    assert_eq!(client.send_file(file.as_raw_fd(), 0, 0).unwrap(), Some(0));
+
+    let mut offset = 0;
+    let mut received = Vec::new();
+    let mut buf = [0; 4_096];
+
+    while received.len() < data.len() {
+        let mut progress = false;
+
+        if offset < data.len() {
+            if let Some(cnt) = client.send_file(file.as_raw_fd(), offset as u64, data.len() - offset).unwrap() {
+                offset += cnt;
+                progress = cnt > 0;
+            }
+        }
+
+        if let Some(cnt) = server.try_read(&mut buf).map_non_block().unwrap() {
+            assert!(cnt > 0, "unexpected end of stream");
+            received.extend(buf[..cnt].iter().cloned());
+            progress = true;
+        }
+
+        if !progress {
+            sleep_ms(10);
+        }
+    }
+
+    assert_eq!(received, data);
+
+    // The file's own position is left untouched
+    assert_eq!(file.seek(SeekFrom::Current(0)).unwrap(), 0);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_send_more() {
+    use mio::TryWrite;
+
+    let (mut client, mut server) = tcp_pair();
+
+    assert_eq!(client.send_more(b"header:").unwrap(), Some(7));
+    assert_eq!(client.try_write(b"body").unwrap(), 4);
+
+    let mut received = Vec::new();
+    let mut buf = [0; 64];
+
+    while received.len() < 11 {
+        match server.try_read(&mut buf).map_non_block().unwrap() {
+            Some(0) => panic!("unexpected end of stream"),
+            Some(cnt) => received.extend(buf[..cnt].iter().cloned()),
+            None => sleep_ms(10),
+        }
+    }
+
+    assert_eq!(&received[..], b"header:body");
+}
+
+#[test]
+pub fn test_cached_addrs() {
+    use mio::tcp::{TcpListener, TcpStream};
+    use super::localhost;
+
+    let addr = localhost();
+    let listener = TcpListener::bind(&addr).unwrap();
+    assert_eq!(listener.local_addr().unwrap(), addr);
+
+    let client = TcpStream::connect(&addr).unwrap();
+
+    let server;
+
+    loop {
+        if let Some(sock) = listener.accept().unwrap() {
+            server = sock;
+            break;
+        }
+
+        sleep_ms(10);
+    }
+
+    // A peer not known yet when `connect` returned is cached on first use
+    assert_eq!(server.local_addr().unwrap(), addr);
+    assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+    assert_eq!(client.peer_addr().unwrap(), addr);
+}
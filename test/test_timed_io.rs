@@ -0,0 +1,98 @@
+use mio::*;
+use mio::unix::{pipe, PipeReader, PipeWriter};
+use mio::util::TimedIo;
+use std::time::{Duration, Instant};
+
+const READER: Token = Token(0);
+
+#[derive(Debug)]
+enum Deadline {
+    Idle,
+    Write,
+}
+
+struct IdleHandler {
+    rd: TimedIo<PipeReader>,
+    wr: PipeWriter,
+    rechecked: bool,
+}
+
+impl Handler for IdleHandler {
+    type Timeout = Deadline;
+    type Message = ();
+
+    fn ready(&mut self, _: &mut EventLoop<IdleHandler>, token: Token, events: EventSet) {
+        assert_eq!(token, READER);
+        assert!(events.is_readable());
+
+        let mut buf = [0; 16];
+        assert_eq!(self.rd.try_read(&mut buf).unwrap(), 4);
+    }
+
+    fn timeout(&mut self, event_loop: &mut EventLoop<IdleHandler>, timeout: Deadline) {
+        match timeout {
+            Deadline::Write => {
+                self.wr.try_write(b"ping").unwrap();
+            }
+            Deadline::Idle => {
+                if self.rd.expired(event_loop, Deadline::Idle).unwrap() {
+                    event_loop.shutdown();
+                } else {
+                    // The read pushed the deadline back
+                    self.rechecked = true;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_idle_timeout_reset_by_activity() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let (rd, wr) = pipe().unwrap();
+
+    let start = Instant::now();
+    let mut handler = IdleHandler {
+        rd: TimedIo::new(rd, Duration::from_millis(300)),
+        wr: wr,
+        rechecked: false,
+    };
+
+    event_loop.register_opt(&handler.rd, READER, EventSet::readable(), PollOpt::level()).unwrap();
+    handler.rd.arm(&mut event_loop, Deadline::Idle).unwrap();
+    event_loop.timeout_ms(Deadline::Write, 150).unwrap();
+
+    event_loop.run(&mut handler).unwrap();
+
+    assert!(handler.rechecked);
+    assert!(start.elapsed() >= Duration::from_millis(450));
+}
+
+#[test]
+pub fn test_idle_timeout_manual_clock() {
+    let clock = ManualClock::new();
+    let mut event_loop = EventLoop::configured_with_clock(EventLoopConfig::default(), Box::new(clock.clone())).unwrap();
+    let (rd, wr) = pipe().unwrap();
+
+    let mut handler = IdleHandler {
+        rd: TimedIo::with_clock(rd, Duration::from_secs(30), Box::new(clock.clone())),
+        wr: wr,
+        rechecked: false,
+    };
+
+    handler.rd.arm(&mut event_loop, Deadline::Idle).unwrap();
+
+    clock.advance(Duration::from_secs(20));
+    assert_eq!(handler.rd.idle_for(), Duration::from_secs(20));
+    handler.rd.touch();
+
+    // The first deadline passes, but the activity pushed it back
+    clock.advance(Duration::from_secs(15));
+    event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+    assert!(handler.rechecked);
+    assert!(event_loop.is_running());
+
+    clock.advance(Duration::from_secs(15));
+    event_loop.run_once(&mut handler, Some(Duration::from_millis(0))).unwrap();
+    assert!(!event_loop.is_running());
+}
@@ -65,7 +65,7 @@ impl TestHandler {
 
                 let mut buf = ByteBuf::mut_with_capacity(2048);
 
-                match self.cli.try_read_buf(&mut buf) {
+                match self.cli.try_read_buf(&mut buf).map_non_block() {
                     Ok(n) => {
                         debug!("read {:?} bytes", n);
                         assert!(b"zomg" == buf.flip().bytes());
@@ -108,7 +108,7 @@ impl Handler for TestHandler {
 
     fn timeout(&mut self, _event_loop: &mut EventLoop<TestHandler>, mut sock: TcpStream) {
         debug!("timeout handler : writing to socket");
-        sock.try_write_buf(&mut SliceBuf::wrap(b"zomg")).unwrap().unwrap();
+        sock.try_write_buf(&mut SliceBuf::wrap(b"zomg")).unwrap();
     }
 }
 
@@ -144,3 +144,103 @@ pub fn test_timer() {
 
     assert!(handler.state == AfterHup, "actual={:?}", handler.state);
 }
+
+#[cfg(target_os = "linux")]
+struct PreciseHandler {
+    fired: bool,
+}
+
+#[cfg(target_os = "linux")]
+impl Handler for PreciseHandler {
+    type Timeout = ();
+    type Message = ();
+
+    fn timeout(&mut self, event_loop: &mut EventLoop<PreciseHandler>, _: ()) {
+        self.fired = true;
+        event_loop.shutdown();
+    }
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_precise_timer() {
+    use std::time::{Duration, Instant};
+
+    // With a long poll timeout, only the timerfd can wake the loop in time
+    let config = EventLoopConfig {
+        io_poll_timeout_ms: 10_000,
+        timer_tick: Duration::new(0, 500_000),
+        timer_precise: true,
+        .. EventLoopConfig::default()
+    };
+
+    let mut event_loop = EventLoop::configured(config).unwrap();
+    let mut handler = PreciseHandler { fired: false };
+
+    let start = Instant::now();
+    event_loop.timeout_ms((), 5).unwrap();
+    event_loop.run(&mut handler).unwrap();
+
+    assert!(handler.fired);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_precise_timer_ignored_with_clock() {
+    use mio::ManualClock;
+    use std::time::{Duration, Instant};
+
+    let config = EventLoopConfig {
+        io_poll_timeout_ms: 10_000,
+        timer_tick: Duration::from_millis(100),
+        timer_precise: true,
+        .. EventLoopConfig::default()
+    };
+
+    let clock = ManualClock::new();
+    let mut event_loop = EventLoop::configured_with_clock(config, Box::new(clock)).unwrap();
+    let mut handler = PreciseHandler { fired: false };
+
+    // A timerfd armed with the manual clock's deadlines would fire at once
+    let start = Instant::now();
+    event_loop.run_once(&mut handler, Some(Duration::from_millis(50))).unwrap();
+
+    assert!(start.elapsed() >= Duration::from_millis(40));
+}
+
+struct BatchHandler {
+    batches: Vec<Vec<u32>>,
+}
+
+impl Handler for BatchHandler {
+    type Timeout = u32;
+    type Message = ();
+
+    fn timeout(&mut self, _: &mut EventLoop<BatchHandler>, _: u32) {
+        panic!("timeouts should be delivered as a batch");
+    }
+
+    fn timeouts(&mut self, event_loop: &mut EventLoop<BatchHandler>) {
+        let mut batch = vec![];
+        event_loop.expired_timeouts(&mut batch);
+        batch.sort();
+
+        self.batches.push(batch);
+        event_loop.shutdown();
+    }
+}
+
+#[test]
+pub fn test_batched_timeouts() {
+    let mut event_loop = EventLoop::new().unwrap();
+    let mut handler = BatchHandler { batches: vec![] };
+
+    for i in 0..3 {
+        event_loop.timeout_ms(i, 50).unwrap();
+    }
+
+    event_loop.run(&mut handler).unwrap();
+
+    assert_eq!(handler.batches, vec![vec![0, 1, 2]]);
+}
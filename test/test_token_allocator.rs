@@ -0,0 +1,36 @@
+use mio::Token;
+use mio::util::TokenAllocator;
+use std::usize;
+
+#[test]
+pub fn test_stale_tokens() {
+    let mut tokens = TokenAllocator::new();
+
+    let a = tokens.allocate();
+    let b = tokens.allocate();
+    assert_eq!(TokenAllocator::index(a), 0);
+    assert_eq!(TokenAllocator::index(b), 1);
+    assert_eq!(tokens.len(), 2);
+
+    assert!(tokens.release(a));
+    assert!(!tokens.release(a));
+    assert!(!tokens.is_live(a));
+
+    // The slot is reused under a new generation
+    let c = tokens.allocate();
+    assert_eq!(TokenAllocator::index(c), 0);
+    assert_eq!(TokenAllocator::generation(c), 1);
+    assert!(a != c);
+    assert!(tokens.is_live(c));
+    assert!(!tokens.is_live(a));
+    assert!(tokens.is_live(b));
+}
+
+#[test]
+pub fn test_unknown_tokens() {
+    let tokens = TokenAllocator::with_capacity(16);
+
+    assert!(tokens.is_empty());
+    assert!(!tokens.is_live(Token(0)));
+    assert!(!tokens.is_live(Token(usize::MAX)));
+}
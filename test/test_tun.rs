@@ -0,0 +1,30 @@
+use mio::unix::TunDevice;
+use std::io::ErrorKind;
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_tun_invalid_name() {
+    let name: String = (0..32).map(|_| 'x').collect();
+    assert_eq!(TunDevice::tun(&name).unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(not(target_os = "linux"))]
+pub fn test_tun_invalid_name() {
+    assert_eq!(TunDevice::tun("eth0").unwrap_err().kind(), ErrorKind::InvalidInput);
+    assert_eq!(TunDevice::tun("tun0/../x").unwrap_err().kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_tun_open() {
+    let dev = match TunDevice::tun("") {
+        Ok(dev) => dev,
+        // Creating devices needs CAP_NET_ADMIN and /dev/net/tun
+        Err(ref e) if e.kind() == ErrorKind::PermissionDenied || e.kind() == ErrorKind::NotFound => return,
+        Err(e) => panic!("failed to open a TUN device; err={:?}", e),
+    };
+
+    // The kernel picked the name
+    assert!(dev.name().starts_with("tun"));
+}
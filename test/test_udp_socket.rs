@@ -80,3 +80,60 @@ pub fn test_udp_socket() {
     info!("Starting event loop to test with...");
     event_loop.run(&mut UdpHandler::new(tx, rx, "hello world")).unwrap();
 }
+
+#[test]
+pub fn test_udp_socket_into_raw_fd() {
+    use std::os::unix::io::{FromRawFd, IntoRawFd};
+
+    let sock = UdpSocket::bound(&localhost()).unwrap();
+    let addr = sock.local_addr().unwrap();
+
+    // The fd must survive the conversion without being closed
+    let fd = sock.into_raw_fd();
+    let sock = unsafe { UdpSocket::from_raw_fd(fd) };
+
+    assert_eq!(sock.local_addr().unwrap(), addr);
+}
+
+#[test]
+pub fn test_udp_socket_debug() {
+    use std::os::unix::io::AsRawFd;
+
+    let addr = localhost();
+    let sock = UdpSocket::bound(&addr).unwrap();
+
+    assert_eq!(format!("{:?}", sock), format!("UdpSocket {{ fd: {}, local: {} }}", sock.as_raw_fd(), addr));
+    assert_eq!(format!("{:?}", EventSet::readable() | EventSet::hup()), "Readable | Hup");
+    assert_eq!(format!("{:?}", EventSet::none()), "(empty)");
+}
+
+#[test]
+pub fn test_udp_socket_recv_ttl() {
+    use bytes::ByteBuf;
+    use super::sleep_ms;
+
+    let tx = UdpSocket::bound(&localhost()).unwrap();
+    let rx = UdpSocket::bound(&localhost()).unwrap();
+    rx.set_recv_ttl(true).unwrap();
+
+    let mut buf = SliceBuf::wrap(b"ttl");
+    tx.send_to(&mut buf, &rx.local_addr().unwrap()).unwrap().unwrap();
+
+    let mut rx_buf = ByteBuf::mut_with_capacity(16);
+
+    let (addr, ttl);
+
+    loop {
+        if let Some((a, t)) = rx.recv_from_ttl(&mut rx_buf).unwrap() {
+            addr = a;
+            ttl = t;
+            break;
+        }
+
+        sleep_ms(10);
+    }
+
+    assert_eq!(addr, tx.local_addr().unwrap());
+    assert!(ttl.unwrap() > 0);
+    assert_eq!(rx_buf.flip().bytes(), b"ttl");
+}
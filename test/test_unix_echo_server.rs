@@ -31,7 +31,7 @@ impl EchoConn {
     fn writable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         let mut buf = self.buf.take().unwrap();
 
-        match self.sock.try_write_buf(&mut buf) {
+        match self.sock.try_write_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 debug!("client flushing buf; WOULDBLOCK");
 
@@ -54,7 +54,7 @@ impl EchoConn {
     fn readable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         let mut buf = self.mut_buf.take().unwrap();
 
-        match self.sock.try_read_buf(&mut buf) {
+        match self.sock.try_read_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 panic!("We just got readable, but were unable to read from the socket?");
             }
@@ -146,7 +146,7 @@ impl EchoClient {
 
         let mut buf = self.mut_buf.take().unwrap();
 
-        match self.sock.try_read_buf(&mut buf) {
+        match self.sock.try_read_buf(&mut buf).map_non_block() {
             Ok(None) => {
                 panic!("We just got readable, but were unable to read from the socket?");
             }
@@ -183,7 +183,7 @@ impl EchoClient {
     fn writable(&mut self, event_loop: &mut EventLoop<Echo>) -> io::Result<()> {
         debug!("client socket writable");
 
-        match self.sock.try_write_buf(&mut self.tx) {
+        match self.sock.try_write_buf(&mut self.tx).map_non_block() {
             Ok(None) => {
                 debug!("client flushing buf; WOULDBLOCK");
                 self.interest.insert(EventSet::writable());
@@ -277,3 +277,64 @@ pub fn test_unix_echo_server() {
     // Start the event loop
     event_loop.run(&mut Echo::new(srv, sock, vec!["foo", "bar"])).unwrap();
 }
+
+#[test]
+pub fn test_unix_stream_pair() {
+    let (mut a, mut b) = UnixStream::pair().unwrap();
+    let mut poll = Poll::new().unwrap();
+
+    poll.register(&b, CLIENT, EventSet::readable(), PollOpt::edge()).unwrap();
+    poll.register(&a, SERVER, EventSet::writable(), PollOpt::level()).unwrap();
+
+    let mut buf = [0; 1_024];
+    assert_eq!(b.try_read(&mut buf).unwrap_err().kind(), io::ErrorKind::WouldBlock);
+
+    a.try_write(b"hello").unwrap();
+    assert!(poll.poll(1_000).unwrap() >= 1);
+    assert_eq!(b.try_read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    // A bounded buffer fills up until the peer reads
+    a.set_send_buffer_size(4_096).unwrap();
+
+    let mut sent = 0;
+
+    loop {
+        match a.try_write(&buf) {
+            Ok(n) => sent += n,
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            Err(e) => panic!("write failed; err={:?}", e),
+        }
+    }
+
+    assert!(sent > 0);
+    poll.poll(0).unwrap();
+    assert!(poll.events().all(|evt| evt.token != SERVER));
+
+    while sent > 0 {
+        sent -= b.try_read(&mut buf).unwrap();
+    }
+
+    assert_eq!(poll.poll(1_000).unwrap(), 1);
+    assert_eq!(poll.event(0).token, SERVER);
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+pub fn test_peer_security_context() {
+    use libc;
+
+    let tmp_dir = TempDir::new("mio").unwrap();
+    let addr = tmp_dir.path().join(&PathBuf::from("sock"));
+
+    let _srv = UnixListener::bind(&addr).unwrap();
+    let sock = UnixStream::connect(&addr).unwrap();
+
+    match sock.peer_security_context() {
+        // e.g. "unconfined" under AppArmor
+        Ok(label) => assert!(!label.is_empty()),
+        // No LSM providing labels is loaded
+        Err(ref e) if e.raw_os_error() == Some(libc::ENOPROTOOPT) => {}
+        Err(e) => panic!("peer_security_context failed: {}", e),
+    }
+}
@@ -0,0 +1,114 @@
+use mio::{MapNonBlock, ReadVectored, TryRead, TryWrite, WriteVectored};
+use mio::unix::pipe;
+use bytes::{Buf, ByteBuf, SliceBuf};
+use super::tcp_pair;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+pub fn test_pipe_vectored() {
+    let (mut rd, mut wr) = pipe().unwrap();
+
+    let mut src = [SliceBuf::wrap(b"head"), SliceBuf::wrap(b"body")];
+    assert_eq!(wr.try_write_bufs(&mut src).unwrap(), 8);
+    assert!(!src[0].has_remaining());
+    assert!(!src[1].has_remaining());
+
+    let mut a = [0; 3];
+    let mut b = [0; 8];
+    assert_eq!(rd.try_read_vectored(&mut [&mut a[..], &mut b[..]]).unwrap(), 8);
+    assert_eq!(&a, b"hea");
+    assert_eq!(&b[..5], b"dbody");
+
+    // Nothing left to read
+    let mut dst = [ByteBuf::mut_with_capacity(4)];
+    assert_eq!(rd.try_read_bufs(&mut dst).map_non_block().unwrap(), None);
+}
+
+#[test]
+pub fn test_buf_list() {
+    use mio::util::BufList;
+
+    let (mut rd, mut wr) = pipe().unwrap();
+
+    let mut list = BufList::new();
+    list.push(SliceBuf::wrap(b"head"));
+    list.push(SliceBuf::wrap(b""));
+    list.push(SliceBuf::wrap(b"body"));
+    assert_eq!(list.len(), 2);
+    assert_eq!(list.remaining(), 8);
+    assert_eq!(list.bytes(), b"head");
+
+    list.advance(5);
+    assert_eq!(list.len(), 1);
+    assert_eq!(list.bytes(), b"ody");
+
+    list.push(SliceBuf::wrap(b"tail"));
+    assert_eq!(list.write_to(&mut wr).unwrap(), 7);
+    assert!(list.is_empty());
+    assert!(!list.has_remaining());
+
+    let mut dst = [0; 16];
+    assert_eq!(rd.try_read(&mut dst).unwrap(), 7);
+    assert_eq!(&dst[..7], b"odytail");
+}
+
+#[test]
+pub fn test_tcp_vectored() {
+    let (mut client, mut server) = tcp_pair();
+
+    assert_eq!(client.write_bufs(&[&b"head"[..], &b""[..], &b"body"[..]]).unwrap(), 8);
+
+    let mut a = [0; 5];
+    let mut b = [0; 8];
+    let mut cnt = 0;
+
+    while cnt < 8 {
+        let (a, b) = if cnt < 5 {
+            (&mut a[cnt..], &mut b[..])
+        } else {
+            (&mut a[5..], &mut b[cnt - 5..])
+        };
+
+        match server.read_bufs(&mut [a, b]).map_non_block().unwrap() {
+            Some(n) => cnt += n,
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+
+    assert_eq!(&a, b"headb");
+    assert_eq!(&b[..3], b"ody");
+}
+
+#[test]
+pub fn test_buf_list_tcp() {
+    use mio::util::BufList;
+    use std::io::ErrorKind;
+
+    let (mut client, mut server) = tcp_pair();
+
+    let mut list = BufList::new();
+    list.push(SliceBuf::wrap(b"head"));
+    list.push(SliceBuf::wrap(b"body"));
+
+    // Drain the list, waiting for the socket to take it all
+    while !list.is_empty() {
+        match list.write_to(&mut client) {
+            Ok(_) => {}
+            Err(ref e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(Duration::from_millis(10)),
+            Err(e) => panic!("write failed; err={:?}", e),
+        }
+    }
+
+    let mut dst = Vec::new();
+    let mut buf = [0; 16];
+
+    while dst.len() < 8 {
+        match server.try_read(&mut buf).map_non_block().unwrap() {
+            Some(n) => dst.extend_from_slice(&buf[..n]),
+            None => thread::sleep(Duration::from_millis(10)),
+        }
+    }
+
+    assert_eq!(&dst[..], b"headbody");
+}
@@ -0,0 +1,23 @@
+use mio::unix::{VsockAddr, VsockListener, VMADDR_CID_ANY};
+use libc;
+
+const VMADDR_PORT_ANY: u32 = 0xffffffff;
+
+#[test]
+pub fn test_vsock_bind() {
+    let addr = VsockAddr::new(VMADDR_CID_ANY, VMADDR_PORT_ANY);
+
+    let listener = match VsockListener::bind(&addr) {
+        Ok(listener) => listener,
+        // Hosts without the vsock module or a transport
+        Err(ref e) if [libc::EAFNOSUPPORT, libc::EADDRNOTAVAIL, libc::ENODEV]
+            .contains(&e.raw_os_error().unwrap_or(0)) => return,
+        Err(e) => panic!("failed to bind a vsock listener; err={:?}", e),
+    };
+
+    // The kernel picked a port
+    assert!(listener.local_addr().unwrap().port != VMADDR_PORT_ANY);
+
+    // Nothing is waiting to be accepted
+    assert!(listener.accept().unwrap().is_none());
+}
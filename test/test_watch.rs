@@ -0,0 +1,51 @@
+use mio::{EventSet, Poll, PollOpt, Token};
+use mio::unix::{FileWatch, ProcWatch};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tempdir::TempDir;
+
+#[test]
+pub fn test_file_watch() {
+    let dir = TempDir::new("mio").unwrap();
+    let path = dir.path().join("watched");
+    let mut file = OpenOptions::new().write(true).create(true).open(&path).unwrap();
+
+    let watch = FileWatch::new(&path).unwrap();
+
+    let evts = watch.poll().unwrap();
+    assert!(evts.is_empty());
+    assert_eq!(format!("{:?}", evts), "(empty)");
+
+    file.write_all(b"change").unwrap();
+
+    let evts = watch.poll().unwrap();
+    assert!(evts.is_write());
+
+    // Pending changes are cleared by `poll`
+    assert!(watch.poll().unwrap().is_empty());
+}
+
+#[test]
+pub fn test_proc_watch_exit() {
+    // `cat` runs until its stdin is closed, so the watch is set up in time
+    let mut child = Command::new("cat").stdin(Stdio::piped()).spawn().unwrap();
+
+    let watch = ProcWatch::new(child.id() as i32).unwrap();
+    assert_eq!(format!("{:?}", watch.poll().unwrap()), "(empty)");
+
+    let mut poll = Poll::new().unwrap();
+    poll.register(&watch, Token(0), EventSet::readable(), PollOpt::edge()).unwrap();
+
+    drop(child.stdin.take());
+
+    let mut evts = watch.poll().unwrap();
+
+    while !evts.is_exit() {
+        poll.poll(1_000).unwrap();
+        evts = watch.poll().unwrap();
+    }
+
+    assert!(!evts.is_fork());
+    child.wait().unwrap();
+}
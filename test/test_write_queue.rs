@@ -0,0 +1,36 @@
+use mio::EventSet;
+use mio::util::{MockStream, WriteQueue};
+use bytes::SliceBuf;
+
+#[test]
+pub fn test_write_queue_partial_flush() {
+    let mut dst = MockStream::new();
+    let mut queue = WriteQueue::new();
+
+    // Room for seven bytes, over two writes
+    dst.push_write(7);
+    dst.push_write(1);
+
+    assert_eq!(queue.interest(), EventSet::none());
+
+    queue.push(SliceBuf::wrap(b"hello "));
+    queue.push(SliceBuf::wrap(b""));
+    queue.push(SliceBuf::wrap(b"world"));
+
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.remaining(), 11);
+
+    // The first buffer and part of the second go out
+    assert!(!queue.flush(&mut dst).unwrap());
+    assert_eq!(dst.written(), b"hello w");
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue.remaining(), 4);
+    assert_eq!(queue.interest(), EventSet::writable());
+
+    dst.push_write(1024);
+
+    assert!(queue.flush(&mut dst).unwrap());
+    assert_eq!(dst.written(), b"hello world");
+    assert!(queue.is_empty());
+    assert_eq!(queue.interest(), EventSet::none());
+}